@@ -36,6 +36,60 @@ pub struct SecurityConfig {
     pub encryption_key_rotation_days: u32,
     pub auto_lock_timeout_minutes: u32,
     pub require_confirmation_for_deletion: bool,
+    /// Local Have I Been Pwned password file (or directory of range files) for the audit
+    #[serde(default)]
+    pub hibp_range_path: Option<String>,
+    /// Previous passwords kept per entry (0 keeps none)
+    #[serde(default = "default_password_history_depth")]
+    pub password_history_depth: usize,
+    /// Seconds before a copied password is cleared from the clipboard (0 leaves it)
+    #[serde(default = "default_clipboard_clear_seconds")]
+    pub clipboard_clear_seconds: u64,
+    /// Directory holding the named password vaults
+    #[serde(default = "default_vault_dir")]
+    pub vault_dir: String,
+    /// Failed unlock attempts in a row before `unlock_failure_action` applies (0 disables it)
+    #[serde(default)]
+    pub max_unlock_attempts: u32,
+    #[serde(default)]
+    pub unlock_failure_action: UnlockFailureAction,
+    /// How long a `lockout` blocks unlocking
+    #[serde(default = "default_lockout_minutes")]
+    pub lockout_minutes: u64,
+    /// Encrypted copies of each vault kept before it is rewritten (0 disables snapshots)
+    #[serde(default = "default_vault_snapshots")]
+    pub vault_snapshots: usize,
+}
+
+/// What happens once a vault reaches `max_unlock_attempts` failed unlocks
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnlockFailureAction {
+    /// Refuse further attempts for `lockout_minutes`
+    #[default]
+    Lockout,
+    /// Delete the vault file; only for vaults that are backed up elsewhere
+    Wipe,
+}
+
+fn default_password_history_depth() -> usize {
+    10
+}
+
+fn default_clipboard_clear_seconds() -> u64 {
+    30
+}
+
+fn default_vault_dir() -> String {
+    "vaults".to_string()
+}
+
+fn default_lockout_minutes() -> u64 {
+    15
+}
+
+fn default_vault_snapshots() -> usize {
+    10
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +107,16 @@ pub struct NetworkConfig {
     pub max_retries: u32,
     pub user_agent: String,
     pub speed_test_duration_seconds: u64,
+    /// Time between probes of a continuous ping
+    #[serde(default = "default_ping_interval_ms")]
+    pub ping_interval_ms: u64,
+    /// Probes a continuous ping sends (0 runs until stopped)
+    #[serde(default)]
+    pub ping_count: u16,
+}
+
+fn default_ping_interval_ms() -> u64 {
+    1000
 }
 
 impl Default for AppConfig {
@@ -78,6 +142,14 @@ impl Default for AppConfig {
                 encryption_key_rotation_days: 90,
                 auto_lock_timeout_minutes: 30,
                 require_confirmation_for_deletion: true,
+                hibp_range_path: None,
+                password_history_depth: default_password_history_depth(),
+                clipboard_clear_seconds: default_clipboard_clear_seconds(),
+                vault_dir: default_vault_dir(),
+                max_unlock_attempts: 0,
+                unlock_failure_action: UnlockFailureAction::default(),
+                lockout_minutes: default_lockout_minutes(),
+                vault_snapshots: default_vault_snapshots(),
             },
             ui: UiConfig {
                 theme: "dark".to_string(),
@@ -91,6 +163,8 @@ impl Default for AppConfig {
                 max_retries: 3,
                 user_agent: "Toolbox/1.0".to_string(),
                 speed_test_duration_seconds: 10,
+                ping_interval_ms: default_ping_interval_ms(),
+                ping_count: 0,
             },
         }
     }
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Local, Utc};
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
+use std::fs::OpenOptions;
 use std::io::{self, Write};
 use std::path::Path;
 
@@ -12,6 +12,18 @@ pub enum LogLevel {
     Error,
 }
 
+impl LogLevel {
+    /// Parses the `logging.level` config value, falling back to `Info`
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "debug" => LogLevel::Debug,
+            "warning" | "warn" => LogLevel::Warning,
+            "error" => LogLevel::Error,
+            _ => LogLevel::Info,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub timestamp: i64,
@@ -239,27 +251,27 @@ pub fn log_error_with_metadata(module: &str, message: &str, metadata: serde_json
 #[macro_export]
 macro_rules! log_debug {
     ($module:expr, $($arg:tt)*) => {
-        $crate::logger::log_debug($module, &format!($($arg)*))
+        $crate::core::logging::log_debug($module, &format!($($arg)*))
     };
 }
 
 #[macro_export]
 macro_rules! log_info {
     ($module:expr, $($arg:tt)*) => {
-        $crate::logger::log_info($module, &format!($($arg)*))
+        $crate::core::logging::log_info($module, &format!($($arg)*))
     };
 }
 
 #[macro_export]
 macro_rules! log_warning {
     ($module:expr, $($arg:tt)*) => {
-        $crate::logger::log_warning($module, &format!($($arg)*))
+        $crate::core::logging::log_warning($module, &format!($($arg)*))
     };
 }
 
 #[macro_export]
 macro_rules! log_error {
     ($module:expr, $($arg:tt)*) => {
-        $crate::logger::log_error($module, &format!($($arg)*))
+        $crate::core::logging::log_error($module, &format!($($arg)*))
     };
 } 
\ No newline at end of file
@@ -1,8 +1,7 @@
 pub mod config;
+pub mod secret;
 pub mod logging;
-pub mod backup;
 
 // Re-export for convenience
 pub use config::*;
-pub use logging::*;
-pub use backup::*; 
\ No newline at end of file
+pub use secret::SecretString;
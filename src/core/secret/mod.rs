@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Deref;
+use zeroize::Zeroize;
+
+/// A string holding a password or other secret
+///
+/// The buffer is overwritten with zeros when the value is dropped or cleared, and
+/// `Debug` never prints it. Only appending methods that wipe the old buffer on
+/// reallocation are offered, so no stale copy is left behind while a secret is typed.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn push(&mut self, c: char) {
+        if self.0.len() + c.len_utf8() > self.0.capacity() {
+            // Grow into a fresh buffer ourselves; String would free the old one unwiped
+            let mut grown = String::with_capacity((self.0.capacity() * 2).max(32));
+            grown.push_str(&self.0);
+            self.0.zeroize();
+            self.0 = grown;
+        }
+        self.0.push(c);
+    }
+
+    pub fn pop(&mut self) -> Option<char> {
+        let c = self.0.chars().next_back()?;
+        let mut bytes = std::mem::take(&mut self.0).into_bytes();
+        let len = bytes.len() - c.len_utf8();
+        // Wipe the removed bytes, which would otherwise stay in the spare capacity
+        bytes[len..].zeroize();
+        bytes.truncate(len);
+        self.0 = String::from_utf8(bytes).expect("truncated at a char boundary");
+        Some(c)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl Deref for SecretString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl PartialEq<str> for SecretString {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for SecretString {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<redacted>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_string_edits_and_redacts() {
+        let mut secret = SecretString::new();
+        for c in "hunter2é".chars() {
+            secret.push(c);
+        }
+        assert_eq!(secret.pop(), Some('é'));
+        assert_eq!(secret, "hunter2");
+        assert_eq!(format!("{:?}", secret), "<redacted>");
+
+        let json = serde_json::to_string(&secret).unwrap();
+        assert_eq!(json, "\"hunter2\"");
+        assert_eq!(serde_json::from_str::<SecretString>(&json).unwrap(), secret);
+
+        secret.clear();
+        assert!(secret.is_empty());
+    }
+}
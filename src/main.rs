@@ -16,6 +16,7 @@
 //! It follows an event-driven architecture with different operational modes for each feature.
 
 // Module imports
+mod core;
 mod modules;
 
 // Crate list
@@ -26,9 +27,48 @@ use ratatui::crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use crate::modules::network_tools::model::{ping, SpeedTestResult};
+use crate::modules::network_tools::model::dns::{
+    compare_resolvers, parse_resolver, system_resolvers, RecordType, ResolverAnswer, QUERY_TIMEOUT,
+};
+use crate::modules::network_tools::model::ping::{ping_continuous, PingEvent, PingOptions};
+use crate::modules::network_tools::model::traceroute::{
+    traceroute, Hop, HopStatus, TraceEvent, TraceOptions,
+};
+use crate::modules::network_tools::model::tcp::{
+    check_ports, parse_targets, tcp_ping, PortCheck, PortState, CONNECT_TIMEOUT,
+};
+use crate::modules::network_tools::model::{ping, PingResult, SpeedTestResult};
 use once_cell::sync::Lazy;
-use crate::modules::password_manager::model::{save_password, PasswordEntry};
+use crate::modules::password_manager::model::{
+    all_tags, find_duplicate_service, format_custom_fields, parse_custom_fields, parse_tags,
+    vault_status, EntryKind, PasswordEntry, Vault, VaultError, VaultStatus, FILE_PATH,
+};
+use crate::modules::password_manager::model::generator::{
+    self, GeneratorMode, GeneratorOptions,
+};
+use crate::modules::password_manager::model::audit::{audit, AuditReport};
+use crate::modules::password_manager::model::clipboard::ClipboardCopy;
+use crate::modules::password_manager::model::export::{export_csv, export_portable, ExportFormat};
+use crate::modules::password_manager::model::integrity::{
+    check_vault, restore_snapshot, FileCheck, VaultCheck,
+};
+use crate::modules::password_manager::model::lockout::{wipe_vault, UnlockAttempts};
+use crate::modules::password_manager::model::import::{
+    CsvField, ImportError, ImportFormat, ImportSession,
+};
+use crate::modules::password_manager::model::search::{search_entries, SearchField, SearchHit};
+use crate::modules::password_manager::model::share::{open_shared_entry, share_entry, Recipient};
+use crate::modules::password_manager::model::ssh::{
+    load_private_key, KeyCommand, KEY_PLACEHOLDER,
+};
+use crate::modules::password_manager::model::totp::TotpConfig;
+use crate::modules::password_manager::model::vaults::{
+    adopt_legacy_vault, create_vault_dir, list_vaults, validate_vault_name, vault_path,
+};
+use crate::core::logging::{init_logger, log_error, log_info, log_warning, LogLevel};
+use crate::core::{
+    get_config, init_config, AppConfig, SecretString, SecurityConfig, UnlockFailureAction,
+};
 use signal_hook::consts::SIGINT;
 use signal_hook::flag;
 use std::io;
@@ -47,23 +87,10 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Table},
+    widgets::{Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Sparkline, Table, Wrap},
     Frame, Terminal,
 };
 
-/// Results from a ping network test
-#[derive(serde::Deserialize, Debug)]
-struct PingResult {
-    packets_transmitted: u32,
-    packets_received: u32,
-    packet_loss: f32,
-    time: Option<u32>,
-    round_trip_min: f32,
-    round_trip_avg: f32,
-    round_trip_max: f32,
-    round_trip_mdev: f32,
-}
-
 /// Represents the different input modes of the application
 #[derive(Debug, Clone, PartialEq)]
 enum InputMode {
@@ -79,6 +106,12 @@ enum InputMode {
     ViewResults,
     /// Running a speed test (in progress)
     SpeedTestRunning,
+    /// Watching a continuous ping's latency chart and statistics
+    ContinuousPing,
+    /// Watching the hops of a traceroute come in
+    Traceroute,
+    /// Comparing the answers of one or more resolvers to a DNS query
+    DnsLookup,
     /// Adding a new task
     AddingTask,
     /// Editing an existing task
@@ -89,6 +122,32 @@ enum InputMode {
     AddingReminder,
     /// Configuring email settings
     ConfiguringEmail,
+    /// Entering the master password to unlock (or create) the password vault
+    Unlocking,
+    /// Entering the path of a password export to import
+    ImportPath,
+    /// Reviewing imported entries before they are written to the vault
+    ImportPreview,
+    /// Choosing the file and format for a password export
+    Exporting,
+    /// Reviewing the password health report
+    SecurityAudit,
+    /// Browsing the previous passwords of the selected entry
+    PasswordHistory,
+    /// Typing a fuzzy search over the password list
+    PasswordSearch,
+    /// Choosing (or naming a new) password vault to open
+    VaultPicker,
+    /// Changing the master password, which re-encrypts the vault under a new key
+    ChangingMasterPassword,
+    /// Running a command with the selected SSH key written to a temporary file
+    SshCommand,
+    /// Encrypting the selected entry to a teammate's public key
+    SharingEntry,
+    /// Importing an entry that was shared to this vault's public key
+    ReceivingShare,
+    /// Integrity check of the vault, with its snapshots to restore from
+    VaultCheck,
 }
 
 /// Main menu items available in the application
@@ -134,6 +193,12 @@ enum ConfirmationDialogue {
     None,
     /// Confirmation to kill a process (PID, process name)
     KillProcess(u32, String),
+    /// Confirmation to delete a password entry (index, service name)
+    DeletePassword(usize, String),
+    /// Confirmation to write every password to an unencrypted CSV file (path)
+    ExportCsv(String),
+    /// Confirmation to put a snapshot back in place of the vault (index, time taken)
+    RestoreSnapshot(usize, String),
 }
 
 /// Process sorting options
@@ -160,6 +225,8 @@ enum NotificationSeverity {
     Error,
     /// Yellow — recoverable failure or degraded state
     Warning,
+    /// Green — an action completed successfully
+    Info,
 }
 
 /// A transient overlay notification displayed in the bottom-right corner
@@ -188,6 +255,12 @@ impl Notification {
 /// During the grace period (< 3 failures) the last known good snapshot is shown.
 const SYS_FAIL_THRESHOLD: u8 = 3;
 
+/// How long a revealed password stays in cleartext in the password list
+const PASSWORD_REVEAL_DURATION: Duration = Duration::from_secs(10);
+
+/// Shown in place of every password that is not revealed
+const PASSWORD_MASK: &str = "••••••••";
+
 /// Central application state structure
 ///
 /// This structure holds all the state information for the application, including:
@@ -211,9 +284,98 @@ struct AppState {
     /// Username for password entry
     username: String,
     /// Password for password entry
-    password: String,
-    /// Current field being edited in password manager (0-2)
+    password: SecretString,
+    /// Current field being edited in password manager (0-8, the last being the generator)
     input_field: usize,
+    /// Unlocked password vault (None while locked)
+    vault: Option<Vault>,
+    /// State of the vault file, decides what the unlock screen asks for
+    vault_status: VaultStatus,
+    /// Name of the vault being unlocked or open
+    active_vault: Option<String>,
+    /// Vaults found in the vault directory, for the picker
+    vault_names: Vec<String>,
+    /// Index of the selected row in the vault picker
+    selected_vault_index: usize,
+    /// Name typed for a new vault (None while only picking)
+    new_vault_name: Option<String>,
+    /// Master password typed on the unlock screen
+    master_password: SecretString,
+    /// Confirmation of a new master password (create/migrate/change)
+    master_password_confirm: SecretString,
+    /// Replacement master password typed when changing it
+    new_master_password: SecretString,
+    /// Decrypted entries shown in the password list (reloaded after every change)
+    password_entries: Vec<PasswordEntry>,
+    /// Index of the selected row in the password list
+    selected_password_index: usize,
+    /// Entry being edited in the input modal (None = adding a new entry)
+    editing_password_index: Option<usize>,
+    /// True once the user has been warned that the service already has an entry
+    duplicate_service_confirmed: bool,
+    /// Password list row currently shown in cleartext, and when it was revealed
+    revealed_password: Option<(usize, Instant)>,
+    /// Time of the last key press, used for the vault auto-lock
+    last_activity: Instant,
+    /// Value copied from the vault and when, so it can be cleared again
+    clipboard: Option<(ClipboardCopy, Instant)>,
+    /// Path typed on the import screen
+    import_path: String,
+    /// Public key (`age1...`) the selected entry is shared to
+    share_recipient: String,
+    /// File a shared entry is written to or read from
+    share_path: String,
+    /// This vault's public key, shown on the receive screen
+    sharing_public_key: Option<String>,
+    /// Result of the last vault integrity check
+    vault_check: Option<VaultCheck>,
+    selected_snapshot_index: usize,
+    /// Export being previewed before import
+    import_session: Option<ImportSession>,
+    /// Selected row in the import preview
+    selected_import_index: usize,
+    /// Password typed for an encrypted (portable vault) import
+    import_password: SecretString,
+    /// True once the import file turned out to be an encrypted vault
+    import_password_needed: bool,
+    /// Selected row in the password history pane
+    selected_history_index: usize,
+    /// Latest password health report
+    audit_report: Option<AuditReport>,
+    /// Selected row in the audit report
+    selected_audit_index: usize,
+    /// Destination, format and password of the export screen
+    export_path: String,
+    export_format: ExportFormat,
+    export_password: SecretString,
+    export_password_confirm: SecretString,
+    /// TOTP field of the add/edit modal: base32 secret or otpauth:// URI
+    totp_input: SecretString,
+    /// Optional fields of the add/edit modal; tags are comma-separated and custom
+    /// fields are `name=value` lines
+    url_input: String,
+    notes_input: SecretString,
+    tags_input: String,
+    custom_fields_input: SecretString,
+    /// Kind of item the add/edit form creates
+    entry_kind: EntryKind,
+    /// Private key pasted into the form, or the path of a key file
+    private_key_input: SecretString,
+    public_key_input: String,
+    /// Command typed for the selected SSH key, with `{key}` for the key file
+    ssh_command_input: String,
+    /// The running SSH key command; dropping it kills the command and removes the key file
+    ssh_command: Option<KeyCommand>,
+    /// Output of the last SSH key command
+    ssh_command_output: Option<String>,
+    /// Only entries with this tag (or a tag nested below it) are listed
+    tag_filter: Option<String>,
+    /// Fuzzy search typed over the password list; ranks and filters it while non-empty
+    search_query: String,
+    /// Settings of the generator panel in the add/edit modal
+    generator_options: GeneratorOptions,
+    /// Entropy estimate of the generated password currently in the password field
+    generated_entropy: Option<f64>,
 
     /// Error message to display to the user
     error_message: Option<String>,
@@ -227,6 +389,38 @@ struct AppState {
     selected_tool: Option<String>,
     /// Channel receiver for asynchronous speed test results
     speed_test_receiver: Option<Receiver<crate::modules::network_tools::model::SpeedTestResult>>,
+    /// Result of the TCP ping running in the background
+    tcp_ping_receiver: Option<Receiver<Result<PingResult, String>>>,
+    /// Results of the port check running in the background
+    port_check_receiver: Option<Receiver<Vec<PortCheck>>>,
+    /// Seconds between probes typed for a continuous ping
+    ping_interval_input: String,
+    /// Probes to send typed for a continuous ping (0 runs until stopped)
+    ping_count_input: String,
+    /// Replies streamed by the running continuous ping
+    ping_receiver: Option<Receiver<PingEvent>>,
+    /// Tells the continuous ping thread to stop
+    ping_stop: Arc<AtomicBool>,
+    /// Replies and statistics of the current continuous ping
+    live_ping: Option<PingResult>,
+    /// When the current continuous ping was started
+    live_ping_started: Instant,
+    /// Hops streamed by the running traceroute
+    trace_receiver: Option<Receiver<TraceEvent>>,
+    /// Tells the traceroute thread to stop
+    trace_stop: Arc<AtomicBool>,
+    /// Resolved destination and hop limit of the current traceroute
+    trace_target: Option<(std::net::IpAddr, u8)>,
+    /// Hops of the current traceroute so far
+    trace_hops: Vec<Hop>,
+    /// Record type chosen for a DNS lookup
+    dns_record_type: RecordType,
+    /// Resolver addresses typed for a DNS lookup (empty uses the system's)
+    dns_resolvers_input: String,
+    /// Answers of the DNS lookup in progress
+    dns_receiver: Option<Receiver<Vec<ResolverAnswer>>>,
+    /// Each resolver's answer to the last DNS lookup
+    dns_results: Vec<ResolverAnswer>,
 
     // System Utilities fields
     /// System monitor instance for real-time system information
@@ -289,7 +483,7 @@ struct AppState {
     /// Email account username
     email_username: String,
     /// Email account password
-    email_password: String,
+    email_password: SecretString,
     /// Current field being edited in email config (0-4)
     email_config_field: usize,
 
@@ -351,13 +545,77 @@ impl Default for AppState {
             input_mode: InputMode::Normal,
             service: String::new(),
             username: String::new(),
-            password: String::new(),
+            password: SecretString::new(),
             input_field: 0,
+            vault: None,
+            vault_status: VaultStatus::Missing,
+            active_vault: None,
+            vault_names: Vec::new(),
+            selected_vault_index: 0,
+            new_vault_name: None,
+            master_password: SecretString::new(),
+            master_password_confirm: SecretString::new(),
+            new_master_password: SecretString::new(),
+            password_entries: Vec::new(),
+            selected_password_index: 0,
+            editing_password_index: None,
+            duplicate_service_confirmed: false,
+            revealed_password: None,
+            clipboard: None,
+            last_activity: Instant::now(),
+            import_path: String::new(),
+            share_recipient: String::new(),
+            share_path: String::new(),
+            sharing_public_key: None,
+            vault_check: None,
+            selected_snapshot_index: 0,
+            import_session: None,
+            selected_import_index: 0,
+            import_password: SecretString::new(),
+            import_password_needed: false,
+            selected_history_index: 0,
+            audit_report: None,
+            selected_audit_index: 0,
+            export_path: String::new(),
+            export_format: ExportFormat::PortableVault,
+            export_password: SecretString::new(),
+            export_password_confirm: SecretString::new(),
+            totp_input: SecretString::new(),
+            url_input: String::new(),
+            notes_input: SecretString::new(),
+            tags_input: String::new(),
+            custom_fields_input: SecretString::new(),
+            entry_kind: EntryKind::Login,
+            private_key_input: SecretString::new(),
+            public_key_input: String::new(),
+            ssh_command_input: String::new(),
+            ssh_command: None,
+            ssh_command_output: None,
+            tag_filter: None,
+            search_query: String::new(),
+            generator_options: GeneratorOptions::default(),
+            generated_entropy: None,
             error_message: None,
             address: String::new(),
             result: None,
             selected_tool: None,
             speed_test_receiver: None,
+            tcp_ping_receiver: None,
+            port_check_receiver: None,
+            ping_interval_input: String::new(),
+            ping_count_input: String::new(),
+            ping_receiver: None,
+            ping_stop: Arc::new(AtomicBool::new(false)),
+            live_ping: None,
+            live_ping_started: Instant::now(),
+            trace_receiver: None,
+            trace_stop: Arc::new(AtomicBool::new(false)),
+            trace_target: None,
+            trace_hops: Vec::new(),
+            dns_record_type: RecordType::A,
+            dns_resolvers_input: String::new(),
+            dns_receiver: None,
+            dns_results: Vec::new(),
             system_monitor: None,
             selected_system_tool: None,
             system_view_mode: SystemViewMode::Overview,
@@ -387,7 +645,7 @@ impl Default for AppState {
             email_smtp_server: String::new(),
             email_smtp_port: String::from("587"),
             email_username: String::new(),
-            email_password: String::new(),
+            email_password: SecretString::new(),
             email_config_field: 0,
             reminder_date: String::new(),
             reminder_time: String::new(),
@@ -474,6 +732,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }));
 
+    // Load (or create) the application config before the terminal switches to raw mode
+    if let Err(e) = init_config("config.json") {
+        eprintln!("Failed to initialize config: {}", e);
+    }
+    let logging = get_config(|config| config.logging.clone()).unwrap_or_else(|| AppConfig::default().logging);
+    if logging.enabled {
+        // The TUI owns the terminal, so log lines only go to the file
+        init_logger(&logging.file_path, false, LogLevel::from_name(&logging.level));
+    }
+
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -617,6 +885,15 @@ fn run_app<B: Backend>(
         }
 
         check_expired_status(&mut app_state);
+        check_vault_auto_lock(&mut app_state);
+
+        // Re-mask a revealed password once its time is up
+        if let Some((_, revealed_at)) = app_state.revealed_password {
+            if revealed_at.elapsed() >= PASSWORD_REVEAL_DURATION {
+                app_state.revealed_password = None;
+            }
+        }
+        check_clipboard_clear(&mut app_state);
 
         // Auto-dismiss expired notifications
         app_state.notifications.retain(|n| !n.is_expired());
@@ -625,9 +902,27 @@ fn run_app<B: Backend>(
             match app_state.active_menu {
                 MenuItem::Main => draw_main_menu(f),
                 MenuItem::PasswordManager => match app_state.input_mode {
-                    InputMode::Normal => draw_password_manager_menu(f),
+                    InputMode::Normal => draw_password_manager_menu(f, &app_state),
                     InputMode::Editing => draw_input_modal(f, &app_state),
-                    InputMode::Viewing => draw_password_list(f),
+                    InputMode::Viewing | InputMode::PasswordHistory | InputMode::PasswordSearch => {
+                        draw_password_list(f, &app_state)
+                    }
+                    InputMode::Unlocking => draw_unlock_screen(f, &app_state),
+                    InputMode::ImportPath => draw_import_path(f, &app_state),
+                    InputMode::ImportPreview => draw_import_preview(f, &app_state),
+                    InputMode::Exporting => draw_export(f, &app_state),
+                    InputMode::SecurityAudit => draw_security_audit(f, &app_state),
+                    InputMode::VaultPicker => draw_vault_picker(f, &app_state),
+                    InputMode::ChangingMasterPassword => {
+                        draw_change_master_password(f, &app_state)
+                    }
+                    InputMode::SshCommand => {
+                        draw_password_list(f, &app_state);
+                        draw_ssh_command(f, &app_state);
+                    }
+                    InputMode::SharingEntry => draw_share_entry(f, &app_state),
+                    InputMode::ReceivingShare => draw_receive_share(f, &app_state),
+                    InputMode::VaultCheck => draw_vault_check(f, &app_state),
                     _ => {}
                 },
                 MenuItem::NetworkTools => match app_state.input_mode {
@@ -635,6 +930,9 @@ fn run_app<B: Backend>(
                     InputMode::EnterAddress => draw_address_input(f, &app_state),
                     InputMode::ViewResults => draw_view_results(f, &app_state),
                     InputMode::SpeedTestRunning => draw_speed_test(f, &app_state),
+                    InputMode::ContinuousPing => draw_continuous_ping(f, &app_state),
+                    InputMode::Traceroute => draw_traceroute(f, &app_state),
+                    InputMode::DnsLookup => draw_dns_lookup(f, &app_state),
                     _ => {}
                 },
                 MenuItem::SystemUtilities => {
@@ -660,7 +958,23 @@ fn run_app<B: Backend>(
                     InputMode::EnterAddress => {}
                     InputMode::ViewResults => {}
                     InputMode::SpeedTestRunning => {}
+                    InputMode::ContinuousPing => {}
+                    InputMode::Traceroute => {}
+                    InputMode::DnsLookup => {}
                     InputMode::EditingTask => {}
+                    InputMode::Unlocking => {}
+                    InputMode::ImportPath => {}
+                    InputMode::ImportPreview => {}
+                    InputMode::Exporting => {}
+                    InputMode::SecurityAudit => {}
+                    InputMode::PasswordHistory => {}
+                    InputMode::PasswordSearch => {}
+                    InputMode::VaultPicker => {}
+                    InputMode::ChangingMasterPassword => {}
+                    InputMode::SshCommand => {}
+                    InputMode::SharingEntry => {}
+                    InputMode::ReceivingShare => {}
+                    InputMode::VaultCheck => {}
                 },
             }
             render_notifications(f, &app_state.notifications);
@@ -668,6 +982,9 @@ fn run_app<B: Backend>(
 
         if event::poll(Duration::from_millis(10))? {
             if let Ok(event) = event::read() {
+                if let event::Event::Key(_) = event {
+                    app_state.last_activity = Instant::now();
+                }
                 match event {
                     event::Event::Key(KeyEvent { code, .. }) => match app_state.active_menu {
                         MenuItem::Main => handle_normal_mode(&mut app_state, code, &running)?,
@@ -681,6 +998,45 @@ fn run_app<B: Backend>(
                             InputMode::Viewing => {
                                 handle_viewing_mode(&mut app_state, code, &running)?
                             }
+                            InputMode::Unlocking => {
+                                handle_unlocking_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::ImportPath => {
+                                handle_import_path_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::ImportPreview => {
+                                handle_import_preview_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::Exporting => {
+                                handle_export_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::SecurityAudit => {
+                                handle_security_audit_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::PasswordHistory => {
+                                handle_password_history_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::PasswordSearch => {
+                                handle_password_search_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::VaultPicker => {
+                                handle_vault_picker_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::ChangingMasterPassword => {
+                                handle_change_master_password_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::SshCommand => {
+                                handle_ssh_command_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::SharingEntry => {
+                                handle_share_entry_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::ReceivingShare => {
+                                handle_receive_share_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::VaultCheck => {
+                                handle_vault_check_mode(&mut app_state, code, &running)?
+                            }
                             _ => {}
                         },
                         MenuItem::NetworkTools => match app_state.input_mode {
@@ -696,6 +1052,15 @@ fn run_app<B: Backend>(
                             InputMode::SpeedTestRunning => {
                                 handle_speed_test_running_mode(&mut app_state, code, &running)?
                             }
+                            InputMode::ContinuousPing => {
+                                handle_continuous_ping_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::Traceroute => {
+                                handle_traceroute_mode(&mut app_state, code, &running)?
+                            }
+                            InputMode::DnsLookup => {
+                                handle_dns_lookup_mode(&mut app_state, code, &running)?
+                            }
                             _ => {}
                         },
                         MenuItem::SystemUtilities => {
@@ -723,7 +1088,23 @@ fn run_app<B: Backend>(
                             | InputMode::EnterAddress
                             | InputMode::ViewResults
                             | InputMode::SpeedTestRunning
-                            | InputMode::EditingTask => {}
+                            | InputMode::ContinuousPing
+                            | InputMode::Traceroute
+                            | InputMode::DnsLookup
+                            | InputMode::EditingTask
+                            | InputMode::Unlocking
+                            | InputMode::ImportPath
+                            | InputMode::ImportPreview
+                            | InputMode::Exporting
+                            | InputMode::SecurityAudit
+                            | InputMode::PasswordHistory
+                            | InputMode::PasswordSearch
+                            | InputMode::VaultPicker
+                            | InputMode::ChangingMasterPassword
+                            | InputMode::SshCommand
+                            | InputMode::SharingEntry
+                            | InputMode::ReceivingShare
+                            | InputMode::VaultCheck => {}
                         },
                     },
                     _ => {}
@@ -824,6 +1205,99 @@ fn run_app<B: Backend>(
             }
         }
 
+        // Collect the replies of a running continuous ping
+        if let Some(ref rx) = app_state.ping_receiver {
+            loop {
+                match rx.try_recv() {
+                    Ok(PingEvent::Started { address, method }) => {
+                        app_state.live_ping = Some(PingResult::new(address, method));
+                    }
+                    Ok(PingEvent::Reply(reply, method)) => {
+                        if let Some(ref mut live_ping) = app_state.live_ping {
+                            live_ping.record(reply, method, app_state.live_ping_started.elapsed());
+                        }
+                    }
+                    Ok(PingEvent::Failed(e)) => app_state.error_message = Some(e),
+                    Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        // The count was reached or the ping failed
+                        app_state.ping_receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Collect the hops of a running traceroute
+        if let Some(ref rx) = app_state.trace_receiver {
+            loop {
+                match rx.try_recv() {
+                    Ok(TraceEvent::Started { address, max_hops }) => {
+                        app_state.trace_target = Some((address, max_hops));
+                    }
+                    Ok(TraceEvent::Hop(hop)) => app_state.trace_hops.push(hop),
+                    Ok(TraceEvent::Failed(e)) => app_state.error_message = Some(e),
+                    Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                        app_state.trace_receiver = None;
+                        break;
+                    }
+                }
+            }
+        }
+
+        // Pick up the result of a TCP ping
+        if let Some(ref rx) = app_state.tcp_ping_receiver {
+            match rx.try_recv() {
+                Ok(Ok(result)) => {
+                    app_state.result = Some(
+                        serde_json::to_string(&result)
+                            .unwrap_or_else(|_| "Failed to serialize ping result.".to_string()),
+                    );
+                    app_state.tcp_ping_receiver = None;
+                }
+                Ok(Err(e)) => {
+                    app_state.error_message = Some(format!("Error: {}", e));
+                    app_state.result = None;
+                    app_state.input_mode = InputMode::Normal;
+                    app_state.tcp_ping_receiver = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    app_state.tcp_ping_receiver = None;
+                }
+            }
+        }
+
+        // Pick up the results of a port check
+        if let Some(ref rx) = app_state.port_check_receiver {
+            match rx.try_recv() {
+                Ok(checks) => {
+                    app_state.result = Some(
+                        serde_json::to_string(&checks)
+                            .unwrap_or_else(|_| "Failed to serialize port checks.".to_string()),
+                    );
+                    app_state.port_check_receiver = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    app_state.port_check_receiver = None;
+                }
+            }
+        }
+
+        // Pick up the answers of a DNS lookup
+        if let Some(ref rx) = app_state.dns_receiver {
+            match rx.try_recv() {
+                Ok(results) => {
+                    app_state.dns_results = results;
+                    app_state.dns_receiver = None;
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {}
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => app_state.dns_receiver = None,
+            }
+        }
+
         // Poll SMTP test result — extract before notify to avoid borrow conflict
         let smtp_result = app_state
             .smtp_test_receiver
@@ -837,7 +1311,7 @@ fn run_app<B: Backend>(
                 app_state.status_message = None;
                 app_state.push_notification(
                     "SMTP connection successful",
-                    NotificationSeverity::Warning,
+                    NotificationSeverity::Info,
                 );
             }
             Some(Ok(Err(e))) => {
@@ -862,6 +1336,7 @@ fn run_app<B: Backend>(
                 // Still running or not started — keep "Testing SMTP..." status visible
             }
         }
+        check_ssh_command(&mut app_state);
     }
     cleanup_resources(&mut app_state);
 
@@ -882,6 +1357,11 @@ fn handle_normal_mode(
         }
         (KeyCode::Char('1'), MenuItem::Main) => {
             app_state.active_menu = MenuItem::PasswordManager;
+
+            // The vault stays open across menu changes; only ask which vault to open when locked
+            if app_state.vault.is_none() {
+                open_vault_picker(app_state);
+            }
         }
         (KeyCode::Char('2'), MenuItem::Main) => {
             app_state.active_menu = MenuItem::NetworkTools;
@@ -934,18 +1414,109 @@ fn handle_normal_mode(
             app_state.selected_system_tool = Some("disk_analyzer".to_string());
             app_state.system_view_mode = SystemViewMode::DiskDetails;
         }
-        (KeyCode::Char('a'), MenuItem::PasswordManager) => {
+        (KeyCode::Char(key @ ('a' | 'n' | 'y')), MenuItem::PasswordManager) => {
+            // Entries are needed up front for duplicate-service detection
+            refresh_password_entries(app_state);
+            app_state.entry_kind = match key {
+                'n' => EntryKind::SecureNote,
+                'y' => EntryKind::SshKey,
+                _ => EntryKind::Login,
+            };
+            app_state.editing_password_index = None;
             app_state.input_mode = InputMode::Editing;
         }
         (KeyCode::Char('v'), MenuItem::PasswordManager) => {
+            refresh_password_entries(app_state);
             app_state.input_mode = InputMode::Viewing;
         }
+        (KeyCode::Char('/'), MenuItem::PasswordManager) => {
+            refresh_password_entries(app_state);
+            app_state.search_query.clear();
+            app_state.input_mode = InputMode::PasswordSearch;
+        }
+        (KeyCode::Char('i'), MenuItem::PasswordManager) => {
+            // Existing entries are needed to flag duplicates in the preview
+            refresh_password_entries(app_state);
+            app_state.import_path.clear();
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::ImportPath;
+        }
+        (KeyCode::Char('s'), MenuItem::PasswordManager) if app_state.vault.is_some() => {
+            run_security_audit(app_state);
+            app_state.input_mode = InputMode::SecurityAudit;
+        }
+        (KeyCode::Char('x'), MenuItem::PasswordManager) => {
+            refresh_password_entries(app_state);
+            clear_export_form(app_state);
+            app_state.input_mode = InputMode::Exporting;
+        }
+        (KeyCode::Char('k'), MenuItem::PasswordManager) if app_state.vault.is_some() => {
+            clear_master_password_form(app_state);
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::ChangingMasterPassword;
+        }
+        (KeyCode::Char('o'), MenuItem::PasswordManager) => {
+            lock_vault(app_state);
+            open_vault_picker(app_state);
+        }
+        (KeyCode::Char('c'), MenuItem::PasswordManager) => {
+            if let Some(ref vault) = app_state.vault {
+                app_state.vault_check = Some(vault.check());
+                app_state.selected_snapshot_index = 0;
+                app_state.error_message = None;
+                app_state.input_mode = InputMode::VaultCheck;
+            }
+        }
+        (KeyCode::Char('r'), MenuItem::PasswordManager) if app_state.vault.is_some() => {
+            app_state.share_path.clear();
+            app_state.input_field = 0;
+            app_state.error_message = None;
+            app_state.sharing_public_key = None;
+            // The key pair is created the first time this screen is opened
+            let public_key = app_state.vault.as_ref().map(|vault| {
+                vault
+                    .sharing_identity()
+                    .map(|identity| identity.recipient())
+                    .map_err(|e| e.to_string())
+            });
+            match public_key {
+                Some(Ok(recipient)) => app_state.sharing_public_key = Some(recipient.to_string()),
+                Some(Err(e)) => app_state.error_message = Some(e),
+                None => {}
+            }
+            app_state.input_mode = InputMode::ReceivingShare;
+        }
         (KeyCode::Char('p'), MenuItem::NetworkTools) => {
             app_state.selected_tool = Some("ping".to_string());
             app_state.input_mode = InputMode::EnterAddress;
         }
+        (KeyCode::Char('c'), MenuItem::NetworkTools) => {
+            let network = get_config(|config| config.network.clone())
+                .unwrap_or_else(|| AppConfig::default().network);
+            app_state.selected_tool = Some("continuous ping".to_string());
+            app_state.ping_interval_input = format!("{}", network.ping_interval_ms as f64 / 1000.0);
+            app_state.ping_count_input = network.ping_count.to_string();
+            app_state.input_field = 0;
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::EnterAddress;
+        }
+        (KeyCode::Char('o'), MenuItem::NetworkTools) => {
+            app_state.selected_tool = Some("tcp ping".to_string());
+            app_state.input_mode = InputMode::EnterAddress;
+        }
+        (KeyCode::Char('r'), MenuItem::NetworkTools) => {
+            app_state.selected_tool = Some("port check".to_string());
+            app_state.input_mode = InputMode::EnterAddress;
+        }
         (KeyCode::Char('t'), MenuItem::NetworkTools) => {
             app_state.selected_tool = Some("traceroute".to_string());
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::EnterAddress;
+        }
+        (KeyCode::Char('d'), MenuItem::NetworkTools) => {
+            app_state.selected_tool = Some("dns lookup".to_string());
+            app_state.input_field = 0;
+            app_state.error_message = None;
             app_state.input_mode = InputMode::EnterAddress;
         }
         (KeyCode::Char('s'), MenuItem::NetworkTools) => {
@@ -1041,33 +1612,136 @@ fn handle_editing_mode(
 ) -> io::Result<()> {
     match code {
         KeyCode::Esc => {
-            app_state.input_mode = InputMode::Normal;
+            // Cancelling an edit must not leave the entry's values behind for the next add
+            if app_state.editing_password_index.take().is_some() {
+                clear_entry_form(app_state);
+                app_state.input_mode = InputMode::Viewing;
+            } else {
+                app_state.input_mode = InputMode::Normal;
+            }
+            app_state.duplicate_service_confirmed = false;
+            app_state.generated_entropy = None;
             app_state.error_message = None;
         }
         KeyCode::Char('q') => {
             running.store(false, Ordering::Relaxed);
         }
         KeyCode::Tab => {
-            app_state.input_field = (app_state.input_field + 1) % 3;
+            // The last stop is the generator panel
+            app_state.input_field = (app_state.input_field + 1) % 9;
         }
         KeyCode::BackTab => {
-            app_state.input_field = (app_state.input_field + 2) % 3;
+            app_state.input_field = (app_state.input_field + 8) % 9;
+        }
+        // Notes and custom fields span several lines, so Enter there starts a new one
+        KeyCode::Enter if matches!(app_state.input_field, 6 | 7) => {
+            match (app_state.input_field, app_state.entry_kind) {
+                (6, EntryKind::SshKey) => app_state.private_key_input.push('\n'),
+                (6, _) => app_state.notes_input.push('\n'),
+                _ => app_state.custom_fields_input.push('\n'),
+            }
         }
         KeyCode::Enter => {
+            let custom_fields = match parse_custom_fields(&app_state.custom_fields_input) {
+                Ok(fields) => fields,
+                Err(e) => {
+                    app_state.error_message = Some(e);
+                    return Ok(());
+                }
+            };
+            let kind = app_state.entry_kind;
+            // SSH keys use the TOTP and notes slots of the form for their keys
+            let (private_key, public_key) = if kind == EntryKind::SshKey {
+                match load_private_key(&app_state.private_key_input) {
+                    Ok((private_key, from_file)) => {
+                        let typed = app_state.public_key_input.trim();
+                        (private_key, if typed.is_empty() { from_file } else { Some(typed.to_string()) })
+                    }
+                    Err(e) => {
+                        app_state.error_message = Some(format!("Private key: {}", e));
+                        return Ok(());
+                    }
+                }
+            } else {
+                (SecretString::new(), None)
+            };
+            let totp = if kind == EntryKind::SshKey || app_state.totp_input.trim().is_empty() {
+                None
+            } else {
+                match TotpConfig::parse(&app_state.totp_input) {
+                    Ok(totp) => Some(totp),
+                    Err(e) => {
+                        app_state.error_message = Some(e.to_string());
+                        return Ok(());
+                    }
+                }
+            };
+
+            let url = app_state.url_input.trim();
+            let notes = if kind == EntryKind::SshKey {
+                SecretString::new()
+            } else {
+                app_state.notes_input.trim_end().into()
+            };
             let entry = PasswordEntry {
+                kind,
                 service: app_state.service.clone(),
                 username: app_state.username.clone(),
                 password: app_state.password.clone(),
+                totp,
+                url: (!url.is_empty()).then(|| url.to_string()),
+                notes,
+                tags: parse_tags(&app_state.tags_input),
+                custom_fields,
+                private_key,
+                public_key,
+                ..Default::default()
+            };
+
+            // Warn once before creating a second entry for the same service
+            if !app_state.duplicate_service_confirmed {
+                if let Some(existing) = find_duplicate_service(
+                    &app_state.password_entries,
+                    &entry.service,
+                    app_state.editing_password_index,
+                ) {
+                    app_state.error_message = Some(format!(
+                        "'{}' already has an entry (user '{}'). Press 'Enter' again to save anyway.",
+                        app_state.password_entries[existing].service,
+                        app_state.password_entries[existing].username
+                    ));
+                    app_state.duplicate_service_confirmed = true;
+                    return Ok(());
+                }
+            }
+
+            let save_result = match (&app_state.vault, app_state.editing_password_index) {
+                (Some(vault), Some(index)) => {
+                    vault.update_password(index, &entry, security_config().password_history_depth)
+                }
+                (Some(vault), None) => vault.save_password(&entry),
+                (None, _) => Err(VaultError::Io(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Vault is locked",
+                ))),
             };
 
-            match save_password(&entry) {
+            match save_result {
                 Ok(()) => {
-                    app_state.push_notification("Password saved.", NotificationSeverity::Warning);
-                    app_state.error_message = Some("Password saved successfully.".to_string());
-                    app_state.service.clear();
-                    app_state.username.clear();
-                    app_state.password.clear();
-                    app_state.input_mode = InputMode::Normal;
+                    app_state.push_notification(
+                        format!("{} saved.", kind.name()),
+                        NotificationSeverity::Info,
+                    );
+                    app_state.error_message = Some(format!("{} saved successfully.", kind.name()));
+                    clear_entry_form(app_state);
+                    app_state.duplicate_service_confirmed = false;
+                    app_state.generated_entropy = None;
+                    refresh_password_entries(app_state);
+                    app_state.input_mode = if app_state.editing_password_index.take().is_some() {
+                        InputMode::Viewing
+                    } else {
+                        InputMode::Normal
+                    };
                 }
                 Err(e) => {
                     app_state.push_notification(
@@ -1078,27 +1752,115 @@ fn handle_editing_mode(
                 }
             }
         }
-        KeyCode::Char(c) => match app_state.input_field {
-            0 => app_state.service.push(c),
-            1 => app_state.username.push(c),
-            2 => app_state.password.push(c),
-            _ => {}
-        },
-        KeyCode::Backspace => match app_state.input_field {
-            0 => {
-                app_state.service.pop();
+        KeyCode::Char(c) if app_state.input_field == 8 => handle_generator_key(app_state, c),
+        KeyCode::Left if app_state.input_field == 8 => handle_generator_key(app_state, '-'),
+        KeyCode::Right if app_state.input_field == 8 => handle_generator_key(app_state, '+'),
+        KeyCode::Char(c) => {
+            match app_state.input_field {
+                0 => app_state.service.push(c),
+                1 => app_state.username.push(c),
+                2 => {
+                    app_state.password.push(c);
+                    app_state.generated_entropy = None;
+                }
+                3 if app_state.entry_kind == EntryKind::SshKey => {
+                    app_state.public_key_input.push(c)
+                }
+                3 => app_state.totp_input.push(c),
+                4 => app_state.url_input.push(c),
+                5 => app_state.tags_input.push(c),
+                6 if app_state.entry_kind == EntryKind::SshKey => {
+                    app_state.private_key_input.push(c)
+                }
+                6 => app_state.notes_input.push(c),
+                7 => app_state.custom_fields_input.push(c),
+                _ => {}
             }
-            1 => {
-                app_state.username.pop();
+            app_state.duplicate_service_confirmed = false;
+        }
+        KeyCode::Backspace => {
+            match app_state.input_field {
+                0 => {
+                    app_state.service.pop();
+                }
+                1 => {
+                    app_state.username.pop();
+                }
+                2 => {
+                    app_state.password.pop();
+                    app_state.generated_entropy = None;
+                }
+                3 if app_state.entry_kind == EntryKind::SshKey => {
+                    app_state.public_key_input.pop();
+                }
+                3 => {
+                    app_state.totp_input.pop();
+                }
+                4 => {
+                    app_state.url_input.pop();
+                }
+                5 => {
+                    app_state.tags_input.pop();
+                }
+                6 if app_state.entry_kind == EntryKind::SshKey => {
+                    app_state.private_key_input.pop();
+                }
+                6 => {
+                    app_state.notes_input.pop();
+                }
+                7 => {
+                    app_state.custom_fields_input.pop();
+                }
+                _ => {}
+            }
+            app_state.duplicate_service_confirmed = false;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Keys of the generator panel: g generates, m switches mode, l/u/d/s toggle the
+/// character classes, a toggles ambiguous characters, +/- change the length
+fn handle_generator_key(app_state: &mut AppState, key: char) {
+    let min_length = security_config().password_min_length;
+    let options = &mut app_state.generator_options;
+
+    match key {
+        'g' => match generator::generate(options, min_length) {
+            Ok(generated) => {
+                app_state.password = generated.password;
+                app_state.generated_entropy = Some(generated.entropy_bits);
+                app_state.error_message = None;
+            }
+            Err(e) => app_state.error_message = Some(e.to_string()),
+        },
+        'm' => {
+            options.mode = match options.mode {
+                GeneratorMode::Characters => GeneratorMode::Passphrase,
+                GeneratorMode::Passphrase => GeneratorMode::Characters,
+            };
+        }
+        'l' => options.lowercase = !options.lowercase,
+        'u' => options.uppercase = !options.uppercase,
+        'd' => options.digits = !options.digits,
+        's' => options.symbols = !options.symbols,
+        'a' => options.exclude_ambiguous = !options.exclude_ambiguous,
+        '+' | '=' => match options.mode {
+            GeneratorMode::Characters => {
+                options.length = (options.length + 1).min(generator::MAX_LENGTH)
             }
-            2 => {
-                app_state.password.pop();
+            GeneratorMode::Passphrase => options.words = (options.words + 1).min(generator::MAX_WORDS),
+        },
+        '-' => match options.mode {
+            // Never offer a length below the configured minimum
+            GeneratorMode::Characters => {
+                options.length = options.length.saturating_sub(1).max(min_length).max(1)
             }
-            _ => {}
+            GeneratorMode::Passphrase => options.words = options.words.saturating_sub(1).max(1),
         },
         _ => {}
     }
-    Ok(())
 }
 
 fn handle_viewing_mode(
@@ -1106,701 +1868,951 @@ fn handle_viewing_mode(
     code: KeyCode,
     running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
+    // Handle delete confirmation if active
+    if let ConfirmationDialogue::DeletePassword(index, _) = app_state.confirmation_dialogue {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                delete_password_entry(app_state, index);
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+            }
+            _ => {} // Ignore other keys while dialog is active
+        }
+        return Ok(());
+    }
+
     match code {
         KeyCode::Esc | KeyCode::Enter => {
+            app_state.revealed_password = None;
             app_state.input_mode = InputMode::Normal;
         }
         KeyCode::Char('q') => {
             running.store(false, Ordering::Relaxed);
         }
+        KeyCode::Up => move_password_selection(app_state, true),
+        KeyCode::Down => move_password_selection(app_state, false),
+        KeyCode::Char('/') => {
+            app_state.search_query.clear();
+            app_state.input_mode = InputMode::PasswordSearch;
+        }
+        KeyCode::Char('c') | KeyCode::Char('u') | KeyCode::Char('o') => {
+            let Some(entry) = app_state.password_entries.get(app_state.selected_password_index)
+            else {
+                return Ok(());
+            };
+            let (label, value) = match code {
+                KeyCode::Char('c') => ("Password", entry.password.clone()),
+                KeyCode::Char('u') => ("Username", entry.username.as_str().into()),
+                _ => match entry.totp {
+                    Some(ref totp) => (
+                        "One-time code",
+                        totp.code_at(Utc::now().timestamp().max(0) as u64).into(),
+                    ),
+                    None => {
+                        app_state.status_message = Some(prepare_status_message(
+                            "This entry has no TOTP secret",
+                            StatusMessageType::Warning,
+                            3,
+                        ));
+                        return Ok(());
+                    }
+                },
+            };
+            copy_to_clipboard(app_state, label, &value);
+        }
+        KeyCode::Char('h') if !app_state.password_entries.is_empty() => {
+            app_state.selected_history_index = 0;
+            app_state.input_mode = InputMode::PasswordHistory;
+        }
+        KeyCode::Char('s') if !app_state.password_entries.is_empty() => {
+            let service = &app_state.password_entries[app_state.selected_password_index].service;
+            let file_name: String = service
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+                .collect();
+            app_state.share_path = format!("{}.age", file_name);
+            app_state.input_field = 0;
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::SharingEntry;
+        }
+        KeyCode::Char('x') => {
+            let Some(entry) = app_state.password_entries.get(app_state.selected_password_index)
+            else {
+                return Ok(());
+            };
+            if entry.kind != EntryKind::SshKey {
+                app_state.status_message = Some(prepare_status_message(
+                    "Only SSH key items can run a command",
+                    StatusMessageType::Warning,
+                    3,
+                ));
+                return Ok(());
+            }
+            let host = entry.url.clone().unwrap_or_default();
+            let target = match (entry.username.is_empty(), host.is_empty()) {
+                (_, true) => String::new(),
+                (true, false) => host,
+                (false, false) => format!("{}@{}", entry.username, host),
+            };
+            app_state.ssh_command_input =
+                format!("ssh -o BatchMode=yes -i {} {}", KEY_PLACEHOLDER, target);
+            app_state.ssh_command_output = None;
+            app_state.input_mode = InputMode::SshCommand;
+        }
+        KeyCode::Char('t') => {
+            // Cycle through every tag in use, then back to showing everything
+            let tags = all_tags(&app_state.password_entries);
+            let next = match app_state.tag_filter {
+                Some(ref current) => tags
+                    .iter()
+                    .position(|tag| tag == current)
+                    .and_then(|position| tags.get(position + 1)),
+                None => tags.first(),
+            };
+            app_state.tag_filter = next.cloned();
+            app_state.revealed_password = None;
+            sync_password_selection(app_state);
+        }
+        KeyCode::Char('r') if !app_state.password_entries.is_empty() => {
+            let index = app_state.selected_password_index;
+            app_state.revealed_password = match app_state.revealed_password {
+                Some((revealed, _)) if revealed == index => None,
+                _ => Some((index, Instant::now())),
+            };
+        }
+        KeyCode::Char('e') => {
+            if let Some(entry) = app_state.password_entries.get(app_state.selected_password_index) {
+                app_state.service = entry.service.clone();
+                app_state.username = entry.username.clone();
+                app_state.password = entry.password.clone();
+                app_state.totp_input = entry
+                    .totp
+                    .as_ref()
+                    .map(|totp| totp.to_uri(&entry.service))
+                    .unwrap_or_default()
+                    .into();
+                app_state.url_input = entry.url.clone().unwrap_or_default();
+                app_state.notes_input = entry.notes.clone();
+                app_state.tags_input = entry.tags.join(", ");
+                app_state.custom_fields_input = format_custom_fields(&entry.custom_fields).into();
+                app_state.entry_kind = entry.kind;
+                app_state.private_key_input = entry.private_key.clone();
+                app_state.public_key_input = entry.public_key.clone().unwrap_or_default();
+                app_state.input_field = 0;
+                app_state.error_message = None;
+                app_state.editing_password_index = Some(app_state.selected_password_index);
+                app_state.input_mode = InputMode::Editing;
+            }
+        }
+        KeyCode::Char('d') => {
+            let index = app_state.selected_password_index;
+            if let Some(entry) = app_state.password_entries.get(index) {
+                if security_config().require_confirmation_for_deletion {
+                    app_state.confirmation_dialogue =
+                        ConfirmationDialogue::DeletePassword(index, entry.service.clone());
+                } else {
+                    delete_password_entry(app_state, index);
+                }
+            }
+        }
         _ => {}
     }
     Ok(())
 }
 
-fn handle_email_config_mode(
+fn handle_password_search_mode(
     app_state: &mut AppState,
     code: KeyCode,
-    running: &Arc<AtomicBool>,
+    _running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
+    // 'q' is not a quit key here since it can be part of the search
     match code {
-        KeyCode::Esc => {
-            app_state.input_mode = InputMode::Normal;
-            app_state.email_address_invalid = false;
+        // Enter keeps the chosen entry selected in the full list, ready for its actions
+        KeyCode::Esc | KeyCode::Enter => {
+            app_state.search_query.clear();
+            app_state.input_mode = InputMode::Viewing;
+            sync_password_selection(app_state);
         }
-        KeyCode::Char('t') => {
-            // Only start a new test if no test is currently running
-            if app_state.smtp_test_receiver.is_some() {
-                app_state.status_message = Some(prepare_status_message(
-                    "SMTP test already running...",
-                    StatusMessageType::Info,
-                    2,
-                ));
-                return Ok(());
-            }
+        KeyCode::Up => move_password_selection(app_state, true),
+        KeyCode::Down => move_password_selection(app_state, false),
+        KeyCode::Char(c) => {
+            app_state.search_query.push(c);
+            select_best_search_hit(app_state);
+        }
+        KeyCode::Backspace => {
+            app_state.search_query.pop();
+            select_best_search_hit(app_state);
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-            // Gate: SMTP-dependent features are disabled when email config load failed.
-            // Cleared automatically when set_email_config() succeeds — no restart needed.
-            let smtp_load_failed = app_state
-                .task_scheduler
-                .as_ref()
-                .and_then(|arc| arc.lock().ok())
-                .map(|sched| sched.email_config_load_error.is_some())
-                .unwrap_or(false);
-            if smtp_load_failed {
-                app_state.push_notification(
-                    "SMTP unavailable: email config failed to load. Save a valid config to re-enable.",
-                    NotificationSeverity::Warning,
-                );
-                return Ok(());
-            }
+fn select_best_search_hit(app_state: &mut AppState) {
+    if let Some(&index) = visible_password_indices(app_state).first() {
+        app_state.selected_password_index = index;
+    }
+    app_state.revealed_password = None;
+}
 
-            // Clone the scheduler Arc to move into the thread
-            if let Some(ref task_scheduler) = app_state.task_scheduler {
-                let scheduler_arc = Arc::clone(task_scheduler);
-                let (tx, rx) = mpsc::channel::<Result<(), String>>();
+/// Moves the selection one listed row up or down
+fn move_password_selection(app_state: &mut AppState, up: bool) {
+    let visible = visible_password_indices(app_state);
+    let position = visible
+        .iter()
+        .position(|&index| index == app_state.selected_password_index)
+        .unwrap_or(0);
+    let position = if up {
+        position.saturating_sub(1)
+    } else {
+        (position + 1).min(visible.len().saturating_sub(1))
+    };
+    if let Some(&index) = visible.get(position) {
+        app_state.selected_password_index = index;
+        app_state.revealed_password = None;
+    }
+}
 
-                thread::spawn(move || {
-                    if let Ok(scheduler) = scheduler_arc.lock() {
-                        let result = scheduler.test_email_config();
-                        let _ = tx.send(result);
-                    } else {
-                        let _ = tx.send(Err("Failed to acquire scheduler lock".to_string()));
-                    }
-                });
+fn handle_password_history_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let index = app_state.selected_password_index;
+    let history_len = app_state
+        .password_entries
+        .get(index)
+        .map_or(0, |entry| entry.history.len());
 
-                app_state.smtp_test_receiver = Some(rx);
-                app_state.status_message = Some(prepare_status_message(
-                    "Testing SMTP...",
-                    StatusMessageType::Info,
-                    30, // Long enough to cover the 30s timeout
-                ));
-            }
-        }
-        KeyCode::Tab => {
-            app_state.email_config_field = (app_state.email_config_field + 1) % 5;
+    match code {
+        KeyCode::Esc | KeyCode::Char('h') => {
+            app_state.input_mode = InputMode::Viewing;
         }
-        KeyCode::BackTab => {
-            app_state.email_config_field = (app_state.email_config_field + 4) % 5;
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
         }
-        KeyCode::Char(c) => {
-            match app_state.email_config_field {
-                0 => app_state.email_address.push(c),
-                1 => app_state.email_smtp_server.push(c),
-                2 => app_state.email_smtp_port.push(c),
-                3 => app_state.email_username.push(c),
-                4 => app_state.email_password.push(c),
-                _ => {}
-            }
-            // Live validate email field once '@' is present
-            if app_state.email_config_field == 0 {
-                if app_state.email_address.contains('@') {
-                    let valid = {
-                        let parts: Vec<&str> = app_state.email_address.splitn(2, '@').collect();
-                        parts.len() == 2
-                            && !parts[0].is_empty()
-                            && parts[1].contains('.')
-                            && !parts[1].starts_with('.')
-                            && !parts[1].ends_with('.')
-                    };
-                    app_state.email_address_invalid = !valid;
-                } else {
-                    app_state.email_address_invalid = false;
-                }
-            }
+        KeyCode::Up => {
+            app_state.selected_history_index = app_state.selected_history_index.saturating_sub(1);
         }
-        KeyCode::Backspace => {
-            match app_state.email_config_field {
-                0 => {
-                    app_state.email_address.pop();
-                }
-                1 => {
-                    app_state.email_smtp_server.pop();
-                }
-                2 => {
-                    app_state.email_smtp_port.pop();
-                }
-                3 => {
-                    app_state.email_username.pop();
-                }
-                4 => {
-                    app_state.email_password.pop();
-                }
-                _ => {}
-            }
-            // Live validate email field once '@' is present
-            if app_state.email_config_field == 0 {
-                if app_state.email_address.contains('@') {
-                    let valid = {
-                        let parts: Vec<&str> = app_state.email_address.splitn(2, '@').collect();
-                        parts.len() == 2
-                            && !parts[0].is_empty()
-                            && parts[1].contains('.')
-                            && !parts[1].starts_with('.')
-                            && !parts[1].ends_with('.')
-                    };
-                    app_state.email_address_invalid = !valid;
-                } else {
-                    app_state.email_address_invalid = false;
-                }
-            }
+        KeyCode::Down if app_state.selected_history_index + 1 < history_len => {
+            app_state.selected_history_index += 1;
         }
-        KeyCode::Enter => {
-            println!("Saving email config...");
-            // Validate email format before empty-field check
-            if app_state.email_address_invalid {
-                app_state.input_mode = InputMode::ConfiguringEmail; // stay in form
-                app_state.email_config_field = 0; // jump to email field
-                app_state.push_notification(
-                    "Invalid email address format",
-                    NotificationSeverity::Warning,
-                );
-                return Ok(());
-            }
-            // Validate and save email config
-            if app_state.email_address.is_empty()
-                || app_state.email_smtp_server.is_empty()
-                || app_state.email_smtp_port.is_empty()
-                || app_state.email_username.is_empty()
-                || app_state.email_password.is_empty()
-            {
-                println!("Email config validation failed - empty fields");
-                app_state.status_message = Some(prepare_status_message(
-                    "All fields are required",
-                    StatusMessageType::Error,
-                    3,
-                ));
-                return Ok(());
-            }
-
-            // Parse port
-            let port = match app_state.email_smtp_port.parse::<u16>() {
-                Ok(p) => p,
-                Err(_) => {
-                    app_state.status_message = Some(prepare_status_message(
-                        "Invalid port number",
-                        StatusMessageType::Error,
-                        3,
-                    ));
-                    return Ok(());
-                }
+        // Reveals the current and previous passwords of the entry together
+        KeyCode::Char('r') => {
+            app_state.revealed_password = match app_state.revealed_password {
+                Some((revealed, _)) if revealed == index => None,
+                _ => Some((index, Instant::now())),
             };
-
-            // Create config
-            let config = EmailConfig {
-                email: app_state.email_address.clone(),
-                smtp_server: app_state.email_smtp_server.clone(),
-                smtp_port: port,
-                username: app_state.email_username.clone(),
-                password: app_state.email_password.clone(),
-                retry_attempts: 3,
-                retry_delay_seconds: 300,
+        }
+        KeyCode::Enter if app_state.selected_history_index < history_len => {
+            let result = match app_state.vault {
+                Some(ref vault) => vault.restore_password(
+                    index,
+                    app_state.selected_history_index,
+                    security_config().password_history_depth,
+                ),
+                None => return Ok(()),
             };
 
-            // Set config — extract result first to avoid borrow conflicts
-            let save_result = app_state
-                .task_scheduler
-                .as_ref()
-                .and_then(|arc| arc.lock().ok())
-                .map(|mut scheduler| scheduler.set_email_config(config));
-            match save_result {
-                Some(Ok(())) => {
-                    // email_config_load_error is now None — SMTP features re-enable automatically
+            match result {
+                Ok(()) => {
                     app_state.status_message = Some(prepare_status_message(
-                        "Email configuration saved",
+                        "Previous password restored; the replaced one is now in the history",
                         StatusMessageType::Success,
                         3,
                     ));
-                    app_state.input_mode = InputMode::Normal;
+                    app_state.selected_history_index = 0;
                 }
-                Some(Err(e)) => {
+                Err(e) => {
                     app_state.push_notification(
-                        format!("Email config save failed: {}", e),
+                        format!("Password restore failed: {}", e),
                         NotificationSeverity::Error,
                     );
                 }
-                None => {
-                    // Scheduler not initialized — should not happen in normal flow
-                }
             }
+            refresh_password_entries(app_state);
         }
         _ => {}
     }
     Ok(())
 }
 
-fn handle_adding_reminder_mode(
+fn handle_import_path_mode(
     app_state: &mut AppState,
     code: KeyCode,
-    running: &Arc<AtomicBool>,
+    _running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
+    // 'q' is not a quit key here since it can be part of a path
     match code {
         KeyCode::Esc => {
-            app_state.input_mode = InputMode::ViewingTasks;
-            app_state.reminder_date.clear();
-            app_state.reminder_time.clear();
-        }
-        KeyCode::Char('q') => {
-            running.store(false, Ordering::Relaxed);
+            app_state.import_password.clear();
+            app_state.import_password_needed = false;
+            app_state.input_field = 0;
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::Normal;
         }
-        KeyCode::Tab => {
+        KeyCode::Tab if app_state.import_password_needed => {
             app_state.input_field = (app_state.input_field + 1) % 2;
         }
-        KeyCode::BackTab => {
-            app_state.input_field = (app_state.input_field + 1) % 2;
+        KeyCode::Enter => {
+            let path = app_state.import_path.trim();
+            let password = app_state
+                .import_password_needed
+                .then_some(app_state.import_password.as_str());
+            match ImportSession::open(path, password, &app_state.password_entries) {
+                Ok(session) => {
+                    app_state.import_session = Some(session);
+                    app_state.selected_import_index = 0;
+                    app_state.import_password.clear();
+                    app_state.import_password_needed = false;
+                    app_state.input_field = 0;
+                    app_state.error_message = None;
+                    app_state.input_mode = InputMode::ImportPreview;
+                }
+                Err(ImportError::PasswordRequired) => {
+                    app_state.import_password_needed = true;
+                    app_state.input_field = 1;
+                    app_state.error_message = Some(ImportError::PasswordRequired.to_string());
+                }
+                Err(e) => app_state.error_message = Some(e.to_string()),
+            }
         }
         KeyCode::Char(c) => match app_state.input_field {
-            0 => app_state.reminder_date.push(c),
-            1 => app_state.reminder_time.push(c),
-            _ => {}
-        },
-        KeyCode::Backspace => match app_state.input_field {
-            0 => {
-                app_state.reminder_date.pop();
-            }
-            1 => {
-                app_state.reminder_time.pop();
-            }
-            _ => {}
+            1 => app_state.import_password.push(c),
+            _ => app_state.import_path.push(c),
         },
-        KeyCode::Up | KeyCode::Down => {
-            // Cycle through reminder types
-            app_state.reminder_type = match app_state.reminder_type {
-                ReminderType::Email => ReminderType::Notification,
-                ReminderType::Notification => ReminderType::Sms,
-                ReminderType::Sms => ReminderType::Both,
-                ReminderType::Both => ReminderType::All,
-                ReminderType::All => ReminderType::Email,
+        KeyCode::Backspace => {
+            match app_state.input_field {
+                1 => app_state.import_password.pop(),
+                _ => app_state.import_path.pop(),
             };
         }
-        KeyCode::Enter => {
-            if let Some(task_id) = app_state.selected_task_id {
-                // Parse date and time
-                let reminder_datetime =
-                    format!("{} {}", app_state.reminder_date, app_state.reminder_time);
-
-                if let Ok(dt) = NaiveDateTime::parse_from_str(
-                    &format!("{} 00", reminder_datetime),
-                    "%Y-%m-%d %H:%M %S",
-                ) {
-                    let timestamp = dt.timestamp();
+        _ => {}
+    }
+    Ok(())
+}
 
-                    // Add reminder
-                    if let Some(ref task_scheduler) = app_state.task_scheduler {
-                        if let Ok(mut scheduler) = task_scheduler.lock() {
-                            if let Err(e) = scheduler.add_reminder_to_task(
-                                task_id,
-                                timestamp,
-                                app_state.reminder_type.clone(),
-                            ) {
-                                app_state.status_message = Some(prepare_status_message(
-                                    &format!("Error: {}", e),
-                                    StatusMessageType::Error,
-                                    3,
-                                ));
-                            } else {
-                                app_state.status_message = Some(prepare_status_message(
-                                    "Reminder added successfully",
-                                    StatusMessageType::Success,
-                                    3,
-                                ));
-                                app_state.input_mode = InputMode::ViewingTasks;
-                            }
-                        }
-                    }
-                } else {
-                    app_state.status_message = Some(prepare_status_message(
-                        "Invalid date or time format",
-                        StatusMessageType::Error,
-                        3,
-                    ));
-                }
+fn handle_export_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    // Confirm writing passwords in clear text
+    if let ConfirmationDialogue::ExportCsv(ref path) = app_state.confirmation_dialogue {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                let path = path.clone();
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+                let result = export_csv(&app_state.password_entries, &path);
+                finish_export(app_state, &path, result);
             }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+            }
+            _ => {} // Ignore other keys while dialog is active
         }
-        _ => {}
+        return Ok(());
     }
-    Ok(())
-}
 
-fn handle_enter_address_mode(
-    app_state: &mut AppState,
-    code: KeyCode,
-    running: &Arc<AtomicBool>,
-) -> io::Result<()> {
+    // 'q' is not a quit key here since it can be part of a path or password
     match code {
         KeyCode::Esc => {
+            clear_export_form(app_state);
             app_state.input_mode = InputMode::Normal;
-            app_state.address.clear();
-            app_state.selected_tool = None;
         }
-        KeyCode::Char('q') => {
-            running.store(false, Ordering::Relaxed);
+        KeyCode::Left | KeyCode::Right => {
+            app_state.export_format = match app_state.export_format {
+                ExportFormat::Csv => ExportFormat::PortableVault,
+                ExportFormat::PortableVault => ExportFormat::Csv,
+            };
+            app_state.input_field = 0;
+        }
+        KeyCode::Tab if app_state.export_format == ExportFormat::PortableVault => {
+            app_state.input_field = (app_state.input_field + 1) % 3;
         }
         KeyCode::Enter => {
-            if let Some(ref tool) = app_state.selected_tool {
-                let address = app_state.address.trim();
-                let result = match tool.as_str() {
-                    "ping" => ping(address).map(|output| {
-                        // Store the PingResult as a JSON string for simplicity
-                        serde_json::to_string(&output)
-                            .unwrap_or_else(|_| "Failed to serialize ping result.".to_string())
-                    }),
-                    // Handle other tools if necessary
-                    _ => Err("Unsupported tool".into()),
-                };
+            let path = app_state.export_path.trim().to_string();
+            if path.is_empty() {
+                app_state.error_message = Some("Enter a file path to export to".to_string());
+                return Ok(());
+            }
 
-                match result {
-                    Ok(json_result) => {
-                        app_state.result = Some(json_result);
-                        app_state.input_mode = InputMode::ViewResults;
-                    }
-                    Err(e) => {
-                        println!("Failed to parse packet stats: {:?}", e);
-                        app_state.error_message = Some(format!("Error: {}", e));
-                        app_state.input_mode = InputMode::Normal;
+            match app_state.export_format {
+                ExportFormat::Csv => {
+                    app_state.confirmation_dialogue = ConfirmationDialogue::ExportCsv(path);
+                }
+                ExportFormat::PortableVault => {
+                    if app_state.export_password.is_empty() {
+                        app_state.error_message =
+                            Some("Choose a password for the export file".to_string());
+                    } else if app_state.export_password != app_state.export_password_confirm {
+                        app_state.error_message = Some("Passwords do not match".to_string());
+                    } else {
+                        let result = export_portable(
+                            &app_state.password_entries,
+                            &path,
+                            &app_state.export_password,
+                        );
+                        finish_export(app_state, &path, result);
                     }
                 }
-                app_state.address.clear();
-            } else {
-                app_state.error_message = Some("No tool selected.".to_string());
-                app_state.input_mode = InputMode::Normal;
             }
         }
-        KeyCode::Char(c) => {
-            app_state.address.push(c);
-        }
+        KeyCode::Char(c) => match app_state.input_field {
+            1 => app_state.export_password.push(c),
+            2 => app_state.export_password_confirm.push(c),
+            _ => app_state.export_path.push(c),
+        },
         KeyCode::Backspace => {
-            app_state.address.pop();
+            match app_state.input_field {
+                1 => app_state.export_password.pop(),
+                2 => app_state.export_password_confirm.pop(),
+                _ => app_state.export_path.pop(),
+            };
         }
         _ => {}
     }
     Ok(())
 }
 
-fn handle_view_results_mode(
+/// Rebuilds the audit report from the current vault contents
+fn run_security_audit(app_state: &mut AppState) {
+    refresh_password_entries(app_state);
+    let security = security_config();
+    let report = audit(
+        &app_state.password_entries,
+        security.encryption_key_rotation_days,
+        Utc::now().timestamp(),
+        security.hibp_range_path.as_deref(),
+    );
+
+    if let Some(ref error) = report.hibp_error {
+        app_state.push_notification(
+            format!("Breach check skipped: {}", error),
+            NotificationSeverity::Warning,
+        );
+    }
+    app_state.selected_audit_index = 0;
+    app_state.audit_report = Some(report);
+}
+
+fn handle_security_audit_mode(
     app_state: &mut AppState,
     code: KeyCode,
     running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
+    let rows = app_state
+        .audit_report
+        .as_ref()
+        .map_or(0, |report| report.entries.len());
+
     match code {
-        KeyCode::Esc | KeyCode::Enter => {
+        KeyCode::Esc => {
+            app_state.audit_report = None;
             app_state.input_mode = InputMode::Normal;
-            app_state.result = None;
-            app_state.selected_tool = None;
         }
         KeyCode::Char('q') => {
             running.store(false, Ordering::Relaxed);
         }
+        KeyCode::Up => {
+            app_state.selected_audit_index = app_state.selected_audit_index.saturating_sub(1);
+        }
+        KeyCode::Down if app_state.selected_audit_index + 1 < rows => {
+            app_state.selected_audit_index += 1;
+        }
+        KeyCode::Char('r') => run_security_audit(app_state),
+        // Jump straight to fixing the selected entry
+        KeyCode::Char('e') => {
+            let index = app_state
+                .audit_report
+                .as_ref()
+                .and_then(|report| report.entries.get(app_state.selected_audit_index))
+                .map(|finding| finding.index);
+            if let Some(index) = index {
+                app_state.audit_report = None;
+                app_state.selected_password_index = index;
+                app_state.input_mode = InputMode::Viewing;
+                handle_viewing_mode(app_state, KeyCode::Char('e'), running)?;
+            }
+        }
         _ => {}
     }
     Ok(())
 }
 
-fn handle_adding_task_mode(
+fn finish_export(app_state: &mut AppState, path: &str, result: Result<(), VaultError>) {
+    match result {
+        Ok(()) => {
+            app_state.push_notification(
+                format!("Exported {} passwords to {}.", app_state.password_entries.len(), path),
+                NotificationSeverity::Info,
+            );
+            clear_export_form(app_state);
+            app_state.input_mode = InputMode::Normal;
+        }
+        Err(e) => app_state.error_message = Some(format!("Export failed: {}", e)),
+    }
+}
+
+fn clear_export_form(app_state: &mut AppState) {
+    app_state.export_path.clear();
+    app_state.export_password.clear();
+    app_state.export_password_confirm.clear();
+    app_state.input_field = 0;
+    app_state.error_message = None;
+}
+
+fn handle_import_preview_mode(
     app_state: &mut AppState,
     code: KeyCode,
     running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
+    let Some(ref mut session) = app_state.import_session else {
+        app_state.input_mode = InputMode::Normal;
+        return Ok(());
+    };
+
     match code {
         KeyCode::Esc => {
+            app_state.import_session = None;
             app_state.input_mode = InputMode::Normal;
-            app_state.task_title.clear();
-            app_state.task_description.clear();
-            app_state.task_due_date.clear();
-            app_state.task_tags.clear();
-            app_state.task_date_invalid = false;
-        }
-        KeyCode::Tab => {
-            app_state.input_field = (app_state.input_field + 1) % 4; // Cycle through title, description, due date, tags
-        }
-        KeyCode::BackTab => {
-            app_state.input_field = (app_state.input_field + 3) % 4;
         }
         KeyCode::Char('q') => {
             running.store(false, Ordering::Relaxed);
         }
-        KeyCode::Char(c) => {
-            match app_state.input_field {
-                0 => app_state.task_title.push(c),
-                1 => app_state.task_description.push(c),
-                2 => app_state.task_due_date.push(c),
-                3 => app_state.task_tags.push(c),
-                _ => {}
-            }
-            // Live validate date field after minimum length
-            if app_state.input_field == 2 {
-                if app_state.task_due_date.len() >= 10 {
-                    app_state.task_date_invalid = parse_due_date(&app_state.task_due_date).is_none();
-                } else {
-                    app_state.task_date_invalid = false;
-                }
-            }
+        KeyCode::Up => {
+            app_state.selected_import_index = app_state.selected_import_index.saturating_sub(1);
         }
-        KeyCode::Backspace => {
-            match app_state.input_field {
-                0 => {
-                    app_state.task_title.pop();
-                }
-                1 => {
-                    app_state.task_description.pop();
-                }
-                2 => {
-                    app_state.task_due_date.pop();
-                }
-                3 => {
-                    app_state.task_tags.pop();
+        KeyCode::Down if app_state.selected_import_index + 1 < session.candidates.len() => {
+            app_state.selected_import_index += 1;
+        }
+        KeyCode::Char(' ') => session.toggle(app_state.selected_import_index),
+        // CSV column mapping
+        KeyCode::Char(key @ ('s' | 'u' | 'p' | 't' | 'r' | 'n' | 'g' | 'f' | 'k' | 'v' | 'b')) => {
+            let field = match key {
+                's' => CsvField::Service,
+                'u' => CsvField::Username,
+                'p' => CsvField::Password,
+                't' => CsvField::Totp,
+                'r' => CsvField::Url,
+                'n' => CsvField::Notes,
+                'g' => CsvField::Tags,
+                'f' => CsvField::CustomFields,
+                'k' => CsvField::Kind,
+                'v' => CsvField::PrivateKey,
+                _ => CsvField::PublicKey,
+            };
+            session.cycle_mapping(field, &app_state.password_entries);
+            app_state.selected_import_index = 0;
+        }
+        KeyCode::Enter => {
+            let entries = session.selected_entries();
+            let result = match app_state.vault {
+                Some(ref vault) => vault.save_passwords(&entries),
+                None => return Ok(()),
+            };
+
+            match result {
+                Ok(()) => {
+                    app_state.push_notification(
+                        format!("Imported {} passwords.", entries.len()),
+                        NotificationSeverity::Info,
+                    );
+                    app_state.import_session = None;
+                    refresh_password_entries(app_state);
+                    app_state.input_mode = InputMode::Viewing;
                 }
-                _ => {}
-            }
-            // Live validate date field after minimum length
-            if app_state.input_field == 2 {
-                if app_state.task_due_date.len() >= 10 {
-                    app_state.task_date_invalid = parse_due_date(&app_state.task_due_date).is_none();
-                } else {
-                    app_state.task_date_invalid = false;
+                Err(e) => {
+                    app_state.push_notification(
+                        format!("Import failed: {}", e),
+                        NotificationSeverity::Error,
+                    );
                 }
             }
         }
-        KeyCode::Enter => {
-            // Parse due date — validate first
-            let due_date = parse_due_date(&app_state.task_due_date);
-            if due_date.is_none() {
-                app_state.task_date_invalid = true;
-                app_state.input_field = 2; // jump cursor to due date field
-                app_state.push_notification(
-                    "Invalid due date — use YYYY-MM-DD format",
-                    NotificationSeverity::Warning,
-                );
-                return Ok(());
-            }
+        _ => {}
+    }
+    Ok(())
+}
 
-            if let Some(timestamp) = due_date {
-                // Parse tags
-                let tags: Vec<String> = app_state
-                    .task_tags
-                    .split(',')
-                    .map(|tag| tag.trim().to_string())
-                    .filter(|tag| !tag.is_empty())
-                    .collect();
+/// Reloads the decrypted entry list shown in the password views
+fn refresh_password_entries(app_state: &mut AppState) {
+    let result = match app_state.vault {
+        Some(ref vault) => vault.retrieve_password(),
+        None => return,
+    };
 
-                // Add task — extract result before borrowing app_state mutably
-                let add_result = app_state.task_scheduler.as_ref().and_then(|arc| {
-                    arc.lock().ok().map(|mut scheduler| {
-                        scheduler.add_task(
-                            app_state.task_title.clone(),
-                            app_state.task_description.clone(),
-                            timestamp,
-                            app_state.task_priority.clone(),
-                            tags,
-                        )
-                    })
-                });
-                match add_result {
-                    Some(Ok(_)) => {
-                        // Success message
-                        app_state.status_message = Some(prepare_status_message(
-                            "Task added successfully",
-                            StatusMessageType::Success,
-                            3,
-                        ));
-                        // Clear fields and return to normal mode
-                        app_state.task_title.clear();
-                        app_state.task_description.clear();
-                        app_state.task_due_date.clear();
-                        app_state.task_tags.clear();
-                        app_state.input_mode = InputMode::Normal;
-                    }
-                    Some(Err(e)) => {
-                        app_state.push_notification(
-                            format!("Task save failed: {}. Changes preserved in memory.", e),
-                            NotificationSeverity::Error,
-                        );
-                        // Still clear fields and continue — task is in memory
-                        app_state.task_title.clear();
-                        app_state.task_description.clear();
-                        app_state.task_due_date.clear();
-                        app_state.task_tags.clear();
-                        app_state.input_mode = InputMode::Normal;
-                    }
-                    None => {
-                        // Scheduler not initialized
-                    }
-                }
+    match result {
+        Ok(entries) => {
+            app_state.password_entries = entries;
+            if app_state.selected_password_index >= app_state.password_entries.len() {
+                app_state.selected_password_index = app_state.password_entries.len().saturating_sub(1);
             }
+            sync_password_selection(app_state);
         }
-        KeyCode::Up => {
-            // Cycle through priorities
-            app_state.task_priority = match app_state.task_priority {
-                TaskPriority::Low => TaskPriority::Urgent,
-                TaskPriority::Medium => TaskPriority::Low,
-                TaskPriority::High => TaskPriority::Medium,
-                TaskPriority::Urgent => TaskPriority::High,
-            };
+        Err(e) => {
+            app_state.password_entries.clear();
+            app_state.push_notification(
+                format!("Failed to load passwords: {}", e),
+                NotificationSeverity::Error,
+            );
         }
-        KeyCode::Down => {
-            // Cycle through priorities
-            app_state.task_priority = match app_state.task_priority {
-                TaskPriority::Low => TaskPriority::Medium,
-                TaskPriority::Medium => TaskPriority::High,
-                TaskPriority::High => TaskPriority::Urgent,
-                TaskPriority::Urgent => TaskPriority::Low,
+    }
+}
+
+/// Search hits within the tag filter, best first (empty while no search is typed)
+fn password_search_hits(app_state: &AppState) -> Vec<SearchHit> {
+    if app_state.search_query.trim().is_empty() {
+        return Vec::new();
+    }
+    search_entries(&app_state.password_entries, &app_state.search_query)
+        .into_iter()
+        .filter(|hit| {
+            app_state
+                .tag_filter
+                .as_ref()
+                .is_none_or(|tag| app_state.password_entries[hit.index].has_tag(tag))
+        })
+        .collect()
+}
+
+/// Vault indices of the entries shown in the password list, in display order
+fn visible_password_indices(app_state: &AppState) -> Vec<usize> {
+    if !app_state.search_query.trim().is_empty() {
+        return password_search_hits(app_state)
+            .iter()
+            .map(|hit| hit.index)
+            .collect();
+    }
+
+    app_state
+        .password_entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| {
+            app_state
+                .tag_filter
+                .as_ref()
+                .is_none_or(|tag| entry.has_tag(tag))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Keeps the selection on a listed entry, dropping a tag filter that no longer matches anything
+fn sync_password_selection(app_state: &mut AppState) {
+    let mut visible = visible_password_indices(app_state);
+    // An empty search result is expected while typing; an empty tag is stale
+    if visible.is_empty()
+        && app_state.search_query.trim().is_empty()
+        && app_state.tag_filter.take().is_some()
+    {
+        visible = visible_password_indices(app_state);
+    }
+    if !visible.contains(&app_state.selected_password_index) {
+        app_state.selected_password_index = visible.first().copied().unwrap_or(0);
+    }
+}
+
+/// Empties the add/edit modal
+fn clear_entry_form(app_state: &mut AppState) {
+    app_state.service.clear();
+    app_state.username.clear();
+    app_state.password.clear();
+    app_state.totp_input.clear();
+    app_state.url_input.clear();
+    app_state.notes_input.clear();
+    app_state.tags_input.clear();
+    app_state.custom_fields_input.clear();
+    app_state.entry_kind = EntryKind::Login;
+    app_state.private_key_input.clear();
+    app_state.public_key_input.clear();
+}
+
+fn delete_password_entry(app_state: &mut AppState, index: usize) {
+    let result = match app_state.vault {
+        Some(ref vault) => vault.delete_password(index),
+        None => return,
+    };
+
+    match result {
+        Ok(removed) => {
+            app_state.status_message = Some(prepare_status_message(
+                &format!("Deleted entry for '{}'", removed.service),
+                StatusMessageType::Success,
+                3,
+            ));
+        }
+        Err(e) => {
+            app_state.push_notification(
+                format!("Password delete failed: {}", e),
+                NotificationSeverity::Error,
+            );
+        }
+    }
+    refresh_password_entries(app_state);
+}
+
+/// Copies a value from the vault, replacing (and so forgetting) any earlier copy
+fn copy_to_clipboard(app_state: &mut AppState, label: &str, value: &str) {
+    app_state.clipboard = None;
+    match ClipboardCopy::copy(&mut io::stdout(), value) {
+        Ok(copy) => {
+            let clear_after = security_config().clipboard_clear_seconds;
+            let message = if clear_after > 0 {
+                format!(
+                    "{} copied via {}; clipboard clears in {}s",
+                    label,
+                    copy.method(),
+                    clear_after
+                )
+            } else {
+                format!("{} copied via {}", label, copy.method())
             };
+            app_state.status_message = Some(prepare_status_message(
+                &message,
+                StatusMessageType::Success,
+                3,
+            ));
+            app_state.clipboard = Some((copy, Instant::now()));
+        }
+        Err(e) => {
+            app_state.push_notification(
+                format!("Copy to clipboard failed: {}", e),
+                NotificationSeverity::Error,
+            );
         }
-        _ => {}
     }
-    Ok(())
 }
 
-fn handle_viewing_tasks_mode(
+/// Empties the clipboard once `security.clipboard_clear_seconds` have passed
+fn check_clipboard_clear(app_state: &mut AppState) {
+    let clear_after = security_config().clipboard_clear_seconds;
+    let expired = app_state.clipboard.as_ref().is_some_and(|(_, copied_at)| {
+        clear_after > 0 && copied_at.elapsed() >= Duration::from_secs(clear_after)
+    });
+    if expired {
+        clear_clipboard(app_state);
+    }
+}
+
+fn clear_clipboard(app_state: &mut AppState) {
+    if let Some((copy, _)) = app_state.clipboard.take() {
+        if let Err(e) = copy.clear(&mut io::stdout()) {
+            app_state.push_notification(
+                format!("Clearing the clipboard failed: {}", e),
+                NotificationSeverity::Error,
+            );
+        }
+    }
+}
+
+/// Security settings from the global config, falling back to the built-in defaults
+fn security_config() -> SecurityConfig {
+    get_config(|config| config.security.clone()).unwrap_or_else(|| AppConfig::default().security)
+}
+
+/// Drops the vault key and every decrypted value held for the password views
+///
+/// If the password manager is open it goes straight back to the unlock screen.
+fn lock_vault(app_state: &mut AppState) {
+    app_state.vault = None;
+    app_state.password_entries.clear();
+    app_state.revealed_password = None;
+    app_state.editing_password_index = None;
+    app_state.duplicate_service_confirmed = false;
+    app_state.generated_entropy = None;
+    app_state.tag_filter = None;
+    app_state.search_query.clear();
+    clear_clipboard(app_state);
+    clear_entry_form(app_state);
+    app_state.import_session = None;
+    app_state.audit_report = None;
+    app_state.import_password.clear();
+    app_state.export_password.clear();
+    app_state.export_password_confirm.clear();
+    clear_master_password_form(app_state);
+    app_state.vault_check = None;
+    app_state.ssh_command = None;
+    app_state.ssh_command_input.clear();
+    app_state.ssh_command_output = None;
+    if matches!(
+        app_state.confirmation_dialogue,
+        ConfirmationDialogue::DeletePassword(..)
+            | ConfirmationDialogue::ExportCsv(_)
+            | ConfirmationDialogue::RestoreSnapshot(..)
+    ) {
+        app_state.confirmation_dialogue = ConfirmationDialogue::None;
+    }
+
+    if app_state.active_menu == MenuItem::PasswordManager {
+        if let Some(path) = active_vault_path(app_state) {
+            app_state.vault_status = vault_status(&path);
+        }
+        app_state.input_field = 0;
+        app_state.error_message = None;
+        app_state.input_mode = InputMode::Unlocking;
+    }
+}
+
+/// Locks the vault after `auto_lock_timeout_minutes` without a key press (0 disables it)
+fn check_vault_auto_lock(app_state: &mut AppState) {
+    if app_state.vault.is_none() {
+        return;
+    }
+
+    let timeout_minutes = security_config().auto_lock_timeout_minutes;
+    if timeout_minutes == 0
+        || app_state.last_activity.elapsed() < Duration::from_secs(u64::from(timeout_minutes) * 60)
+    {
+        return;
+    }
+
+    lock_vault(app_state);
+    app_state.push_notification(
+        format!("Vault locked after {} minutes of inactivity.", timeout_minutes),
+        NotificationSeverity::Warning,
+    );
+}
+
+fn clear_master_password_form(app_state: &mut AppState) {
+    app_state.master_password.clear();
+    app_state.new_master_password.clear();
+    app_state.master_password_confirm.clear();
+    app_state.input_field = 0;
+}
+
+/// Warns when the open vault's key is older than `encryption_key_rotation_days` (0 disables it)
+fn remind_key_rotation(app_state: &mut AppState) {
+    let rotation_days = security_config().encryption_key_rotation_days;
+    let age_days = app_state
+        .vault
+        .as_ref()
+        .and_then(|vault| vault.key_age_days(Utc::now().timestamp()));
+    if let Some(age_days) = age_days {
+        if rotation_days > 0 && age_days >= i64::from(rotation_days) {
+            app_state.push_notification(
+                format!(
+                    "The master key is {} days old (rotation period: {} days). Press 'k' to change the master password.",
+                    age_days, rotation_days
+                ),
+                NotificationSeverity::Warning,
+            );
+        }
+    }
+}
+
+fn handle_change_master_password_mode(
     app_state: &mut AppState,
     code: KeyCode,
-    running: &Arc<AtomicBool>,
+    _running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
     match code {
         KeyCode::Esc => {
+            clear_master_password_form(app_state);
+            app_state.error_message = None;
             app_state.input_mode = InputMode::Normal;
-            app_state.selected_task_id = None;
-        }
-        KeyCode::Char('q') => {
-            running.store(false, Ordering::Relaxed);
         }
-        KeyCode::Char('r') => {
-            // Add reminder to selected task
-            if let Some(task_id) = app_state.selected_task_id {
-                app_state.input_mode = InputMode::AddingReminder;
-                app_state.reminder_date = Local::now().format("%Y-%m-%d").to_string();
-                app_state.reminder_time = Local::now().format("%H:%M").to_string();
-            }
+        KeyCode::Tab => app_state.input_field = (app_state.input_field + 1) % 3,
+        KeyCode::BackTab => app_state.input_field = (app_state.input_field + 2) % 3,
+        KeyCode::Char(c) => match app_state.input_field {
+            0 => app_state.master_password.push(c),
+            1 => app_state.new_master_password.push(c),
+            _ => app_state.master_password_confirm.push(c),
+        },
+        KeyCode::Backspace => {
+            match app_state.input_field {
+                0 => app_state.master_password.pop(),
+                1 => app_state.new_master_password.pop(),
+                _ => app_state.master_password_confirm.pop(),
+            };
         }
-        KeyCode::Char('d') => {
-            // Delete selected task — extract result before borrowing app_state mutably
-            if let Some(task_id) = app_state.selected_task_id {
-                let delete_result = app_state
-                    .task_scheduler
-                    .as_ref()
-                    .and_then(|arc| arc.lock().ok())
-                    .map(|mut scheduler| scheduler.delete_task(task_id));
-                match delete_result {
-                    Some(Ok(())) => {
-                        app_state.status_message = Some(prepare_status_message(
-                            "Task deleted successfully",
-                            StatusMessageType::Success,
-                            3,
-                        ));
-                        app_state.selected_task_id = None;
-                    }
-                    Some(Err(ref e)) if e.contains("save") || e.contains("write") || e.contains("Error") => {
-                        // Save failure — task removed from memory but disk write failed
-                        let msg = format!("Task save failed: {}. Changes preserved in memory.", e);
-                        app_state.push_notification(msg, NotificationSeverity::Error);
-                        app_state.selected_task_id = None;
-                    }
-                    Some(Err(e)) => {
-                        app_state.status_message = Some(prepare_status_message(
-                            &format!("Error: {}", e),
-                            StatusMessageType::Error,
-                            3,
-                        ));
-                    }
-                    None => {}
-                }
+        KeyCode::Enter => {
+            if app_state.new_master_password.is_empty() {
+                app_state.error_message = Some("Master password cannot be empty.".to_string());
+                app_state.input_field = 1;
+                return Ok(());
             }
-        }
-        KeyCode::Char('c') => {
-            // Complete selected task — extract result before borrowing app_state mutably
-            if let Some(task_id) = app_state.selected_task_id {
-                let update_result = app_state.task_scheduler.as_ref().and_then(|arc| {
-                    arc.lock().ok().and_then(|mut scheduler| {
-                        // Clone the task first to release the immutable borrow before calling update
-                        let updated = scheduler.get_task(task_id).map(|task| {
-                            let mut t = task.clone();
-                            t.status = TaskStatus::Completed;
-                            t
-                        });
-                        updated.map(|updated_task| scheduler.update_task(task_id, updated_task))
-                    })
-                });
-                match update_result {
-                    Some(Ok(())) => {
-                        app_state.status_message = Some(prepare_status_message(
-                            "Task marked as completed",
-                            StatusMessageType::Success,
-                            3,
-                        ));
-                    }
-                    Some(Err(ref e)) if e.contains("save") || e.contains("write") || e.contains("Error") => {
-                        // Save failure — in-memory state updated but disk write failed
-                        let msg = format!("Task save failed: {}. Changes preserved in memory.", e);
-                        app_state.push_notification(msg, NotificationSeverity::Error);
-                    }
-                    Some(Err(e)) => {
-                        app_state.status_message = Some(prepare_status_message(
-                            &format!("Error: {}", e),
-                            StatusMessageType::Error,
-                            3,
-                        ));
-                    }
-                    None => {}
-                }
+            if app_state.new_master_password != app_state.master_password_confirm {
+                app_state.error_message = Some("New master passwords do not match.".to_string());
+                app_state.master_password_confirm.clear();
+                app_state.input_field = 2;
+                return Ok(());
             }
-        }
-        KeyCode::Up => {
-            // Navigate tasks up
-            if let Some(ref task_scheduler) = app_state.task_scheduler {
-                if let Ok(scheduler) = task_scheduler.lock() {
-                    let tasks = scheduler.get_all_tasks();
-                    if tasks.is_empty() {
-                        return Ok(());
-                    }
-
-                    // Sort tasks by due date
-                    let mut task_ids: Vec<u32> = tasks.iter().map(|task| task.id).collect();
-                    task_ids.sort_by(|a, b| {
-                        match (scheduler.get_task(*a), scheduler.get_task(*b)) {
-                            (Some(task_a), Some(task_b)) => task_a.due_date.cmp(&task_b.due_date),
-                            _ => std::cmp::Ordering::Equal,
-                        }
-                    });
+            let Some(vault) = app_state.vault.as_mut() else {
+                return Ok(());
+            };
 
-                    if let Some(selected_id) = app_state.selected_task_id {
-                        if let Some(pos) = task_ids.iter().position(|id| *id == selected_id) {
-                            if pos > 0 {
-                                app_state.selected_task_id = Some(task_ids[pos - 1]);
-                            }
-                        }
-                    } else if !task_ids.is_empty() {
-                        app_state.selected_task_id = Some(task_ids[0]);
-                    }
+            match vault.change_master_password(
+                &app_state.master_password,
+                &app_state.new_master_password,
+            ) {
+                Ok(()) => {
+                    clear_master_password_form(app_state);
+                    app_state.error_message = None;
+                    app_state.input_mode = InputMode::Normal;
+                    app_state.push_notification(
+                        "Master password changed; the vault was re-encrypted with a new key.",
+                        NotificationSeverity::Info,
+                    );
+                }
+                Err(VaultError::WrongPassword) => {
+                    app_state.error_message =
+                        Some("The current master password is incorrect.".to_string());
+                    app_state.master_password.clear();
+                    app_state.input_field = 0;
+                }
+                Err(e) => {
+                    app_state.error_message = Some(format!(
+                        "Re-encryption failed, the vault was left unchanged: {}",
+                        e
+                    ));
                 }
             }
         }
-        KeyCode::Down => {
-            // Navigate tasks down
-            if let Some(ref task_scheduler) = app_state.task_scheduler {
-                if let Ok(scheduler) = task_scheduler.lock() {
-                    let tasks = scheduler.get_all_tasks();
-                    if tasks.is_empty() {
-                        return Ok(());
-                    }
-
-                    // Sort tasks by due date
-                    let mut task_ids: Vec<u32> = tasks.iter().map(|task| task.id).collect();
-                    task_ids.sort_by(|a, b| {
-                        match (scheduler.get_task(*a), scheduler.get_task(*b)) {
-                            (Some(task_a), Some(task_b)) => task_a.due_date.cmp(&task_b.due_date),
-                            _ => std::cmp::Ordering::Equal,
-                        }
-                    });
+        _ => {}
+    }
+    Ok(())
+}
 
-                    if let Some(selected_id) = app_state.selected_task_id {
-                        if let Some(pos) = task_ids.iter().position(|id| *id == selected_id) {
-                            if pos < task_ids.len() - 1 {
-                                app_state.selected_task_id = Some(task_ids[pos + 1]);
-                            }
-                        }
-                    } else if !task_ids.is_empty() {
-                        app_state.selected_task_id = Some(task_ids[0]);
-                    }
+fn handle_ssh_command_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let running_command = app_state.ssh_command.is_some();
+    match code {
+        KeyCode::Esc => {
+            // A running command is killed and its key file removed
+            app_state.ssh_command = None;
+            app_state.ssh_command_input.clear();
+            app_state.ssh_command_output = None;
+            app_state.input_mode = InputMode::Viewing;
+        }
+        _ if running_command => {}
+        KeyCode::Char(c) => app_state.ssh_command_input.push(c),
+        KeyCode::Backspace => {
+            app_state.ssh_command_input.pop();
+        }
+        KeyCode::Enter => {
+            let Some(entry) = app_state.password_entries.get(app_state.selected_password_index)
+            else {
+                return Ok(());
+            };
+            match KeyCommand::start(&entry.private_key, &app_state.ssh_command_input) {
+                Ok(command) => {
+                    app_state.ssh_command = Some(command);
+                    app_state.ssh_command_output = Some("Running...".to_string());
+                    log_info(
+                        "password_manager",
+                        &format!("Running a command with SSH key '{}'", entry.service),
+                    );
+                }
+                Err(e) => {
+                    app_state.ssh_command_output =
+                        Some(format!("Could not run the command: {}", e));
                 }
             }
         }
@@ -1809,622 +2821,1301 @@ fn handle_viewing_tasks_mode(
     Ok(())
 }
 
-// Helper function to parse due date
-fn parse_due_date(date_str: &str) -> Option<i64> {
-    // Try to parse as YYYY-MM-DD
-    if let Ok(date) =
-        NaiveDateTime::parse_from_str(&format!("{} 00:00:00", date_str), "%Y-%m-%d %H:%M:%S")
-    {
-        return Some(date.timestamp());
-    }
-
-    None
+/// Picks up the result of a command started from the SSH key popup
+fn check_ssh_command(app_state: &mut AppState) {
+    let result = match app_state.ssh_command.as_mut().map(KeyCommand::try_finish) {
+        Some(Ok(Some(output))) => Ok(output),
+        Some(Err(e)) => Err(e),
+        Some(Ok(None)) | None => return,
+    };
+    app_state.ssh_command = None;
+    app_state.ssh_command_output = Some(match result {
+        Ok(output) => {
+            let status = match (output.success, output.code) {
+                (true, _) => "Command succeeded".to_string(),
+                (false, Some(code)) => format!("Command failed with exit code {}", code),
+                (false, None) => "Command was killed by a signal".to_string(),
+            };
+            format!("{}. The key file was removed.\n\n{}", status, output.output)
+        }
+        Err(e) => format!("Could not run the command: {}", e),
+    });
 }
 
-fn draw_task_scheduler_menu(f: &mut Frame) {
-    let text_color = get_text_color();
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)].as_ref())
-        .split(f.size());
+fn handle_share_entry_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    // 'q' is not a quit key here since it can be part of a path
+    match code {
+        KeyCode::Esc => {
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::Viewing;
+        }
+        KeyCode::Tab | KeyCode::BackTab => app_state.input_field = (app_state.input_field + 1) % 2,
+        KeyCode::Char(c) => match app_state.input_field {
+            0 => app_state.share_recipient.push(c),
+            _ => app_state.share_path.push(c),
+        },
+        KeyCode::Backspace => {
+            match app_state.input_field {
+                0 => app_state.share_recipient.pop(),
+                _ => app_state.share_path.pop(),
+            };
+        }
+        KeyCode::Enter => {
+            let recipient = match Recipient::parse(&app_state.share_recipient) {
+                Ok(recipient) => recipient,
+                Err(e) => {
+                    app_state.error_message = Some(e.to_string());
+                    app_state.input_field = 0;
+                    return Ok(());
+                }
+            };
+            let path = app_state.share_path.trim().to_string();
+            if path.is_empty() {
+                app_state.error_message = Some("Enter a file path to write to".to_string());
+                app_state.input_field = 1;
+                return Ok(());
+            }
+            let Some(entry) = app_state.password_entries.get(app_state.selected_password_index)
+            else {
+                return Ok(());
+            };
 
-    let text = vec![
-        Line::from(Span::raw("a. Add Task")),
-        Line::from(Span::raw("v. View Tasks")),
-        Line::from(Span::raw("e. Email Configuration")),
-        Line::from(Span::raw("Esc. Back to Main Menu")),
-        Line::from(Span::raw("Press 'q' to quit")),
-    ];
-    let paragraph = Paragraph::new(text)
-        .block(
-            Block::default()
-                .title("Task Scheduler")
-                .borders(Borders::ALL),
-        )
-        .style(Style::default().fg(text_color));
-    f.render_widget(paragraph, chunks[0]);
+            match share_entry(entry, &recipient, &path) {
+                Ok(()) => {
+                    log_info(
+                        "password_manager",
+                        &format!("Shared '{}' with {}", entry.service, recipient),
+                    );
+                    app_state.push_notification(
+                        format!("'{}' encrypted to {}", entry.service, path),
+                        NotificationSeverity::Info,
+                    );
+                    app_state.error_message = None;
+                    app_state.input_mode = InputMode::Viewing;
+                }
+                Err(e) => app_state.error_message = Some(format!("Sharing failed: {}", e)),
+            }
+        }
+        _ => {}
+    }
+    Ok(())
 }
 
-fn draw_add_task(f: &mut Frame, app_state: &AppState) {
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage(10),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
-
-    let highlight_style = Style::default()
-        .fg(Color::Yellow)
-        .bg(Color::DarkGray)
-        .add_modifier(Modifier::BOLD);
-    let normal_style = Style::default().fg(Color::White);
-
-    let priority_display = format!("{:?}", app_state.task_priority);
-
-    let fields = [
-        ("Title: ", &app_state.task_title, app_state.input_field == 0),
-        (
-            "Description: ",
-            &app_state.task_description,
-            app_state.input_field == 1,
-        ),
-        (
-            "Due Date (YYYY-MM-DD): ",
-            &app_state.task_due_date,
-            app_state.input_field == 2,
-        ),
-        (
-            "Tags (comma-separated): ",
-            &app_state.task_tags,
-            app_state.input_field == 3,
-        ),
-    ];
+fn handle_receive_share_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    // 'q' is not a quit key here since it can be part of a path
+    match code {
+        KeyCode::Esc => {
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::Normal;
+        }
+        KeyCode::Tab | KeyCode::BackTab => app_state.input_field = (app_state.input_field + 1) % 2,
+        KeyCode::Char(c) if app_state.input_field == 0 => app_state.share_path.push(c),
+        KeyCode::Backspace if app_state.input_field == 0 => {
+            app_state.share_path.pop();
+        }
+        KeyCode::Enter if app_state.input_field == 1 => {
+            if let Some(public_key) = app_state.sharing_public_key.clone() {
+                copy_to_clipboard(app_state, "Public key", &public_key);
+            }
+        }
+        KeyCode::Enter => {
+            let Some(ref vault) = app_state.vault else {
+                return Ok(());
+            };
+            let identity = match vault.sharing_identity() {
+                Ok(identity) => identity,
+                Err(e) => {
+                    app_state.error_message = Some(e.to_string());
+                    return Ok(());
+                }
+            };
+            let entry = match open_shared_entry(app_state.share_path.trim(), &identity) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    app_state.error_message = Some(e.to_string());
+                    return Ok(());
+                }
+            };
 
-    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
-        let text = Line::from(vec![
-            Span::raw(*label),
-            Span::styled(
-                (*value).clone(),
-                if *is_selected {
-                    highlight_style
-                } else {
-                    normal_style
-                },
-            ),
-        ]);
+            refresh_password_entries(app_state);
+            let duplicate =
+                find_duplicate_service(&app_state.password_entries, &entry.service, None).is_some();
+            let Some(ref vault) = app_state.vault else {
+                return Ok(());
+            };
+            match vault.save_password(&entry) {
+                Ok(()) => {
+                    log_info(
+                        "password_manager",
+                        &format!("Received shared entry '{}'", entry.service),
+                    );
+                    let (message, severity) = if duplicate {
+                        (
+                            format!(
+                                "'{}' added; the vault already had an entry for this service.",
+                                entry.service
+                            ),
+                            NotificationSeverity::Warning,
+                        )
+                    } else {
+                        (
+                            format!("'{}' added to the vault.", entry.service),
+                            NotificationSeverity::Info,
+                        )
+                    };
+                    app_state.push_notification(message, severity);
+                    refresh_password_entries(app_state);
+                    app_state.error_message = None;
+                    app_state.input_mode = InputMode::Normal;
+                }
+                Err(e) => app_state.error_message = Some(format!("Saving failed: {}", e)),
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-        // Determine border color: red if invalid, yellow if selected, default otherwise
-        let border_color = if i == 2 && app_state.task_date_invalid {
-            Color::Red
-        } else if *is_selected {
-            Color::Yellow
-        } else {
-            Color::Reset
-        };
+fn handle_vault_check_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let snapshot_count = app_state
+        .vault_check
+        .as_ref()
+        .map_or(0, |check| check.snapshots.len());
 
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(border_color));
+    if let ConfirmationDialogue::RestoreSnapshot(index, _) = app_state.confirmation_dialogue {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+                restore_selected_snapshot(app_state, index);
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+            }
+            _ => {} // Ignore other keys while dialog is active
+        }
+        return Ok(());
+    }
 
-        let paragraph = Paragraph::new(text).block(block);
-        f.render_widget(paragraph, layout[i + 1]);
+    match code {
+        KeyCode::Esc => {
+            app_state.vault_check = None;
+            app_state.input_mode = if app_state.vault.is_some() {
+                InputMode::Normal
+            } else {
+                InputMode::Unlocking
+            };
+        }
+        KeyCode::Up => {
+            app_state.selected_snapshot_index = app_state.selected_snapshot_index.saturating_sub(1);
+        }
+        KeyCode::Down if app_state.selected_snapshot_index + 1 < snapshot_count => {
+            app_state.selected_snapshot_index += 1;
+        }
+        KeyCode::Char('r') => {
+            let Some(snapshot) = app_state
+                .vault_check
+                .as_ref()
+                .and_then(|check| check.snapshots.get(app_state.selected_snapshot_index))
+            else {
+                return Ok(());
+            };
+            // Only snapshots that decrypt (now or with an earlier password) are worth restoring
+            if !matches!(snapshot.result, Ok(_) | Err(VaultError::WrongPassword)) {
+                app_state.status_message = Some(prepare_status_message(
+                    "This snapshot is damaged too; pick another one",
+                    StatusMessageType::Warning,
+                    3,
+                ));
+                return Ok(());
+            }
+            app_state.confirmation_dialogue = ConfirmationDialogue::RestoreSnapshot(
+                app_state.selected_snapshot_index,
+                snapshot_taken_at(snapshot),
+            );
+        }
+        _ => {}
     }
+    Ok(())
+}
 
-    // Similarly update your priority display with clearer highlighting
-    // Add a blinking cursor or indicator for the active field
-    if app_state.input_field < 4 {
-        let field_layout = layout[app_state.input_field + 1];
-        let cursor_x =
-            fields[app_state.input_field].0.len() + fields[app_state.input_field].1.len();
+/// When a checked snapshot was taken, for display
+fn snapshot_taken_at(check: &FileCheck) -> String {
+    format_timestamp(check.snapshot.as_ref().map(|snapshot| snapshot.taken_at_ms / 1000))
+}
 
-        // You could render a cursor indicator here
-        if cursor_x < field_layout.width as usize {
-            let cursor_rect = Rect::new(
-                field_layout.x + cursor_x as u16 + 1, // +1 for border
-                field_layout.y + 1,                   // +1 for border
-                1,
-                1,
-            );
-            f.render_widget(
-                Block::default().style(Style::default().bg(Color::White)),
-                cursor_rect,
+/// Replaces the vault with snapshot `index` of the last check and goes to the unlock screen
+fn restore_selected_snapshot(app_state: &mut AppState, index: usize) {
+    let (Some(path), Some(name)) = (active_vault_path(app_state), app_state.active_vault.clone())
+    else {
+        return;
+    };
+    let Some(snapshot) = app_state
+        .vault_check
+        .as_ref()
+        .and_then(|check| check.snapshots.get(index))
+        .and_then(|check| check.snapshot.clone())
+    else {
+        return;
+    };
+
+    match restore_snapshot(&path, &snapshot) {
+        Ok(kept) => {
+            let taken_at = format_timestamp(Some(snapshot.taken_at_ms / 1000));
+            log_warning(
+                "password_manager",
+                &format!("Vault '{}' restored from the snapshot of {}", name, taken_at),
             );
+            let message = match kept {
+                Some(kept) => format!(
+                    "Snapshot of {} restored; the replaced file was kept as {}",
+                    taken_at, kept
+                ),
+                None => format!("Snapshot of {} restored", taken_at),
+            };
+            app_state.push_notification(message, NotificationSeverity::Info);
+            lock_vault(app_state);
+            select_vault(app_state, name);
         }
+        Err(e) => app_state.push_notification(
+            format!("Restoring the snapshot failed: {}", e),
+            NotificationSeverity::Error,
+        ),
     }
+}
 
-    // Priority field for the add task screen
-    let priority_text = Line::from(vec![
-        Span::raw("Priority: "),
-        Span::styled(
-            priority_display,
-            Style::default().fg(get_priority_color(&app_state.task_priority)),
+/// File of the vault chosen in the picker
+fn active_vault_path(app_state: &AppState) -> Option<String> {
+    app_state
+        .active_vault
+        .as_ref()
+        .map(|name| vault_path(&security_config().vault_dir, name))
+}
+
+/// Lists the vaults in the vault directory and shows the picker
+///
+/// The first time, the old `passwords.json` is moved in as the `default` vault.
+fn open_vault_picker(app_state: &mut AppState) {
+    let vault_dir = security_config().vault_dir;
+    app_state.error_message = None;
+    match adopt_legacy_vault(&vault_dir) {
+        Ok(Some(name)) => app_state.push_notification(
+            format!("Moved {} to {} as the '{}' vault.", FILE_PATH, vault_dir, name),
+            NotificationSeverity::Info,
         ),
-        Span::raw(" (Use ↑↓ to change)"),
-    ]);
-    let priority_paragraph =
-        Paragraph::new(priority_text).block(Block::default().borders(Borders::ALL));
-    f.render_widget(priority_paragraph, layout[5]);
+        Ok(None) => {}
+        Err(e) => app_state.push_notification(
+            format!("Could not move {} to {}: {}", FILE_PATH, vault_dir, e),
+            NotificationSeverity::Error,
+        ),
+    }
 
-    // Instructions
-    let instructions = Paragraph::new("Press 'Enter' to Save, 'Esc' to Cancel")
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(instructions, layout[6]);
+    match list_vaults(&vault_dir) {
+        Ok(names) => app_state.vault_names = names,
+        Err(e) => {
+            app_state.vault_names.clear();
+            app_state.error_message = Some(format!("Failed to read {}: {}", vault_dir, e));
+        }
+    }
+    // Keep the last vault selected so switching back is a single Enter
+    app_state.selected_vault_index = app_state
+        .active_vault
+        .as_ref()
+        .and_then(|active| app_state.vault_names.iter().position(|name| name == active))
+        .unwrap_or(0);
+    app_state.new_vault_name = None;
+    app_state.input_mode = InputMode::VaultPicker;
+}
 
-    // Display status message if present
-    if let Some(ref msg) = app_state.status_message {
-        let status_block = Paragraph::new(msg.message.clone())
-            .style(Style::default().fg(match msg.message_type {
-                StatusMessageType::Info => Color::Blue,
-                StatusMessageType::Success => Color::Green,
-                StatusMessageType::Warning => Color::Yellow,
-                StatusMessageType::Error => Color::Red,
-            }))
-            .block(Block::default().borders(Borders::ALL).title("Status"));
+/// Moves on to the unlock screen for the vault called `name`
+fn select_vault(app_state: &mut AppState, name: String) {
+    app_state.vault_status = vault_status(&vault_path(&security_config().vault_dir, &name));
+    app_state.active_vault = Some(name);
+    app_state.input_field = 0;
+    app_state.error_message = None;
+    app_state.input_mode = InputMode::Unlocking;
+}
 
-        // Create a centered popup for the status message
-        let area = f.size();
-        let status_width = 60.min(area.width.saturating_sub(4));
-        let status_height = 5.min(area.height.saturating_sub(4));
+fn handle_vault_picker_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    if let Some(name) = app_state.new_vault_name.as_mut() {
+        match code {
+            KeyCode::Esc => {
+                app_state.new_vault_name = None;
+                app_state.error_message = None;
+            }
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            KeyCode::Enter => {
+                let name = name.trim().to_string();
+                if let Err(e) = validate_vault_name(&name) {
+                    app_state.error_message = Some(e.to_string());
+                } else if app_state.vault_names.contains(&name) {
+                    app_state.error_message =
+                        Some(format!("A vault named '{}' already exists.", name));
+                } else if let Err(e) = create_vault_dir(&security_config().vault_dir) {
+                    app_state.error_message =
+                        Some(format!("Failed to create the vault directory: {}", e));
+                } else {
+                    app_state.new_vault_name = None;
+                    select_vault(app_state, name);
+                }
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
 
-        let status_area = Rect::new(
-            ((area.width - status_width) / 2).max(0),
-            ((area.height - status_height) / 2).max(0),
-            status_width,
-            status_height,
+    match code {
+        KeyCode::Esc => {
+            app_state.error_message = None;
+            app_state.input_mode = InputMode::Normal;
+            app_state.active_menu = MenuItem::Main;
+        }
+        KeyCode::Up => {
+            app_state.selected_vault_index = app_state.selected_vault_index.saturating_sub(1);
+        }
+        KeyCode::Down if app_state.selected_vault_index + 1 < app_state.vault_names.len() => {
+            app_state.selected_vault_index += 1;
+        }
+        KeyCode::Char('n') => {
+            app_state.new_vault_name = Some(String::new());
+            app_state.error_message = None;
+        }
+        KeyCode::Enter => {
+            if let Some(name) = app_state.vault_names.get(app_state.selected_vault_index).cloned() {
+                select_vault(app_state, name);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Counts a wrong master password and applies the back-off and lockout/wipe policy
+fn record_failed_unlock(
+    app_state: &mut AppState,
+    path: &str,
+    vault_name: &str,
+    attempts: &mut UnlockAttempts,
+    security: &SecurityConfig,
+) {
+    attempts.record_failure(Utc::now().timestamp());
+    if let Err(e) = attempts.save(path) {
+        log_error(
+            "password_manager",
+            &format!("Failed to record an unlock attempt for '{}': {}", vault_name, e),
         );
+    }
+    log_warning(
+        "password_manager",
+        &format!(
+            "Wrong master password for vault '{}' ({} failed attempt(s))",
+            vault_name, attempts.failed
+        ),
+    );
+
+    if !attempts.limit_reached(security.max_unlock_attempts) {
+        app_state.error_message = Some(format!(
+            "Unlock failed: {}. Wait {} seconds before trying again.",
+            VaultError::WrongPassword,
+            attempts.backoff_seconds()
+        ));
+        return;
+    }
 
-        f.render_widget(Clear, status_area); // Clear the area
-        f.render_widget(status_block, status_area);
+    match security.unlock_failure_action {
+        UnlockFailureAction::Lockout => {
+            log_warning(
+                "password_manager",
+                &format!(
+                    "Vault '{}' locked out for {} minutes after {} failed attempts",
+                    vault_name, security.lockout_minutes, attempts.failed
+                ),
+            );
+            app_state.error_message = Some(format!(
+                "Too many failed attempts. Unlocking is blocked for {} minutes.",
+                security.lockout_minutes
+            ));
+        }
+        UnlockFailureAction::Wipe => {
+            match wipe_vault(path) {
+                Ok(()) => {
+                    log_error(
+                        "password_manager",
+                        &format!(
+                            "Vault '{}' wiped after {} failed attempts",
+                            vault_name, attempts.failed
+                        ),
+                    );
+                    app_state.push_notification(
+                        format!(
+                            "Vault '{}' was deleted after {} failed unlock attempts.",
+                            vault_name, attempts.failed
+                        ),
+                        NotificationSeverity::Error,
+                    );
+                }
+                Err(e) => {
+                    log_error(
+                        "password_manager",
+                        &format!("Failed to wipe vault '{}': {}", vault_name, e),
+                    );
+                    app_state.push_notification(
+                        format!("Failed to wipe vault '{}': {}", vault_name, e),
+                        NotificationSeverity::Error,
+                    );
+                }
+            }
+            app_state.active_vault = None;
+            open_vault_picker(app_state);
+        }
     }
 }
 
-fn draw_view_tasks(f: &mut Frame, app_state: &AppState) {
-    let text_color = get_text_color();
+fn handle_unlocking_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    _running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    // A new master password (create or migrate) has to be typed twice
+    let needs_confirmation = app_state.vault_status != VaultStatus::Protected;
 
-    // Create layout with a status bar
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .margin(1)
-        .constraints([
-            Constraint::Length(3), // Title
-            Constraint::Min(10),   // Task table
-            Constraint::Length(3), // Task details
-            Constraint::Length(1), // Status message
-            Constraint::Length(3), // Controls
-        ])
-        .split(f.size());
+    match code {
+        KeyCode::Esc => {
+            app_state.master_password.clear();
+            app_state.master_password_confirm.clear();
+            app_state.input_field = 0;
+            open_vault_picker(app_state);
+        }
+        KeyCode::Tab | KeyCode::BackTab if needs_confirmation => {
+            app_state.input_field = (app_state.input_field + 1) % 2;
+        }
+        KeyCode::Char(c) => match app_state.input_field {
+            1 if needs_confirmation => app_state.master_password_confirm.push(c),
+            _ => app_state.master_password.push(c),
+        },
+        KeyCode::Backspace => match app_state.input_field {
+            1 if needs_confirmation => {
+                app_state.master_password_confirm.pop();
+            }
+            _ => {
+                app_state.master_password.pop();
+            }
+        },
+        KeyCode::Enter => {
+            if app_state.master_password.is_empty() {
+                app_state.error_message = Some("Master password cannot be empty.".to_string());
+                return Ok(());
+            }
+            if needs_confirmation && app_state.master_password != app_state.master_password_confirm {
+                app_state.error_message = Some("Master passwords do not match.".to_string());
+                app_state.master_password_confirm.clear();
+                app_state.input_field = 1;
+                return Ok(());
+            }
 
-    // Title
-    let title = Paragraph::new(vec![
-        Line::from(vec![Span::styled(
-            "TASK MANAGER",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(vec![Span::raw(
-            "Press ↑↓ to navigate, 'r' to add reminder, 'c' to complete, 'd' to delete, 'Esc' to go back",
-        )]),
-    ])
-    .block(Block::default().borders(Borders::BOTTOM));
+            let Some(path) = active_vault_path(app_state) else {
+                open_vault_picker(app_state);
+                return Ok(());
+            };
 
-    f.render_widget(title, chunks[0]);
+            let security = security_config();
+            let mut attempts = UnlockAttempts::load(&path);
+            let wait = attempts.wait_seconds(
+                Utc::now().timestamp(),
+                security.max_unlock_attempts,
+                security.lockout_minutes * 60,
+            );
+            if app_state.vault_status == VaultStatus::Protected && wait > 0 {
+                app_state.master_password.clear();
+                app_state.error_message = Some(format!(
+                    "Too many failed attempts. Try again in {} seconds.",
+                    wait
+                ));
+                return Ok(());
+            }
 
-    // Task list
-    if let Some(ref task_scheduler) = app_state.task_scheduler {
-        if let Ok(scheduler) = task_scheduler.lock() {
-            let all_tasks = scheduler.get_all_tasks();
+            let result = match app_state.vault_status {
+                VaultStatus::Missing => Vault::create(&path, &app_state.master_password),
+                VaultStatus::Legacy => Vault::migrate_legacy(&path, &app_state.master_password),
+                VaultStatus::Protected => Vault::unlock(
+                    &path,
+                    &app_state.master_password,
+                    security.vault_snapshots,
+                ),
+            };
+            // A damaged vault is checked with the same password to find a snapshot to restore
+            let check = match result {
+                Err(VaultError::Tampered | VaultError::Malformed(_)) => {
+                    Some(check_vault(&path, &app_state.master_password))
+                }
+                _ => None,
+            };
 
-            if all_tasks.is_empty() {
-                let no_tasks = Paragraph::new("No tasks found. Press 'a' to add a new task.")
-                    .style(Style::default().fg(text_color))
-                    .block(Block::default().borders(Borders::ALL).title("Tasks"));
-                f.render_widget(no_tasks, chunks[1]);
-            } else {
-                // Sort tasks by due date
-                let mut sorted_tasks = all_tasks.clone();
-                sorted_tasks.sort_by(|a, b| a.due_date.cmp(&b.due_date));
+            app_state.master_password.clear();
+            app_state.master_password_confirm.clear();
+            app_state.input_field = 0;
+
+            let vault_name = app_state.active_vault.clone().unwrap_or_default();
+            match result {
+                Ok(mut vault) => {
+                    vault.set_snapshot_limit(security.vault_snapshots);
+                    let message = match app_state.vault_status {
+                        VaultStatus::Missing => "Vault created.",
+                        VaultStatus::Legacy => "Vault migrated to master password.",
+                        VaultStatus::Protected => "Vault unlocked.",
+                    };
+                    app_state.push_notification(message, NotificationSeverity::Info);
+                    if attempts.failed > 0 {
+                        app_state.push_notification(
+                            format!(
+                                "{} failed unlock attempt(s) since this vault was last opened.",
+                                attempts.failed
+                            ),
+                            NotificationSeverity::Warning,
+                        );
+                        log_info(
+                            "password_manager",
+                            &format!(
+                                "Vault '{}' unlocked after {} failed attempt(s)",
+                                vault_name, attempts.failed
+                            ),
+                        );
+                    } else {
+                        log_info("password_manager", &format!("Vault '{}' unlocked", vault_name));
+                    }
+                    if let Err(e) = UnlockAttempts::reset(&path) {
+                        log_error(
+                            "password_manager",
+                            &format!("Failed to reset the unlock attempts of '{}': {}", vault_name, e),
+                        );
+                    }
+                    app_state.vault = Some(vault);
+                    app_state.vault_status = VaultStatus::Protected;
+                    app_state.error_message = None;
+                    app_state.input_mode = InputMode::Normal;
+                    remind_key_rotation(app_state);
+                }
+                Err(VaultError::WrongPassword) => {
+                    record_failed_unlock(app_state, &path, &vault_name, &mut attempts, &security);
+                }
+                // Neither the vault nor a snapshot accepted the password
+                Err(_) if check.as_ref().is_some_and(|check| check.wrong_password()) => {
+                    record_failed_unlock(app_state, &path, &vault_name, &mut attempts, &security);
+                }
+                Err(e) => {
+                    // Tampering is a security event, not a typo — make it impossible to miss
+                    if matches!(e, VaultError::Tampered) {
+                        app_state.push_notification(e.to_string(), NotificationSeverity::Error);
+                        log_error(
+                            "password_manager",
+                            &format!("Vault '{}' failed its integrity check", vault_name),
+                        );
+                    }
+                    app_state.error_message = Some(format!("Unlock failed: {}", e));
+                    if let Some(check) = check {
+                        app_state.vault_check = Some(check);
+                        app_state.selected_snapshot_index = 0;
+                        app_state.input_mode = InputMode::VaultCheck;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-                // Table headers
-                let header_cells = vec![
-                    Cell::from("ID").style(Style::default().fg(Color::Yellow)),
-                    Cell::from("Title").style(Style::default().fg(Color::Yellow)),
-                    Cell::from("Due Date").style(Style::default().fg(Color::Yellow)),
-                    Cell::from("Priority").style(Style::default().fg(Color::Yellow)),
-                    Cell::from("Status").style(Style::default().fg(Color::Yellow)),
-                    Cell::from("Reminders").style(Style::default().fg(Color::Yellow)),
-                ];
+fn handle_email_config_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.email_address_invalid = false;
+        }
+        KeyCode::Char('t') => {
+            // Only start a new test if no test is currently running
+            if app_state.smtp_test_receiver.is_some() {
+                app_state.status_message = Some(prepare_status_message(
+                    "SMTP test already running...",
+                    StatusMessageType::Info,
+                    2,
+                ));
+                return Ok(());
+            }
 
-                let header = Row::new(header_cells)
-                    .style(Style::default().add_modifier(Modifier::BOLD))
-                    .height(1);
+            // Gate: SMTP-dependent features are disabled when email config load failed.
+            // Cleared automatically when set_email_config() succeeds — no restart needed.
+            let smtp_load_failed = app_state
+                .task_scheduler
+                .as_ref()
+                .and_then(|arc| arc.lock().ok())
+                .map(|sched| sched.email_config_load_error.is_some())
+                .unwrap_or(false);
+            if smtp_load_failed {
+                app_state.push_notification(
+                    "SMTP unavailable: email config failed to load. Save a valid config to re-enable.",
+                    NotificationSeverity::Warning,
+                );
+                return Ok(());
+            }
 
-                // Task rows
-                let rows = sorted_tasks.iter().map(|task| {
-                    let id = task.id.to_string();
-                    let title = task.title.clone();
-                    let due_date = crate::modules::task_scheduler::model::format_timestamp(task.due_date);
-                    let priority = format!("{:?}", task.priority);
-                    let status = format!("{:?}", task.status);
-                    let reminders = task.reminders.len().to_string();
+            // Clone the scheduler Arc to move into the thread
+            if let Some(ref task_scheduler) = app_state.task_scheduler {
+                let scheduler_arc = Arc::clone(task_scheduler);
+                let (tx, rx) = mpsc::channel::<Result<(), String>>();
 
-                    // Calculate color based on due date
-                    let now = Utc::now().timestamp();
-                    let row_color = if task.status == TaskStatus::Completed {
-                        Color::DarkGray
-                    } else if task.due_date < now {
-                        Color::Red
-                    } else if task.due_date - now < 86400 {
-                        // Within 24 hours
-                        Color::Yellow
+                thread::spawn(move || {
+                    if let Ok(scheduler) = scheduler_arc.lock() {
+                        let result = scheduler.test_email_config();
+                        let _ = tx.send(result);
                     } else {
-                        text_color
-                    };
-
-                    Row::new(vec![
-                        Cell::from(id),
-                        Cell::from(title),
-                        Cell::from(due_date),
-                        Cell::from(priority)
-                            .style(Style::default().fg(get_priority_color(&task.priority))),
-                        Cell::from(status)
-                            .style(Style::default().fg(get_status_color(&task.status))),
-                        Cell::from(reminders),
-                    ])
-                    .style(Style::default().fg(row_color))
+                        let _ = tx.send(Err("Failed to acquire scheduler lock".to_string()));
+                    }
                 });
 
-                // Create a stateful table
-                let mut state = ratatui::widgets::TableState::default();
-
-                // Find the selected task index
-                let selected_index = if let Some(selected_id) = app_state.selected_task_id {
-                    sorted_tasks.iter().position(|task| task.id == selected_id)
+                app_state.smtp_test_receiver = Some(rx);
+                app_state.status_message = Some(prepare_status_message(
+                    "Testing SMTP...",
+                    StatusMessageType::Info,
+                    30, // Long enough to cover the 30s timeout
+                ));
+            }
+        }
+        KeyCode::Tab => {
+            app_state.email_config_field = (app_state.email_config_field + 1) % 5;
+        }
+        KeyCode::BackTab => {
+            app_state.email_config_field = (app_state.email_config_field + 4) % 5;
+        }
+        KeyCode::Char(c) => {
+            match app_state.email_config_field {
+                0 => app_state.email_address.push(c),
+                1 => app_state.email_smtp_server.push(c),
+                2 => app_state.email_smtp_port.push(c),
+                3 => app_state.email_username.push(c),
+                4 => app_state.email_password.push(c),
+                _ => {}
+            }
+            // Live validate email field once '@' is present
+            if app_state.email_config_field == 0 {
+                if app_state.email_address.contains('@') {
+                    let valid = {
+                        let parts: Vec<&str> = app_state.email_address.splitn(2, '@').collect();
+                        parts.len() == 2
+                            && !parts[0].is_empty()
+                            && parts[1].contains('.')
+                            && !parts[1].starts_with('.')
+                            && !parts[1].ends_with('.')
+                    };
+                    app_state.email_address_invalid = !valid;
                 } else {
-                    None
-                };
-
-                // Set the selected index
-                if let Some(idx) = selected_index {
-                    state.select(Some(idx));
-                } else if !sorted_tasks.is_empty() {
-                    state.select(Some(0));
+                    app_state.email_address_invalid = false;
                 }
-
-                let table = Table::new(rows, [
-                        Constraint::Length(5),
-                        Constraint::Percentage(35),
-                        Constraint::Length(19),
-                        Constraint::Length(10),
-                        Constraint::Length(12),
-                        Constraint::Length(10),
-                    ])
-                    .header(header)
-                    .block(Block::default().title("Tasks").borders(Borders::ALL))
-                    .column_spacing(1)
-                    .highlight_style(
-                        Style::default()
-                            .bg(Color::DarkGray)
-                            .add_modifier(Modifier::BOLD),
-                    );
-
-                // Render stateful table
-                f.render_stateful_widget(table, chunks[1], &mut state);
-
-                // Show task details if a task is selected
-                if let Some(selected_id) = app_state.selected_task_id {
-                    if let Some(task) = scheduler.get_task(selected_id) {
-                        let details = vec![
-                            Line::from(vec![
-                                Span::styled(
-                                    "Title: ",
-                                    Style::default().add_modifier(Modifier::BOLD),
-                                ),
-                                Span::raw(&task.title),
-                            ]),
-                            Line::from(vec![
-                                Span::styled(
-                                    "Description: ",
-                                    Style::default().add_modifier(Modifier::BOLD),
-                                ),
-                                Span::raw(&task.description),
-                            ]),
-                            Line::from(vec![
-                                Span::styled(
-                                    "Tags: ",
-                                    Style::default().add_modifier(Modifier::BOLD),
-                                ),
-                                Span::raw(task.tags.join(", ")),
-                            ]),
-                        ];
-
-                        let details_block = Paragraph::new(details)
-                            .block(Block::default().title("Task Details").borders(Borders::ALL));
-                        f.render_widget(details_block, chunks[2]);
-                    }
+            }
+        }
+        KeyCode::Backspace => {
+            match app_state.email_config_field {
+                0 => {
+                    app_state.email_address.pop();
+                }
+                1 => {
+                    app_state.email_smtp_server.pop();
+                }
+                2 => {
+                    app_state.email_smtp_port.pop();
+                }
+                3 => {
+                    app_state.email_username.pop();
+                }
+                4 => {
+                    app_state.email_password.pop();
+                }
+                _ => {}
+            }
+            // Live validate email field once '@' is present
+            if app_state.email_config_field == 0 {
+                if app_state.email_address.contains('@') {
+                    let valid = {
+                        let parts: Vec<&str> = app_state.email_address.splitn(2, '@').collect();
+                        parts.len() == 2
+                            && !parts[0].is_empty()
+                            && parts[1].contains('.')
+                            && !parts[1].starts_with('.')
+                            && !parts[1].ends_with('.')
+                    };
+                    app_state.email_address_invalid = !valid;
+                } else {
+                    app_state.email_address_invalid = false;
                 }
             }
         }
-    } else {
-        // If no scheduler is available
-        let no_data = Paragraph::new("Task scheduler not initialized")
-            .style(Style::default().fg(text_color))
-            .block(Block::default().borders(Borders::ALL));
-
-        f.render_widget(no_data, chunks[1]);
-    }
+        KeyCode::Enter => {
+            println!("Saving email config...");
+            // Validate email format before empty-field check
+            if app_state.email_address_invalid {
+                app_state.input_mode = InputMode::ConfiguringEmail; // stay in form
+                app_state.email_config_field = 0; // jump to email field
+                app_state.push_notification(
+                    "Invalid email address format",
+                    NotificationSeverity::Warning,
+                );
+                return Ok(());
+            }
+            // Validate and save email config
+            if app_state.email_address.is_empty()
+                || app_state.email_smtp_server.is_empty()
+                || app_state.email_smtp_port.is_empty()
+                || app_state.email_username.is_empty()
+                || app_state.email_password.is_empty()
+            {
+                println!("Email config validation failed - empty fields");
+                app_state.status_message = Some(prepare_status_message(
+                    "All fields are required",
+                    StatusMessageType::Error,
+                    3,
+                ));
+                return Ok(());
+            }
 
-    // Status message
-    if let Some(ref status) = app_state.status_message {
-        let message_color = match status.message_type {
-            StatusMessageType::Info => Color::Blue,
-            StatusMessageType::Success => Color::Green,
-            StatusMessageType::Warning => Color::Yellow,
-            StatusMessageType::Error => Color::Red,
-        };
+            // Parse port
+            let port = match app_state.email_smtp_port.parse::<u16>() {
+                Ok(p) => p,
+                Err(_) => {
+                    app_state.status_message = Some(prepare_status_message(
+                        "Invalid port number",
+                        StatusMessageType::Error,
+                        3,
+                    ));
+                    return Ok(());
+                }
+            };
 
-        let status_text = Line::from(vec![
-            Span::styled("◆ ", Style::default().fg(message_color)),
-            Span::styled(&status.message, Style::default().fg(message_color)),
-        ]);
+            // Create config
+            let config = EmailConfig {
+                email: app_state.email_address.clone(),
+                smtp_server: app_state.email_smtp_server.clone(),
+                smtp_port: port,
+                username: app_state.email_username.clone(),
+                password: app_state.email_password.clone(),
+                retry_attempts: 3,
+                retry_delay_seconds: 300,
+            };
 
-        let status_bar = Paragraph::new(status_text);
-        f.render_widget(status_bar, chunks[3]);
+            // Set config — extract result first to avoid borrow conflicts
+            let save_result = app_state
+                .task_scheduler
+                .as_ref()
+                .and_then(|arc| arc.lock().ok())
+                .map(|mut scheduler| scheduler.set_email_config(config));
+            match save_result {
+                Some(Ok(())) => {
+                    // email_config_load_error is now None — SMTP features re-enable automatically
+                    app_state.status_message = Some(prepare_status_message(
+                        "Email configuration saved",
+                        StatusMessageType::Success,
+                        3,
+                    ));
+                    app_state.input_mode = InputMode::Normal;
+                }
+                Some(Err(e)) => {
+                    app_state.push_notification(
+                        format!("Email config save failed: {}", e),
+                        NotificationSeverity::Error,
+                    );
+                }
+                None => {
+                    // Scheduler not initialized — should not happen in normal flow
+                }
+            }
+        }
+        _ => {}
     }
+    Ok(())
+}
 
-    // Controls
-    let controls = Paragraph::new(vec![Line::from(vec![Span::raw(
-        "Actions: [r]Add Reminder [c]Complete [d]Delete | [↑↓]Navigate",
-    )])])
-    .block(Block::default().borders(Borders::TOP));
-
-    f.render_widget(controls, chunks[4]);
-}
+fn handle_adding_reminder_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::ViewingTasks;
+            app_state.reminder_date.clear();
+            app_state.reminder_time.clear();
+        }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        KeyCode::Tab => {
+            app_state.input_field = (app_state.input_field + 1) % 2;
+        }
+        KeyCode::BackTab => {
+            app_state.input_field = (app_state.input_field + 1) % 2;
+        }
+        KeyCode::Char(c) => match app_state.input_field {
+            0 => app_state.reminder_date.push(c),
+            1 => app_state.reminder_time.push(c),
+            _ => {}
+        },
+        KeyCode::Backspace => match app_state.input_field {
+            0 => {
+                app_state.reminder_date.pop();
+            }
+            1 => {
+                app_state.reminder_time.pop();
+            }
+            _ => {}
+        },
+        KeyCode::Up | KeyCode::Down => {
+            // Cycle through reminder types
+            app_state.reminder_type = match app_state.reminder_type {
+                ReminderType::Email => ReminderType::Notification,
+                ReminderType::Notification => ReminderType::Sms,
+                ReminderType::Sms => ReminderType::Both,
+                ReminderType::Both => ReminderType::All,
+                ReminderType::All => ReminderType::Email,
+            };
+        }
+        KeyCode::Enter => {
+            if let Some(task_id) = app_state.selected_task_id {
+                // Parse date and time
+                let reminder_datetime =
+                    format!("{} {}", app_state.reminder_date, app_state.reminder_time);
 
-fn draw_email_config(f: &mut Frame, app_state: &AppState) {
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage(10),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
+                if let Ok(dt) = NaiveDateTime::parse_from_str(
+                    &format!("{} 00", reminder_datetime),
+                    "%Y-%m-%d %H:%M %S",
+                ) {
+                    let timestamp = dt.timestamp();
 
-    let highlight_style = Style::default()
-        .fg(Color::Yellow)
-        .bg(Color::DarkGray) // More visible background
-        .add_modifier(Modifier::BOLD);
-    let normal_style = Style::default().fg(Color::White);
+                    // Add reminder
+                    if let Some(ref task_scheduler) = app_state.task_scheduler {
+                        if let Ok(mut scheduler) = task_scheduler.lock() {
+                            if let Err(e) = scheduler.add_reminder_to_task(
+                                task_id,
+                                timestamp,
+                                app_state.reminder_type.clone(),
+                            ) {
+                                app_state.status_message = Some(prepare_status_message(
+                                    &format!("Error: {}", e),
+                                    StatusMessageType::Error,
+                                    3,
+                                ));
+                            } else {
+                                app_state.status_message = Some(prepare_status_message(
+                                    "Reminder added successfully",
+                                    StatusMessageType::Success,
+                                    3,
+                                ));
+                                app_state.input_mode = InputMode::ViewingTasks;
+                            }
+                        }
+                    }
+                } else {
+                    app_state.status_message = Some(prepare_status_message(
+                        "Invalid date or time format",
+                        StatusMessageType::Error,
+                        3,
+                    ));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-    let fields = [
-        (
-            "Email Address: ",
-            &app_state.email_address,
-            app_state.email_config_field == 0,
-        ),
-        (
-            "SMTP Server: ",
-            &app_state.email_smtp_server,
-            app_state.email_config_field == 1,
-        ),
-        (
-            "SMTP Port: ",
-            &app_state.email_smtp_port,
-            app_state.email_config_field == 2,
-        ),
-        (
-            "Username: ",
-            &app_state.email_username,
-            app_state.email_config_field == 3,
-        ),
-        (
-            "Password: ",
-            &app_state.email_password,
-            app_state.email_config_field == 4,
-        ),
-    ];
+fn handle_enter_address_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    let continuous = app_state.selected_tool.as_deref() == Some("continuous ping");
+    let dns = app_state.selected_tool.as_deref() == Some("dns lookup");
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.address.clear();
+            app_state.selected_tool = None;
+            app_state.error_message = None;
+        }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        KeyCode::Tab if continuous || dns => {
+            app_state.input_field = (app_state.input_field + 1) % 3;
+        }
+        KeyCode::Enter if dns => start_dns_lookup(app_state),
+        KeyCode::Right | KeyCode::Char(' ') if dns && app_state.input_field == 1 => {
+            app_state.dns_record_type = app_state.dns_record_type.next();
+        }
+        KeyCode::Left if dns && app_state.input_field == 1 => {
+            app_state.dns_record_type = app_state.dns_record_type.previous();
+        }
+        KeyCode::Char(c) if dns && app_state.input_field == 2 => {
+            app_state.dns_resolvers_input.push(c);
+        }
+        KeyCode::Backspace if dns && app_state.input_field == 2 => {
+            app_state.dns_resolvers_input.pop();
+        }
+        // The record type is chosen with the arrow keys
+        KeyCode::Char(_) | KeyCode::Backspace if dns && app_state.input_field == 1 => {}
+        KeyCode::Enter if continuous => start_continuous_ping(app_state),
+        KeyCode::Enter if app_state.selected_tool.as_deref() == Some("traceroute") => {
+            start_traceroute(app_state)
+        }
+        KeyCode::Enter if app_state.selected_tool.as_deref() == Some("tcp ping") => {
+            start_tcp_ping(app_state)
+        }
+        KeyCode::Enter if app_state.selected_tool.as_deref() == Some("port check") => {
+            start_port_check(app_state)
+        }
+        KeyCode::Char(c)
+            if continuous
+                && (app_state.input_field == 2 && c.is_ascii_digit()
+                    || app_state.input_field == 1 && (c.is_ascii_digit() || c == '.')) =>
+        {
+            continuous_ping_field(app_state).push(c);
+        }
+        // The interval and count only take numbers
+        KeyCode::Char(_) if continuous && app_state.input_field > 0 => {}
+        KeyCode::Backspace if continuous => {
+            continuous_ping_field(app_state).pop();
+        }
+        KeyCode::Enter => {
+            if let Some(ref tool) = app_state.selected_tool {
+                let address = app_state.address.trim();
+                let result = match tool.as_str() {
+                    "ping" => ping(address).map_err(|e| e.to_string()).map(|output| {
+                        // Store the PingResult as a JSON string for simplicity
+                        serde_json::to_string(&output)
+                            .unwrap_or_else(|_| "Failed to serialize ping result.".to_string())
+                    }),
+                    // Handle other tools if necessary
+                    _ => Err("Unsupported tool".to_string()),
+                };
 
-    // Title
-    let title = Paragraph::new("Email Configuration")
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().add_modifier(Modifier::BOLD));
-    f.render_widget(title, layout[0]);
+                match result {
+                    Ok(json_result) => {
+                        app_state.result = Some(json_result);
+                        app_state.input_mode = InputMode::ViewResults;
+                    }
+                    Err(e) => {
+                        app_state.error_message = Some(format!("Error: {}", e));
+                        app_state.input_mode = InputMode::Normal;
+                    }
+                }
+                app_state.address.clear();
+            } else {
+                app_state.error_message = Some("No tool selected.".to_string());
+                app_state.input_mode = InputMode::Normal;
+            }
+        }
+        KeyCode::Char(c) => {
+            app_state.address.push(c);
+        }
+        KeyCode::Backspace => {
+            app_state.address.pop();
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
-        let display_value = if i == 4 && !value.is_empty() {
-            "*".repeat(value.len()) // Mask password
-        } else {
-            value.to_string()
-        };
+/// Starts a TCP ping to the typed `host:port` in the background
+///
+/// Against a filtered port every probe waits for the full timeout, so it must not block
+/// the UI.
+fn start_tcp_ping(app_state: &mut AppState) {
+    let target = app_state.address.trim().to_string();
+    if target.is_empty() {
+        app_state.error_message = Some("Enter a host:port target".to_string());
+        return;
+    }
 
-        let text = Line::from(vec![
-            Span::raw(*label),
-            Span::styled(
-                display_value,
-                if *is_selected {
-                    highlight_style
-                } else {
-                    normal_style
-                },
-            ),
-        ]);
+    let (tx, rx) = mpsc::channel();
+    {
+        let target = target.clone();
+        thread::spawn(move || {
+            let _ = tx.send(tcp_ping(&target, &PingOptions::default()).map_err(|e| e.to_string()));
+        });
+    }
+    app_state.tcp_ping_receiver = Some(rx);
+    app_state.result = Some(format!(
+        "TCP ping to {}...\n\nPress Esc to cancel, q to quit",
+        target
+    ));
+    app_state.error_message = None;
+    app_state.address.clear();
+    app_state.input_mode = InputMode::ViewResults;
+}
 
-        // Determine border color: red if invalid, yellow if selected, default otherwise
-        let border_color = if i == 0 && app_state.email_address_invalid {
-            Color::Red
-        } else if *is_selected {
-            Color::Yellow
-        } else {
-            Color::Reset
-        };
+/// Starts checking the typed `host:port` targets in the background
+///
+/// Filtered ports each wait for the full connect timeout, so the check must not block
+/// the UI.
+fn start_port_check(app_state: &mut AppState) {
+    let targets: Vec<String> = parse_targets(&app_state.address)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    if targets.is_empty() {
+        app_state.error_message = Some("Enter one or more host:port targets".to_string());
+        return;
+    }
 
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(border_color));
+    let (tx, rx) = mpsc::channel();
+    let count = targets.len();
+    thread::spawn(move || {
+        let targets: Vec<&str> = targets.iter().map(String::as_str).collect();
+        let _ = tx.send(check_ports(&targets, CONNECT_TIMEOUT));
+    });
+    app_state.port_check_receiver = Some(rx);
+    app_state.result = Some(format!(
+        "Checking {} port(s)...\n\nPress Esc to cancel, q to quit",
+        count
+    ));
+    app_state.error_message = None;
+    app_state.address.clear();
+    app_state.input_mode = InputMode::ViewResults;
+}
 
-        let paragraph = Paragraph::new(text).block(block);
+/// The continuous ping form field being typed into
+fn continuous_ping_field(app_state: &mut AppState) -> &mut String {
+    match app_state.input_field {
+        1 => &mut app_state.ping_interval_input,
+        2 => &mut app_state.ping_count_input,
+        _ => &mut app_state.address,
+    }
+}
 
-        f.render_widget(paragraph, layout[i + 1]);
+/// Starts pinging the typed address in the background with the typed interval and count
+fn start_continuous_ping(app_state: &mut AppState) {
+    let address = app_state.address.trim().to_string();
+    if address.is_empty() {
+        app_state.error_message = Some("Enter a host name or IP address".to_string());
+        return;
     }
+    let interval = match app_state.ping_interval_input.trim().parse::<f64>() {
+        Ok(seconds) if (0.1..=3600.0).contains(&seconds) => Duration::from_secs_f64(seconds),
+        _ => {
+            app_state.error_message =
+                Some("The interval must be between 0.1 and 3600 seconds".to_string());
+            return;
+        }
+    };
+    let count = match app_state.ping_count_input.trim() {
+        "" => 0,
+        count => match count.parse::<u16>() {
+            Ok(count) => count,
+            Err(_) => {
+                app_state.error_message =
+                    Some("The count must be at most 65535 (0 runs until stopped)".to_string());
+                return;
+            }
+        },
+    };
 
-    // Add instructions
-    let instructions = Paragraph::new(vec![
-        Line::from("Press 'Enter' to Save, 'Esc' to Cancel"),
-        Line::from("Press 't' to Test Configuration"),
-    ])
-    .block(Block::default().borders(Borders::ALL));
-
-    f.render_widget(instructions, layout[6]);
-
-    // Optional: Add a blinking cursor for better visual feedback
-    if app_state.email_config_field < 5 {
-        let field_layout = layout[app_state.email_config_field + 1];
-        let label_len = fields[app_state.email_config_field].0.len();
-        let value_len = if app_state.email_config_field == 4 {
-            // For password field
-            "*".repeat(fields[app_state.email_config_field].1.len())
-                .len()
-        } else {
-            fields[app_state.email_config_field].1.len()
-        };
-
-        // You can add a blinking cursor here if needed
+    let options = PingOptions {
+        count,
+        interval,
+        ..PingOptions::default()
+    };
+    let stop = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+    {
+        let stop = stop.clone();
+        thread::spawn(move || ping_continuous(&address, &options, &stop, &tx));
     }
+    app_state.ping_stop = stop;
+    app_state.ping_receiver = Some(rx);
+    app_state.live_ping = None;
+    app_state.live_ping_started = Instant::now();
+    app_state.error_message = None;
+    app_state.input_mode = InputMode::ContinuousPing;
 }
 
-fn draw_add_reminder(f: &mut Frame, app_state: &AppState) {
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(15),
-                Constraint::Percentage(25),
-            ]
-            .as_ref(),
-        )
-        .split(f.size());
+fn handle_continuous_ping_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    if app_state.ping_receiver.is_some() {
+        // Any key stops a running ping; the chart and statistics stay on screen
+        app_state.ping_stop.store(true, Ordering::Relaxed);
+        app_state.ping_receiver = None;
+        return Ok(());
+    }
 
-    let highlight_style = Style::default().fg(Color::Yellow).bg(Color::Blue);
-    let normal_style = Style::default().fg(Color::White);
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.selected_tool = None;
+            app_state.address.clear();
+            app_state.live_ping = None;
+            app_state.error_message = None;
+        }
+        // Back to the form to run it again or change the settings
+        KeyCode::Enter => {
+            app_state.input_mode = InputMode::EnterAddress;
+            app_state.live_ping = None;
+        }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        _ => {}
+    }
+    Ok(())
+}
 
-    // Title with selected task info
-    let mut title_text = "Add Reminder".to_string();
-    if let Some(task_id) = app_state.selected_task_id {
-        if let Some(ref task_scheduler) = app_state.task_scheduler {
-            if let Ok(scheduler) = task_scheduler.lock() {
-                if let Some(task) = scheduler.get_task(task_id) {
-                    title_text = format!("Add Reminder for: {}", task.title);
-                }
+/// Sends the typed DNS query to every typed resolver, or the system's, in the background
+fn start_dns_lookup(app_state: &mut AppState) {
+    let name = app_state.address.trim().to_string();
+    if name.is_empty() {
+        app_state.error_message = Some("Enter a domain name or IP address".to_string());
+        return;
+    }
+    let mut resolvers = Vec::new();
+    for token in app_state
+        .dns_resolvers_input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+    {
+        match parse_resolver(token) {
+            Some(resolver) => resolvers.push(resolver),
+            None => {
+                app_state.error_message = Some(format!(
+                    "Not a resolver address: {} (use an IP address, optionally with a port)",
+                    token
+                ));
+                return;
             }
         }
     }
-
-    let title = Paragraph::new(title_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().add_modifier(Modifier::BOLD));
-    f.render_widget(title, layout[0]);
-
-    let reminder_type_display = format!("{:?}", app_state.reminder_type);
-
-    let fields = [
-        (
-            "Date (YYYY-MM-DD): ",
-            &app_state.reminder_date,
-            app_state.input_field == 0,
-        ),
-        (
-            "Time (HH:MM): ",
-            &app_state.reminder_time,
-            app_state.input_field == 1,
-        ),
-    ];
-
-    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
-        let text = Line::from(vec![
-            Span::raw(*label),
-            Span::styled(
-                (*value).clone(),
-                if *is_selected {
-                    highlight_style
-                } else {
-                    normal_style
-                },
-            ),
-        ]);
-        let paragraph = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
-        f.render_widget(paragraph, layout[i + 1]);
+    if resolvers.is_empty() {
+        resolvers = system_resolvers();
+    }
+    if resolvers.is_empty() {
+        app_state.error_message =
+            Some("No nameserver in /etc/resolv.conf; enter a resolver address".to_string());
+        return;
     }
 
-    // Reminder type selection
-    let reminder_type_text = Line::from(vec![
-        Span::raw("Reminder Type: "),
-        Span::styled(reminder_type_display, Style::default().fg(Color::Cyan)),
-        Span::raw(" (Use ↑↓ to change)"),
-    ]);
-    let reminder_type_paragraph =
-        Paragraph::new(reminder_type_text).block(Block::default().borders(Borders::ALL));
-    f.render_widget(reminder_type_paragraph, layout[3]);
-
-    // Instructions
-    let instructions = Paragraph::new("Press 'Enter' to Save, 'Esc' to Cancel")
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(instructions, layout[4]);
+    let record_type = app_state.dns_record_type;
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(compare_resolvers(&resolvers, &name, record_type, QUERY_TIMEOUT));
+    });
+    app_state.dns_receiver = Some(rx);
+    app_state.dns_results.clear();
+    app_state.error_message = None;
+    app_state.input_mode = InputMode::DnsLookup;
 }
 
-// Helper function for priority colors
-fn get_priority_color(priority: &TaskPriority) -> Color {
-    match priority {
-        TaskPriority::Low => Color::Blue,
-        TaskPriority::Medium => Color::Green,
-        TaskPriority::High => Color::Yellow,
-        TaskPriority::Urgent => Color::Red,
+fn handle_dns_lookup_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.selected_tool = None;
+            app_state.address.clear();
+            app_state.dns_receiver = None;
+            app_state.dns_results.clear();
+            app_state.error_message = None;
+        }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        // The lookup finishes within one query timeout
+        _ if app_state.dns_receiver.is_some() => {}
+        // Ask the same resolvers for another record type
+        KeyCode::Right => {
+            app_state.dns_record_type = app_state.dns_record_type.next();
+            start_dns_lookup(app_state);
+        }
+        KeyCode::Left => {
+            app_state.dns_record_type = app_state.dns_record_type.previous();
+            start_dns_lookup(app_state);
+        }
+        KeyCode::Enter => {
+            app_state.input_mode = InputMode::EnterAddress;
+        }
+        _ => {}
     }
+    Ok(())
 }
 
-// Helper function for status colors
-fn get_status_color(status: &TaskStatus) -> Color {
-    match status {
-        TaskStatus::Pending => Color::Yellow,
-        TaskStatus::InProgress => Color::Blue,
-        TaskStatus::Completed => Color::Green,
-        TaskStatus::Cancelled => Color::Gray,
+/// Starts tracing the route to the typed address in the background
+fn start_traceroute(app_state: &mut AppState) {
+    let address = app_state.address.trim().to_string();
+    if address.is_empty() {
+        app_state.error_message = Some("Enter a host name or IP address".to_string());
+        return;
     }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+    {
+        let stop = stop.clone();
+        thread::spawn(move || traceroute(&address, &TraceOptions::default(), &stop, &tx));
+    }
+    app_state.trace_stop = stop;
+    app_state.trace_receiver = Some(rx);
+    app_state.trace_target = None;
+    app_state.trace_hops.clear();
+    app_state.error_message = None;
+    app_state.input_mode = InputMode::Traceroute;
 }
 
-fn handle_speed_test_running_mode(
+fn handle_traceroute_mode(
     app_state: &mut AppState,
     code: KeyCode,
     running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
+    if app_state.trace_receiver.is_some() {
+        // Any key stops the trace after the hop being probed
+        app_state.trace_stop.store(true, Ordering::Relaxed);
+        app_state.trace_receiver = None;
+        return Ok(());
+    }
+
     match code {
-        // Allow the user to cancel the live speed test by pressing Esc.
         KeyCode::Esc => {
             app_state.input_mode = InputMode::Normal;
-            app_state.speed_test_receiver = None;
+            app_state.selected_tool = None;
+            app_state.address.clear();
+            app_state.trace_hops.clear();
+            app_state.trace_target = None;
+            app_state.error_message = None;
+        }
+        KeyCode::Enter => {
+            app_state.input_mode = InputMode::EnterAddress;
         }
-        // Allow quitting
         KeyCode::Char('q') => {
             running.store(false, Ordering::Relaxed);
         }
@@ -2433,516 +4124,2837 @@ fn handle_speed_test_running_mode(
     Ok(())
 }
 
-fn handle_system_utilities_mode(
+fn handle_view_results_mode(
     app_state: &mut AppState,
     code: KeyCode,
     running: &Arc<AtomicBool>,
 ) -> io::Result<()> {
-    // Handle confirmation dialog if active
-    if app_state.confirmation_dialogue != ConfirmationDialogue::None {
-        match code {
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                if let ConfirmationDialogue::KillProcess(pid, ref name) =
-                    app_state.confirmation_dialogue.clone()
-                {
-                    // Actually kill the process
-                    let kill_result = if let Some(ref monitor) = app_state.system_monitor {
-                        if let Ok(mut monitor) = monitor.lock() {
-                            let result = monitor.kill_process(pid);
-
-                            // Refresh process list
-                            app_state.system_snapshot = Some(monitor.refresh_and_get());
-
-                            result
-                        } else {
-                            Err(format!("Failed to access system monitor"))
-                        }
-                    } else {
-                        Err(format!("System monitor not available"))
-                    };
-
-                    // Set appropriate status message
-                    match kill_result {
-                        Ok(_) => {
-                            // Success message
-                            app_state.status_message = Some(prepare_status_message(
-                                &format!(
-                                    "Process '{}' (PID: {}) terminated successfully",
-                                    name, pid
-                                ),
-                                StatusMessageType::Success,
-                                3,
-                            ));
-                        }
-                        Err(e) => {
-                            // Error message
-                            app_state.status_message = Some(prepare_status_message(
-                                &format!("Failed to kill process: {}", e),
-                                StatusMessageType::Error,
-                                5,
-                            ));
-                        }
-                    }
-                }
-                // Close the dialog
-                app_state.confirmation_dialogue = ConfirmationDialogue::None;
-                return Ok(());
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                // Cancel and close the dialog
-                app_state.confirmation_dialogue = ConfirmationDialogue::None;
-                return Ok(());
-            }
-            _ => return Ok(()), // Ignore other keys while dialog is active
-        }
-    }
-
-    // Regular key handling when no dialog is active
     match code {
-        KeyCode::Esc => {
-            // If we're in a tool view, go back to the system utilities menu
-            if app_state.selected_system_tool.is_some() {
-                app_state.selected_system_tool = None;
-            } else {
-                // Otherwise, go back to the main menu
-                app_state.active_menu = MenuItem::Main;
-                // No need to free up resources when just navigating back to main menu
-            }
+        KeyCode::Esc | KeyCode::Enter => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.result = None;
+            app_state.selected_tool = None;
+            // A TCP ping or port check still running finishes in its thread; its result is dropped
+            app_state.tcp_ping_receiver = None;
+            app_state.port_check_receiver = None;
         }
         KeyCode::Char('q') => {
             running.store(false, Ordering::Relaxed);
         }
-        KeyCode::Char('r') => {
-            // In the menu, 'r' selects resource monitor
-            if app_state.selected_system_tool.is_none() {
-                app_state.selected_system_tool = Some("resource_monitor".to_string());
-            } else {
-                // In a tool view, 'r' refreshes data
-                let refresh_result = if let Some(ref monitor) = app_state.system_monitor {
-                    if let Ok(mut monitor) = monitor.lock() {
-                        app_state.system_snapshot = Some(monitor.refresh_and_get());
-                        true
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                };
+        _ => {}
+    }
+    Ok(())
+}
 
-                // If in process manager and refresh was successful, show message
-                if refresh_result
-                    && app_state.selected_system_tool == Some("process_manager".to_string())
-                {
-                    app_state.status_message = Some(prepare_status_message(
-                        "Process list refreshed",
-                        StatusMessageType::Info,
-                        2,
-                    ));
-                }
-            }
+fn handle_adding_task_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.task_title.clear();
+            app_state.task_description.clear();
+            app_state.task_due_date.clear();
+            app_state.task_tags.clear();
+            app_state.task_date_invalid = false;
         }
-        KeyCode::Char('p') => {
-            // Handle different behaviors for 'p'
-            if app_state.selected_system_tool.is_none() {
-                // Open Process Manager
-                app_state.selected_system_tool = Some("process_manager".to_string());
-                app_state.selected_process_pid = None; // Reset selected process when opening
-
-                // Initialize process selection
-                initialize_process_selection(app_state);
-            } else if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                // Sort by PID in Process Manager
-                app_state.process_sort_type = ProcessSortType::Pid;
-                app_state.status_message = Some(prepare_status_message(
-                    "Sorted by Process ID",
-                    StatusMessageType::Info,
-                    2,
-                ));
-            }
+        KeyCode::Tab => {
+            app_state.input_field = (app_state.input_field + 1) % 4; // Cycle through title, description, due date, tags
         }
-        KeyCode::Char('d') => {
-            // Disk analyzer
-            if app_state.selected_system_tool.is_none() {
-                app_state.selected_system_tool = Some("disk_analyzer".to_string());
-            }
+        KeyCode::BackTab => {
+            app_state.input_field = (app_state.input_field + 3) % 4;
         }
-        KeyCode::Char('c') => {
-            // Handle different behaviors for 'c'
-            if app_state.selected_system_tool == Some("resource_monitor".to_string()) {
-                // CPU details view in resource monitor
-                app_state.system_view_mode = SystemViewMode::CpuDetails;
-            } else if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                // Sort by CPU in Process Manager
-                app_state.process_sort_type = ProcessSortType::CpuUsage;
-                app_state.status_message = Some(prepare_status_message(
-                    "Sorted by CPU usage",
-                    StatusMessageType::Info,
-                    2,
-                ));
-            }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
         }
-        KeyCode::Char('m') => {
-            // Handle different behaviors for 'm'
-            if app_state.selected_system_tool == Some("resource_monitor".to_string()) {
-                // Memory details view in resource monitor
-                app_state.system_view_mode = SystemViewMode::MemoryDetails;
-            } else if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                // Sort by memory in Process Manager
-                app_state.process_sort_type = ProcessSortType::MemoryUsage;
-                app_state.status_message = Some(prepare_status_message(
-                    "Sorted by memory usage",
-                    StatusMessageType::Info,
-                    2,
-                ));
+        KeyCode::Char(c) => {
+            match app_state.input_field {
+                0 => app_state.task_title.push(c),
+                1 => app_state.task_description.push(c),
+                2 => app_state.task_due_date.push(c),
+                3 => app_state.task_tags.push(c),
+                _ => {}
             }
-        }
-        KeyCode::Char('o') => {
-            // Overview in resource monitor
-            if app_state.selected_system_tool == Some("resource_monitor".to_string()) {
-                app_state.system_view_mode = SystemViewMode::Overview;
+            // Live validate date field after minimum length
+            if app_state.input_field == 2 {
+                if app_state.task_due_date.len() >= 10 {
+                    app_state.task_date_invalid = parse_due_date(&app_state.task_due_date).is_none();
+                } else {
+                    app_state.task_date_invalid = false;
+                }
             }
         }
-        KeyCode::Char('n') => {
-            // Sort by name
-            if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                app_state.process_sort_type = ProcessSortType::Name;
-                app_state.status_message = Some(prepare_status_message(
-                    "Sorted by process name",
-                    StatusMessageType::Info,
-                    2,
-                ));
+        KeyCode::Backspace => {
+            match app_state.input_field {
+                0 => {
+                    app_state.task_title.pop();
+                }
+                1 => {
+                    app_state.task_description.pop();
+                }
+                2 => {
+                    app_state.task_due_date.pop();
+                }
+                3 => {
+                    app_state.task_tags.pop();
+                }
+                _ => {}
             }
-        }
-        KeyCode::Char('t') => {
-            // Sort by runtime
-            if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                app_state.process_sort_type = ProcessSortType::Runtime;
-                app_state.status_message = Some(prepare_status_message(
-                    "Sorted by runtime",
-                    StatusMessageType::Info,
-                    2,
-                ));
+            // Live validate date field after minimum length
+            if app_state.input_field == 2 {
+                if app_state.task_due_date.len() >= 10 {
+                    app_state.task_date_invalid = parse_due_date(&app_state.task_due_date).is_none();
+                } else {
+                    app_state.task_date_invalid = false;
+                }
             }
         }
-        KeyCode::Char('k') => {
-            // Show kill confirmation for selected process
-            if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                if let Some(ref snapshot) = app_state.system_snapshot {
-                    if let Some(selected_pid) = app_state.selected_process_pid {
-                        // Find the process in the snapshot
-                        if let Some(process) = snapshot
-                            .top_processes
-                            .iter()
-                            .find(|p| p.pid == selected_pid)
-                        {
-                            let pid = process.pid;
-                            let name = process.name.clone();
+        KeyCode::Enter => {
+            // Parse due date — validate first
+            let due_date = parse_due_date(&app_state.task_due_date);
+            if due_date.is_none() {
+                app_state.task_date_invalid = true;
+                app_state.input_field = 2; // jump cursor to due date field
+                app_state.push_notification(
+                    "Invalid due date — use YYYY-MM-DD format",
+                    NotificationSeverity::Warning,
+                );
+                return Ok(());
+            }
 
-                            // Show confirmation dialog - ensure we use the same name!
-                            app_state.confirmation_dialogue =
-                                ConfirmationDialogue::KillProcess(pid, name);
-                        }
+            if let Some(timestamp) = due_date {
+                // Parse tags
+                let tags: Vec<String> = app_state
+                    .task_tags
+                    .split(',')
+                    .map(|tag| tag.trim().to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+
+                // Add task — extract result before borrowing app_state mutably
+                let add_result = app_state.task_scheduler.as_ref().and_then(|arc| {
+                    arc.lock().ok().map(|mut scheduler| {
+                        scheduler.add_task(
+                            app_state.task_title.clone(),
+                            app_state.task_description.clone(),
+                            timestamp,
+                            app_state.task_priority.clone(),
+                            tags,
+                        )
+                    })
+                });
+                match add_result {
+                    Some(Ok(_)) => {
+                        // Success message
+                        app_state.status_message = Some(prepare_status_message(
+                            "Task added successfully",
+                            StatusMessageType::Success,
+                            3,
+                        ));
+                        // Clear fields and return to normal mode
+                        app_state.task_title.clear();
+                        app_state.task_description.clear();
+                        app_state.task_due_date.clear();
+                        app_state.task_tags.clear();
+                        app_state.input_mode = InputMode::Normal;
+                    }
+                    Some(Err(e)) => {
+                        app_state.push_notification(
+                            format!("Task save failed: {}. Changes preserved in memory.", e),
+                            NotificationSeverity::Error,
+                        );
+                        // Still clear fields and continue — task is in memory
+                        app_state.task_title.clear();
+                        app_state.task_description.clear();
+                        app_state.task_due_date.clear();
+                        app_state.task_tags.clear();
+                        app_state.input_mode = InputMode::Normal;
+                    }
+                    None => {
+                        // Scheduler not initialized
                     }
                 }
             }
         }
-        // Add navigation for process list
         KeyCode::Up => {
-            if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                if let Some(ref snapshot) = app_state.system_snapshot {
-                    if snapshot.top_processes.is_empty() {
-                        return Ok(());
-                    }
-
-                    // Get a sorted copy of processes
-                    let mut sorted_processes = snapshot.top_processes.clone();
-                    match app_state.process_sort_type {
-                        ProcessSortType::Pid => {
-                            sorted_processes.sort_by_key(|p| p.pid);
-                        }
-                        ProcessSortType::Name => {
-                            sorted_processes
-                                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                        }
-                        ProcessSortType::CpuUsage => {
-                            sorted_processes.sort_by(|a, b| {
-                                b.cpu_usage
-                                    .partial_cmp(&a.cpu_usage)
-                                    .unwrap_or(std::cmp::Ordering::Equal)
-                            });
-                        }
-                        ProcessSortType::MemoryUsage => {
-                            sorted_processes.sort_by(|a, b| b.memory_usage.cmp(&a.memory_usage));
-                        }
-                        ProcessSortType::Runtime => {
-                            sorted_processes.sort_by(|a, b| b.run_time.cmp(&a.run_time));
-                        }
+            // Cycle through priorities
+            app_state.task_priority = match app_state.task_priority {
+                TaskPriority::Low => TaskPriority::Urgent,
+                TaskPriority::Medium => TaskPriority::Low,
+                TaskPriority::High => TaskPriority::Medium,
+                TaskPriority::Urgent => TaskPriority::High,
+            };
+        }
+        KeyCode::Down => {
+            // Cycle through priorities
+            app_state.task_priority = match app_state.task_priority {
+                TaskPriority::Low => TaskPriority::Medium,
+                TaskPriority::Medium => TaskPriority::High,
+                TaskPriority::High => TaskPriority::Urgent,
+                TaskPriority::Urgent => TaskPriority::Low,
+            };
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_viewing_tasks_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    match code {
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.selected_task_id = None;
+        }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        KeyCode::Char('r') => {
+            // Add reminder to selected task
+            if let Some(task_id) = app_state.selected_task_id {
+                app_state.input_mode = InputMode::AddingReminder;
+                app_state.reminder_date = Local::now().format("%Y-%m-%d").to_string();
+                app_state.reminder_time = Local::now().format("%H:%M").to_string();
+            }
+        }
+        KeyCode::Char('d') => {
+            // Delete selected task — extract result before borrowing app_state mutably
+            if let Some(task_id) = app_state.selected_task_id {
+                let delete_result = app_state
+                    .task_scheduler
+                    .as_ref()
+                    .and_then(|arc| arc.lock().ok())
+                    .map(|mut scheduler| scheduler.delete_task(task_id));
+                match delete_result {
+                    Some(Ok(())) => {
+                        app_state.status_message = Some(prepare_status_message(
+                            "Task deleted successfully",
+                            StatusMessageType::Success,
+                            3,
+                        ));
+                        app_state.selected_task_id = None;
+                    }
+                    Some(Err(ref e)) if e.contains("save") || e.contains("write") || e.contains("Error") => {
+                        // Save failure — task removed from memory but disk write failed
+                        let msg = format!("Task save failed: {}. Changes preserved in memory.", e);
+                        app_state.push_notification(msg, NotificationSeverity::Error);
+                        app_state.selected_task_id = None;
+                    }
+                    Some(Err(e)) => {
+                        app_state.status_message = Some(prepare_status_message(
+                            &format!("Error: {}", e),
+                            StatusMessageType::Error,
+                            3,
+                        ));
+                    }
+                    None => {}
+                }
+            }
+        }
+        KeyCode::Char('c') => {
+            // Complete selected task — extract result before borrowing app_state mutably
+            if let Some(task_id) = app_state.selected_task_id {
+                let update_result = app_state.task_scheduler.as_ref().and_then(|arc| {
+                    arc.lock().ok().and_then(|mut scheduler| {
+                        // Clone the task first to release the immutable borrow before calling update
+                        let updated = scheduler.get_task(task_id).map(|task| {
+                            let mut t = task.clone();
+                            t.status = TaskStatus::Completed;
+                            t
+                        });
+                        updated.map(|updated_task| scheduler.update_task(task_id, updated_task))
+                    })
+                });
+                match update_result {
+                    Some(Ok(())) => {
+                        app_state.status_message = Some(prepare_status_message(
+                            "Task marked as completed",
+                            StatusMessageType::Success,
+                            3,
+                        ));
+                    }
+                    Some(Err(ref e)) if e.contains("save") || e.contains("write") || e.contains("Error") => {
+                        // Save failure — in-memory state updated but disk write failed
+                        let msg = format!("Task save failed: {}. Changes preserved in memory.", e);
+                        app_state.push_notification(msg, NotificationSeverity::Error);
+                    }
+                    Some(Err(e)) => {
+                        app_state.status_message = Some(prepare_status_message(
+                            &format!("Error: {}", e),
+                            StatusMessageType::Error,
+                            3,
+                        ));
+                    }
+                    None => {}
+                }
+            }
+        }
+        KeyCode::Up => {
+            // Navigate tasks up
+            if let Some(ref task_scheduler) = app_state.task_scheduler {
+                if let Ok(scheduler) = task_scheduler.lock() {
+                    let tasks = scheduler.get_all_tasks();
+                    if tasks.is_empty() {
+                        return Ok(());
                     }
 
-                    if let Some(selected_pid) = app_state.selected_process_pid {
-                        // Find the current process in the sorted list
-                        if let Some(current_idx) =
-                            sorted_processes.iter().position(|p| p.pid == selected_pid)
-                        {
-                            if current_idx > 0 {
-                                // Select the previous process
-                                app_state.selected_process_pid =
-                                    Some(sorted_processes[current_idx - 1].pid);
+                    // Sort tasks by due date
+                    let mut task_ids: Vec<u32> = tasks.iter().map(|task| task.id).collect();
+                    task_ids.sort_by(|a, b| {
+                        match (scheduler.get_task(*a), scheduler.get_task(*b)) {
+                            (Some(task_a), Some(task_b)) => task_a.due_date.cmp(&task_b.due_date),
+                            _ => std::cmp::Ordering::Equal,
+                        }
+                    });
+
+                    if let Some(selected_id) = app_state.selected_task_id {
+                        if let Some(pos) = task_ids.iter().position(|id| *id == selected_id) {
+                            if pos > 0 {
+                                app_state.selected_task_id = Some(task_ids[pos - 1]);
                             }
                         }
-                    } else if !sorted_processes.is_empty() {
-                        // Select the first process if none is selected
-                        app_state.selected_process_pid = Some(sorted_processes[0].pid);
+                    } else if !task_ids.is_empty() {
+                        app_state.selected_task_id = Some(task_ids[0]);
                     }
                 }
             }
         }
         KeyCode::Down => {
-            if app_state.selected_system_tool == Some("process_manager".to_string()) {
-                if let Some(ref snapshot) = app_state.system_snapshot {
-                    if snapshot.top_processes.is_empty() {
+            // Navigate tasks down
+            if let Some(ref task_scheduler) = app_state.task_scheduler {
+                if let Ok(scheduler) = task_scheduler.lock() {
+                    let tasks = scheduler.get_all_tasks();
+                    if tasks.is_empty() {
                         return Ok(());
                     }
 
-                    // Get a sorted copy of processes
-                    let mut sorted_processes = snapshot.top_processes.clone();
-                    match app_state.process_sort_type {
-                        ProcessSortType::Pid => {
-                            sorted_processes.sort_by_key(|p| p.pid);
-                        }
-                        ProcessSortType::Name => {
-                            sorted_processes
-                                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-                        }
-                        ProcessSortType::CpuUsage => {
-                            sorted_processes.sort_by(|a, b| {
-                                b.cpu_usage
-                                    .partial_cmp(&a.cpu_usage)
-                                    .unwrap_or(std::cmp::Ordering::Equal)
-                            });
-                        }
-                        ProcessSortType::MemoryUsage => {
-                            sorted_processes.sort_by(|a, b| b.memory_usage.cmp(&a.memory_usage));
-                        }
-                        ProcessSortType::Runtime => {
-                            sorted_processes.sort_by(|a, b| b.run_time.cmp(&a.run_time));
+                    // Sort tasks by due date
+                    let mut task_ids: Vec<u32> = tasks.iter().map(|task| task.id).collect();
+                    task_ids.sort_by(|a, b| {
+                        match (scheduler.get_task(*a), scheduler.get_task(*b)) {
+                            (Some(task_a), Some(task_b)) => task_a.due_date.cmp(&task_b.due_date),
+                            _ => std::cmp::Ordering::Equal,
                         }
-                    }
+                    });
 
-                    if let Some(selected_pid) = app_state.selected_process_pid {
-                        // Find the current process in the sorted list
-                        if let Some(current_idx) =
-                            sorted_processes.iter().position(|p| p.pid == selected_pid)
-                        {
-                            if current_idx < sorted_processes.len() - 1 {
-                                // Select the next process
-                                app_state.selected_process_pid =
-                                    Some(sorted_processes[current_idx + 1].pid);
+                    if let Some(selected_id) = app_state.selected_task_id {
+                        if let Some(pos) = task_ids.iter().position(|id| *id == selected_id) {
+                            if pos < task_ids.len() - 1 {
+                                app_state.selected_task_id = Some(task_ids[pos + 1]);
                             }
                         }
-                    } else if !sorted_processes.is_empty() {
-                        // Select the first process if none is selected
-                        app_state.selected_process_pid = Some(sorted_processes[0].pid);
+                    } else if !task_ids.is_empty() {
+                        app_state.selected_task_id = Some(task_ids[0]);
                     }
                 }
             }
         }
         _ => {}
     }
-    Ok(())
+    Ok(())
+}
+
+// Helper function to parse due date
+fn parse_due_date(date_str: &str) -> Option<i64> {
+    // Try to parse as YYYY-MM-DD
+    if let Ok(date) =
+        NaiveDateTime::parse_from_str(&format!("{} 00:00:00", date_str), "%Y-%m-%d %H:%M:%S")
+    {
+        return Some(date.timestamp());
+    }
+
+    None
+}
+
+fn draw_task_scheduler_menu(f: &mut Frame) {
+    let text_color = get_text_color();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(f.size());
+
+    let text = vec![
+        Line::from(Span::raw("a. Add Task")),
+        Line::from(Span::raw("v. View Tasks")),
+        Line::from(Span::raw("e. Email Configuration")),
+        Line::from(Span::raw("Esc. Back to Main Menu")),
+        Line::from(Span::raw("Press 'q' to quit")),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title("Task Scheduler")
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(text_color));
+    f.render_widget(paragraph, chunks[0]);
+}
+
+fn draw_add_task(f: &mut Frame, app_state: &AppState) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(10),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    let highlight_style = Style::default()
+        .fg(Color::Yellow)
+        .bg(Color::DarkGray)
+        .add_modifier(Modifier::BOLD);
+    let normal_style = Style::default().fg(Color::White);
+
+    let priority_display = format!("{:?}", app_state.task_priority);
+
+    let fields = [
+        ("Title: ", &app_state.task_title, app_state.input_field == 0),
+        (
+            "Description: ",
+            &app_state.task_description,
+            app_state.input_field == 1,
+        ),
+        (
+            "Due Date (YYYY-MM-DD): ",
+            &app_state.task_due_date,
+            app_state.input_field == 2,
+        ),
+        (
+            "Tags (comma-separated): ",
+            &app_state.task_tags,
+            app_state.input_field == 3,
+        ),
+    ];
+
+    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
+        let text = Line::from(vec![
+            Span::raw(*label),
+            Span::styled(
+                (*value).clone(),
+                if *is_selected {
+                    highlight_style
+                } else {
+                    normal_style
+                },
+            ),
+        ]);
+
+        // Determine border color: red if invalid, yellow if selected, default otherwise
+        let border_color = if i == 2 && app_state.task_date_invalid {
+            Color::Red
+        } else if *is_selected {
+            Color::Yellow
+        } else {
+            Color::Reset
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+
+        let paragraph = Paragraph::new(text).block(block);
+        f.render_widget(paragraph, layout[i + 1]);
+    }
+
+    // Similarly update your priority display with clearer highlighting
+    // Add a blinking cursor or indicator for the active field
+    if app_state.input_field < 4 {
+        let field_layout = layout[app_state.input_field + 1];
+        let cursor_x =
+            fields[app_state.input_field].0.len() + fields[app_state.input_field].1.len();
+
+        // You could render a cursor indicator here
+        if cursor_x < field_layout.width as usize {
+            let cursor_rect = Rect::new(
+                field_layout.x + cursor_x as u16 + 1, // +1 for border
+                field_layout.y + 1,                   // +1 for border
+                1,
+                1,
+            );
+            f.render_widget(
+                Block::default().style(Style::default().bg(Color::White)),
+                cursor_rect,
+            );
+        }
+    }
+
+    // Priority field for the add task screen
+    let priority_text = Line::from(vec![
+        Span::raw("Priority: "),
+        Span::styled(
+            priority_display,
+            Style::default().fg(get_priority_color(&app_state.task_priority)),
+        ),
+        Span::raw(" (Use ↑↓ to change)"),
+    ]);
+    let priority_paragraph =
+        Paragraph::new(priority_text).block(Block::default().borders(Borders::ALL));
+    f.render_widget(priority_paragraph, layout[5]);
+
+    // Instructions
+    let instructions = Paragraph::new("Press 'Enter' to Save, 'Esc' to Cancel")
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instructions, layout[6]);
+
+    // Display status message if present
+    if let Some(ref msg) = app_state.status_message {
+        let status_block = Paragraph::new(msg.message.clone())
+            .style(Style::default().fg(match msg.message_type {
+                StatusMessageType::Info => Color::Blue,
+                StatusMessageType::Success => Color::Green,
+                StatusMessageType::Warning => Color::Yellow,
+                StatusMessageType::Error => Color::Red,
+            }))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+
+        // Create a centered popup for the status message
+        let area = f.size();
+        let status_width = 60.min(area.width.saturating_sub(4));
+        let status_height = 5.min(area.height.saturating_sub(4));
+
+        let status_area = Rect::new(
+            ((area.width - status_width) / 2).max(0),
+            ((area.height - status_height) / 2).max(0),
+            status_width,
+            status_height,
+        );
+
+        f.render_widget(Clear, status_area); // Clear the area
+        f.render_widget(status_block, status_area);
+    }
+}
+
+fn draw_view_tasks(f: &mut Frame, app_state: &AppState) {
+    let text_color = get_text_color();
+
+    // Create layout with a status bar
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(10),   // Task table
+            Constraint::Length(3), // Task details
+            Constraint::Length(1), // Status message
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.size());
+
+    // Title
+    let title = Paragraph::new(vec![
+        Line::from(vec![Span::styled(
+            "TASK MANAGER",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![Span::raw(
+            "Press ↑↓ to navigate, 'r' to add reminder, 'c' to complete, 'd' to delete, 'Esc' to go back",
+        )]),
+    ])
+    .block(Block::default().borders(Borders::BOTTOM));
+
+    f.render_widget(title, chunks[0]);
+
+    // Task list
+    if let Some(ref task_scheduler) = app_state.task_scheduler {
+        if let Ok(scheduler) = task_scheduler.lock() {
+            let all_tasks = scheduler.get_all_tasks();
+
+            if all_tasks.is_empty() {
+                let no_tasks = Paragraph::new("No tasks found. Press 'a' to add a new task.")
+                    .style(Style::default().fg(text_color))
+                    .block(Block::default().borders(Borders::ALL).title("Tasks"));
+                f.render_widget(no_tasks, chunks[1]);
+            } else {
+                // Sort tasks by due date
+                let mut sorted_tasks = all_tasks.clone();
+                sorted_tasks.sort_by(|a, b| a.due_date.cmp(&b.due_date));
+
+                // Table headers
+                let header_cells = vec![
+                    Cell::from("ID").style(Style::default().fg(Color::Yellow)),
+                    Cell::from("Title").style(Style::default().fg(Color::Yellow)),
+                    Cell::from("Due Date").style(Style::default().fg(Color::Yellow)),
+                    Cell::from("Priority").style(Style::default().fg(Color::Yellow)),
+                    Cell::from("Status").style(Style::default().fg(Color::Yellow)),
+                    Cell::from("Reminders").style(Style::default().fg(Color::Yellow)),
+                ];
+
+                let header = Row::new(header_cells)
+                    .style(Style::default().add_modifier(Modifier::BOLD))
+                    .height(1);
+
+                // Task rows
+                let rows = sorted_tasks.iter().map(|task| {
+                    let id = task.id.to_string();
+                    let title = task.title.clone();
+                    let due_date = crate::modules::task_scheduler::model::format_timestamp(task.due_date);
+                    let priority = format!("{:?}", task.priority);
+                    let status = format!("{:?}", task.status);
+                    let reminders = task.reminders.len().to_string();
+
+                    // Calculate color based on due date
+                    let now = Utc::now().timestamp();
+                    let row_color = if task.status == TaskStatus::Completed {
+                        Color::DarkGray
+                    } else if task.due_date < now {
+                        Color::Red
+                    } else if task.due_date - now < 86400 {
+                        // Within 24 hours
+                        Color::Yellow
+                    } else {
+                        text_color
+                    };
+
+                    Row::new(vec![
+                        Cell::from(id),
+                        Cell::from(title),
+                        Cell::from(due_date),
+                        Cell::from(priority)
+                            .style(Style::default().fg(get_priority_color(&task.priority))),
+                        Cell::from(status)
+                            .style(Style::default().fg(get_status_color(&task.status))),
+                        Cell::from(reminders),
+                    ])
+                    .style(Style::default().fg(row_color))
+                });
+
+                // Create a stateful table
+                let mut state = ratatui::widgets::TableState::default();
+
+                // Find the selected task index
+                let selected_index = if let Some(selected_id) = app_state.selected_task_id {
+                    sorted_tasks.iter().position(|task| task.id == selected_id)
+                } else {
+                    None
+                };
+
+                // Set the selected index
+                if let Some(idx) = selected_index {
+                    state.select(Some(idx));
+                } else if !sorted_tasks.is_empty() {
+                    state.select(Some(0));
+                }
+
+                let table = Table::new(rows, [
+                        Constraint::Length(5),
+                        Constraint::Percentage(35),
+                        Constraint::Length(19),
+                        Constraint::Length(10),
+                        Constraint::Length(12),
+                        Constraint::Length(10),
+                    ])
+                    .header(header)
+                    .block(Block::default().title("Tasks").borders(Borders::ALL))
+                    .column_spacing(1)
+                    .highlight_style(
+                        Style::default()
+                            .bg(Color::DarkGray)
+                            .add_modifier(Modifier::BOLD),
+                    );
+
+                // Render stateful table
+                f.render_stateful_widget(table, chunks[1], &mut state);
+
+                // Show task details if a task is selected
+                if let Some(selected_id) = app_state.selected_task_id {
+                    if let Some(task) = scheduler.get_task(selected_id) {
+                        let details = vec![
+                            Line::from(vec![
+                                Span::styled(
+                                    "Title: ",
+                                    Style::default().add_modifier(Modifier::BOLD),
+                                ),
+                                Span::raw(&task.title),
+                            ]),
+                            Line::from(vec![
+                                Span::styled(
+                                    "Description: ",
+                                    Style::default().add_modifier(Modifier::BOLD),
+                                ),
+                                Span::raw(&task.description),
+                            ]),
+                            Line::from(vec![
+                                Span::styled(
+                                    "Tags: ",
+                                    Style::default().add_modifier(Modifier::BOLD),
+                                ),
+                                Span::raw(task.tags.join(", ")),
+                            ]),
+                        ];
+
+                        let details_block = Paragraph::new(details)
+                            .block(Block::default().title("Task Details").borders(Borders::ALL));
+                        f.render_widget(details_block, chunks[2]);
+                    }
+                }
+            }
+        }
+    } else {
+        // If no scheduler is available
+        let no_data = Paragraph::new("Task scheduler not initialized")
+            .style(Style::default().fg(text_color))
+            .block(Block::default().borders(Borders::ALL));
+
+        f.render_widget(no_data, chunks[1]);
+    }
+
+    // Status message
+    if let Some(ref status) = app_state.status_message {
+        let message_color = match status.message_type {
+            StatusMessageType::Info => Color::Blue,
+            StatusMessageType::Success => Color::Green,
+            StatusMessageType::Warning => Color::Yellow,
+            StatusMessageType::Error => Color::Red,
+        };
+
+        let status_text = Line::from(vec![
+            Span::styled("◆ ", Style::default().fg(message_color)),
+            Span::styled(&status.message, Style::default().fg(message_color)),
+        ]);
+
+        let status_bar = Paragraph::new(status_text);
+        f.render_widget(status_bar, chunks[3]);
+    }
+
+    // Controls
+    let controls = Paragraph::new(vec![Line::from(vec![Span::raw(
+        "Actions: [r]Add Reminder [c]Complete [d]Delete | [↑↓]Navigate",
+    )])])
+    .block(Block::default().borders(Borders::TOP));
+
+    f.render_widget(controls, chunks[4]);
+}
+
+fn draw_email_config(f: &mut Frame, app_state: &AppState) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(10),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    let highlight_style = Style::default()
+        .fg(Color::Yellow)
+        .bg(Color::DarkGray) // More visible background
+        .add_modifier(Modifier::BOLD);
+    let normal_style = Style::default().fg(Color::White);
+
+    let fields = [
+        (
+            "Email Address: ",
+            app_state.email_address.as_str(),
+            app_state.email_config_field == 0,
+        ),
+        (
+            "SMTP Server: ",
+            app_state.email_smtp_server.as_str(),
+            app_state.email_config_field == 1,
+        ),
+        (
+            "SMTP Port: ",
+            app_state.email_smtp_port.as_str(),
+            app_state.email_config_field == 2,
+        ),
+        (
+            "Username: ",
+            app_state.email_username.as_str(),
+            app_state.email_config_field == 3,
+        ),
+        (
+            "Password: ",
+            app_state.email_password.as_str(),
+            app_state.email_config_field == 4,
+        ),
+    ];
+
+    // Title
+    let title = Paragraph::new("Email Configuration")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    f.render_widget(title, layout[0]);
+
+    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
+        let display_value = if i == 4 && !value.is_empty() {
+            "*".repeat(value.len()) // Mask password
+        } else {
+            value.to_string()
+        };
+
+        let text = Line::from(vec![
+            Span::raw(*label),
+            Span::styled(
+                display_value,
+                if *is_selected {
+                    highlight_style
+                } else {
+                    normal_style
+                },
+            ),
+        ]);
+
+        // Determine border color: red if invalid, yellow if selected, default otherwise
+        let border_color = if i == 0 && app_state.email_address_invalid {
+            Color::Red
+        } else if *is_selected {
+            Color::Yellow
+        } else {
+            Color::Reset
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+
+        let paragraph = Paragraph::new(text).block(block);
+
+        f.render_widget(paragraph, layout[i + 1]);
+    }
+
+    // Add instructions
+    let instructions = Paragraph::new(vec![
+        Line::from("Press 'Enter' to Save, 'Esc' to Cancel"),
+        Line::from("Press 't' to Test Configuration"),
+    ])
+    .block(Block::default().borders(Borders::ALL));
+
+    f.render_widget(instructions, layout[6]);
+
+    // Optional: Add a blinking cursor for better visual feedback
+    if app_state.email_config_field < 5 {
+        let field_layout = layout[app_state.email_config_field + 1];
+        let label_len = fields[app_state.email_config_field].0.len();
+        let value_len = if app_state.email_config_field == 4 {
+            // For password field
+            "*".repeat(fields[app_state.email_config_field].1.len())
+                .len()
+        } else {
+            fields[app_state.email_config_field].1.len()
+        };
+
+        // You can add a blinking cursor here if needed
+    }
+}
+
+fn draw_add_reminder(f: &mut Frame, app_state: &AppState) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(15),
+                Constraint::Percentage(25),
+            ]
+            .as_ref(),
+        )
+        .split(f.size());
+
+    let highlight_style = Style::default().fg(Color::Yellow).bg(Color::Blue);
+    let normal_style = Style::default().fg(Color::White);
+
+    // Title with selected task info
+    let mut title_text = "Add Reminder".to_string();
+    if let Some(task_id) = app_state.selected_task_id {
+        if let Some(ref task_scheduler) = app_state.task_scheduler {
+            if let Ok(scheduler) = task_scheduler.lock() {
+                if let Some(task) = scheduler.get_task(task_id) {
+                    title_text = format!("Add Reminder for: {}", task.title);
+                }
+            }
+        }
+    }
+
+    let title = Paragraph::new(title_text)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    f.render_widget(title, layout[0]);
+
+    let reminder_type_display = format!("{:?}", app_state.reminder_type);
+
+    let fields = [
+        (
+            "Date (YYYY-MM-DD): ",
+            &app_state.reminder_date,
+            app_state.input_field == 0,
+        ),
+        (
+            "Time (HH:MM): ",
+            &app_state.reminder_time,
+            app_state.input_field == 1,
+        ),
+    ];
+
+    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
+        let text = Line::from(vec![
+            Span::raw(*label),
+            Span::styled(
+                (*value).clone(),
+                if *is_selected {
+                    highlight_style
+                } else {
+                    normal_style
+                },
+            ),
+        ]);
+        let paragraph = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(paragraph, layout[i + 1]);
+    }
+
+    // Reminder type selection
+    let reminder_type_text = Line::from(vec![
+        Span::raw("Reminder Type: "),
+        Span::styled(reminder_type_display, Style::default().fg(Color::Cyan)),
+        Span::raw(" (Use ↑↓ to change)"),
+    ]);
+    let reminder_type_paragraph =
+        Paragraph::new(reminder_type_text).block(Block::default().borders(Borders::ALL));
+    f.render_widget(reminder_type_paragraph, layout[3]);
+
+    // Instructions
+    let instructions = Paragraph::new("Press 'Enter' to Save, 'Esc' to Cancel")
+        .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instructions, layout[4]);
+}
+
+// Helper function for priority colors
+fn get_priority_color(priority: &TaskPriority) -> Color {
+    match priority {
+        TaskPriority::Low => Color::Blue,
+        TaskPriority::Medium => Color::Green,
+        TaskPriority::High => Color::Yellow,
+        TaskPriority::Urgent => Color::Red,
+    }
+}
+
+// Helper function for status colors
+fn get_status_color(status: &TaskStatus) -> Color {
+    match status {
+        TaskStatus::Pending => Color::Yellow,
+        TaskStatus::InProgress => Color::Blue,
+        TaskStatus::Completed => Color::Green,
+        TaskStatus::Cancelled => Color::Gray,
+    }
+}
+
+fn handle_speed_test_running_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    match code {
+        // Allow the user to cancel the live speed test by pressing Esc.
+        KeyCode::Esc => {
+            app_state.input_mode = InputMode::Normal;
+            app_state.speed_test_receiver = None;
+        }
+        // Allow quitting
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn handle_system_utilities_mode(
+    app_state: &mut AppState,
+    code: KeyCode,
+    running: &Arc<AtomicBool>,
+) -> io::Result<()> {
+    // Handle confirmation dialog if active
+    if app_state.confirmation_dialogue != ConfirmationDialogue::None {
+        match code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                if let ConfirmationDialogue::KillProcess(pid, ref name) =
+                    app_state.confirmation_dialogue.clone()
+                {
+                    // Actually kill the process
+                    let kill_result = if let Some(ref monitor) = app_state.system_monitor {
+                        if let Ok(mut monitor) = monitor.lock() {
+                            let result = monitor.kill_process(pid);
+
+                            // Refresh process list
+                            app_state.system_snapshot = Some(monitor.refresh_and_get());
+
+                            result
+                        } else {
+                            Err(format!("Failed to access system monitor"))
+                        }
+                    } else {
+                        Err(format!("System monitor not available"))
+                    };
+
+                    // Set appropriate status message
+                    match kill_result {
+                        Ok(_) => {
+                            // Success message
+                            app_state.status_message = Some(prepare_status_message(
+                                &format!(
+                                    "Process '{}' (PID: {}) terminated successfully",
+                                    name, pid
+                                ),
+                                StatusMessageType::Success,
+                                3,
+                            ));
+                        }
+                        Err(e) => {
+                            // Error message
+                            app_state.status_message = Some(prepare_status_message(
+                                &format!("Failed to kill process: {}", e),
+                                StatusMessageType::Error,
+                                5,
+                            ));
+                        }
+                    }
+                }
+                // Close the dialog
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+                return Ok(());
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                // Cancel and close the dialog
+                app_state.confirmation_dialogue = ConfirmationDialogue::None;
+                return Ok(());
+            }
+            _ => return Ok(()), // Ignore other keys while dialog is active
+        }
+    }
+
+    // Regular key handling when no dialog is active
+    match code {
+        KeyCode::Esc => {
+            // If we're in a tool view, go back to the system utilities menu
+            if app_state.selected_system_tool.is_some() {
+                app_state.selected_system_tool = None;
+            } else {
+                // Otherwise, go back to the main menu
+                app_state.active_menu = MenuItem::Main;
+                // No need to free up resources when just navigating back to main menu
+            }
+        }
+        KeyCode::Char('q') => {
+            running.store(false, Ordering::Relaxed);
+        }
+        KeyCode::Char('r') => {
+            // In the menu, 'r' selects resource monitor
+            if app_state.selected_system_tool.is_none() {
+                app_state.selected_system_tool = Some("resource_monitor".to_string());
+            } else {
+                // In a tool view, 'r' refreshes data
+                let refresh_result = if let Some(ref monitor) = app_state.system_monitor {
+                    if let Ok(mut monitor) = monitor.lock() {
+                        app_state.system_snapshot = Some(monitor.refresh_and_get());
+                        true
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                };
+
+                // If in process manager and refresh was successful, show message
+                if refresh_result
+                    && app_state.selected_system_tool == Some("process_manager".to_string())
+                {
+                    app_state.status_message = Some(prepare_status_message(
+                        "Process list refreshed",
+                        StatusMessageType::Info,
+                        2,
+                    ));
+                }
+            }
+        }
+        KeyCode::Char('p') => {
+            // Handle different behaviors for 'p'
+            if app_state.selected_system_tool.is_none() {
+                // Open Process Manager
+                app_state.selected_system_tool = Some("process_manager".to_string());
+                app_state.selected_process_pid = None; // Reset selected process when opening
+
+                // Initialize process selection
+                initialize_process_selection(app_state);
+            } else if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                // Sort by PID in Process Manager
+                app_state.process_sort_type = ProcessSortType::Pid;
+                app_state.status_message = Some(prepare_status_message(
+                    "Sorted by Process ID",
+                    StatusMessageType::Info,
+                    2,
+                ));
+            }
+        }
+        KeyCode::Char('d') => {
+            // Disk analyzer
+            if app_state.selected_system_tool.is_none() {
+                app_state.selected_system_tool = Some("disk_analyzer".to_string());
+            }
+        }
+        KeyCode::Char('c') => {
+            // Handle different behaviors for 'c'
+            if app_state.selected_system_tool == Some("resource_monitor".to_string()) {
+                // CPU details view in resource monitor
+                app_state.system_view_mode = SystemViewMode::CpuDetails;
+            } else if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                // Sort by CPU in Process Manager
+                app_state.process_sort_type = ProcessSortType::CpuUsage;
+                app_state.status_message = Some(prepare_status_message(
+                    "Sorted by CPU usage",
+                    StatusMessageType::Info,
+                    2,
+                ));
+            }
+        }
+        KeyCode::Char('m') => {
+            // Handle different behaviors for 'm'
+            if app_state.selected_system_tool == Some("resource_monitor".to_string()) {
+                // Memory details view in resource monitor
+                app_state.system_view_mode = SystemViewMode::MemoryDetails;
+            } else if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                // Sort by memory in Process Manager
+                app_state.process_sort_type = ProcessSortType::MemoryUsage;
+                app_state.status_message = Some(prepare_status_message(
+                    "Sorted by memory usage",
+                    StatusMessageType::Info,
+                    2,
+                ));
+            }
+        }
+        KeyCode::Char('o') => {
+            // Overview in resource monitor
+            if app_state.selected_system_tool == Some("resource_monitor".to_string()) {
+                app_state.system_view_mode = SystemViewMode::Overview;
+            }
+        }
+        KeyCode::Char('n') => {
+            // Sort by name
+            if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                app_state.process_sort_type = ProcessSortType::Name;
+                app_state.status_message = Some(prepare_status_message(
+                    "Sorted by process name",
+                    StatusMessageType::Info,
+                    2,
+                ));
+            }
+        }
+        KeyCode::Char('t') => {
+            // Sort by runtime
+            if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                app_state.process_sort_type = ProcessSortType::Runtime;
+                app_state.status_message = Some(prepare_status_message(
+                    "Sorted by runtime",
+                    StatusMessageType::Info,
+                    2,
+                ));
+            }
+        }
+        KeyCode::Char('k') => {
+            // Show kill confirmation for selected process
+            if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                if let Some(ref snapshot) = app_state.system_snapshot {
+                    if let Some(selected_pid) = app_state.selected_process_pid {
+                        // Find the process in the snapshot
+                        if let Some(process) = snapshot
+                            .top_processes
+                            .iter()
+                            .find(|p| p.pid == selected_pid)
+                        {
+                            let pid = process.pid;
+                            let name = process.name.clone();
+
+                            // Show confirmation dialog - ensure we use the same name!
+                            app_state.confirmation_dialogue =
+                                ConfirmationDialogue::KillProcess(pid, name);
+                        }
+                    }
+                }
+            }
+        }
+        // Add navigation for process list
+        KeyCode::Up => {
+            if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                if let Some(ref snapshot) = app_state.system_snapshot {
+                    if snapshot.top_processes.is_empty() {
+                        return Ok(());
+                    }
+
+                    // Get a sorted copy of processes
+                    let mut sorted_processes = snapshot.top_processes.clone();
+                    match app_state.process_sort_type {
+                        ProcessSortType::Pid => {
+                            sorted_processes.sort_by_key(|p| p.pid);
+                        }
+                        ProcessSortType::Name => {
+                            sorted_processes
+                                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+                        }
+                        ProcessSortType::CpuUsage => {
+                            sorted_processes.sort_by(|a, b| {
+                                b.cpu_usage
+                                    .partial_cmp(&a.cpu_usage)
+                                    .unwrap_or(std::cmp::Ordering::Equal)
+                            });
+                        }
+                        ProcessSortType::MemoryUsage => {
+                            sorted_processes.sort_by(|a, b| b.memory_usage.cmp(&a.memory_usage));
+                        }
+                        ProcessSortType::Runtime => {
+                            sorted_processes.sort_by(|a, b| b.run_time.cmp(&a.run_time));
+                        }
+                    }
+
+                    if let Some(selected_pid) = app_state.selected_process_pid {
+                        // Find the current process in the sorted list
+                        if let Some(current_idx) =
+                            sorted_processes.iter().position(|p| p.pid == selected_pid)
+                        {
+                            if current_idx > 0 {
+                                // Select the previous process
+                                app_state.selected_process_pid =
+                                    Some(sorted_processes[current_idx - 1].pid);
+                            }
+                        }
+                    } else if !sorted_processes.is_empty() {
+                        // Select the first process if none is selected
+                        app_state.selected_process_pid = Some(sorted_processes[0].pid);
+                    }
+                }
+            }
+        }
+        KeyCode::Down => {
+            if app_state.selected_system_tool == Some("process_manager".to_string()) {
+                if let Some(ref snapshot) = app_state.system_snapshot {
+                    if snapshot.top_processes.is_empty() {
+                        return Ok(());
+                    }
+
+                    // Get a sorted copy of processes
+                    let mut sorted_processes = snapshot.top_processes.clone();
+                    match app_state.process_sort_type {
+                        ProcessSortType::Pid => {
+                            sorted_processes.sort_by_key(|p| p.pid);
+                        }
+                        ProcessSortType::Name => {
+                            sorted_processes
+                                .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+                        }
+                        ProcessSortType::CpuUsage => {
+                            sorted_processes.sort_by(|a, b| {
+                                b.cpu_usage
+                                    .partial_cmp(&a.cpu_usage)
+                                    .unwrap_or(std::cmp::Ordering::Equal)
+                            });
+                        }
+                        ProcessSortType::MemoryUsage => {
+                            sorted_processes.sort_by(|a, b| b.memory_usage.cmp(&a.memory_usage));
+                        }
+                        ProcessSortType::Runtime => {
+                            sorted_processes.sort_by(|a, b| b.run_time.cmp(&a.run_time));
+                        }
+                    }
+
+                    if let Some(selected_pid) = app_state.selected_process_pid {
+                        // Find the current process in the sorted list
+                        if let Some(current_idx) =
+                            sorted_processes.iter().position(|p| p.pid == selected_pid)
+                        {
+                            if current_idx < sorted_processes.len() - 1 {
+                                // Select the next process
+                                app_state.selected_process_pid =
+                                    Some(sorted_processes[current_idx + 1].pid);
+                            }
+                        }
+                    } else if !sorted_processes.is_empty() {
+                        // Select the first process if none is selected
+                        app_state.selected_process_pid = Some(sorted_processes[0].pid);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// === Drawing Functions ===
+
+static DARK_MODE: Lazy<bool> = Lazy::new(|| {
+    let output = Command::new("osascript")
+        .arg("-e")
+        .arg(
+            "tell application \"System Events\" to tell appearance preferences to return dark mode",
+        )
+        .output()
+        .unwrap_or_else(|_| {
+            eprintln!("Failed to execute osascript");
+            std::process::Output {
+                status: std::process::ExitStatus::from_raw(0),
+                stdout: b"false".to_vec(),
+                stderr: Vec::new(),
+            }
+        });
+    String::from_utf8_lossy(&output.stdout).trim() == "true"
+});
+
+fn get_text_color() -> ratatui::style::Color {
+    if *DARK_MODE {
+        ratatui::style::Color::White
+    } else {
+        ratatui::style::Color::Black
+    }
+}
+
+fn is_dark_mode() -> bool {
+    let Ok(output) = Command::new("osascript")
+        .arg("-e")
+        .arg(
+            "tell application \"System Events\" to tell appearance preferences to return dark mode",
+        )
+        .output()
+    else {
+        return false; // fallback if osascript unavailable
+    };
+
+    let result = String::from_utf8_lossy(&output.stdout);
+    result.trim() == "true"
+}
+
+fn draw_main_menu(f: &mut Frame) {
+    let text_color = get_text_color();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(f.size());
+
+    let text = vec![
+        Line::from(Span::raw("1. Password Manager")),
+        Line::from(Span::raw("2. Network Tools")),
+        Line::from(Span::raw("3. System Utilities")),
+        Line::from(Span::raw("4. Task Manager")),
+        Line::from(Span::raw("Press 'q' to quit")),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().title("Main Menu").borders(Borders::ALL))
+        .style(Style::default().fg(text_color)); // Use dynamic text color
+    f.render_widget(paragraph, chunks[0]);
+}
+
+fn draw_password_manager_menu(f: &mut Frame, app_state: &AppState) {
+    let text_color = get_text_color();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(100)].as_ref())
+        .split(f.size());
+
+    let text = vec![
+        Line::from(Span::raw("a. Add Password")),
+        Line::from(Span::raw("n. Add Secure Note")),
+        Line::from(Span::raw("y. Add SSH Key")),
+        Line::from(Span::raw("v. View Passwords")),
+        Line::from(Span::raw("/. Search Passwords")),
+        Line::from(Span::raw("i. Import Passwords (CSV, Bitwarden JSON, KeePass XML)")),
+        Line::from(Span::raw("x. Export Passwords (portable vault or CSV)")),
+        Line::from(Span::raw("s. Security Audit")),
+        Line::from(Span::raw("k. Change Master Password (re-encrypts the vault)")),
+        Line::from(Span::raw("c. Check Vault Integrity and Snapshots")),
+        Line::from(Span::raw("r. Receive a Shared Entry")),
+        Line::from(Span::raw("o. Open Another Vault")),
+        Line::from(Span::raw("Esc. Back to Main Menu")),
+        Line::from(Span::raw("Press 'q' to quit")),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(match app_state.active_vault {
+                    Some(ref name) => format!("Password Manager - {}", name),
+                    None => "Password Manager".to_string(),
+                })
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(text_color)); // Set text color to black
+    f.render_widget(paragraph, chunks[0]);
+}
+
+fn draw_import_path(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(8), Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(f.area());
+
+    let field_style = |index: usize| {
+        if app_state.input_field == index {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    };
+
+    let mut text = vec![
+        Line::from(Span::raw(
+            "Path of the export to import (.csv, Bitwarden .json, KeePass .xml or toolbox portable vault):",
+        )),
+        Line::from(Span::styled(&app_state.import_path, field_style(0))),
+    ];
+    if app_state.import_password_needed {
+        text.push(Line::from(Span::raw("Export password:")));
+        text.push(Line::from(Span::styled(
+            "*".repeat(app_state.import_password.chars().count()),
+            field_style(1),
+        )));
+    }
+    text.push(Line::from(Span::raw("")));
+    text.push(Line::from(Span::raw(
+        "Press 'Enter' to preview, 'Tab' to switch fields, 'Esc' to cancel",
+    )));
+
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title("Import Passwords")
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White));
+    f.render_widget(paragraph, chunks[0]);
+
+    if let Some(ref msg) = app_state.error_message {
+        let status_block = Paragraph::new(msg.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status_block, chunks[1]);
+    }
+}
+
+fn draw_share_entry(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(f.area());
+
+    let field_style = |index: usize| {
+        if app_state.input_field == index {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    };
+    let service = app_state
+        .password_entries
+        .get(app_state.selected_password_index)
+        .map(|entry| entry.service.as_str())
+        .unwrap_or_default();
+
+    let text = vec![
+        Line::from(Span::raw("Recipient's public key (age1..., from 'r' in their Password Manager):")),
+        Line::from(Span::styled(&app_state.share_recipient, field_style(0))),
+        Line::from(Span::raw("File to write (armored age format, safe to send over chat):")),
+        Line::from(Span::styled(&app_state.share_path, field_style(1))),
+        Line::from(Span::raw("")),
+        Line::from(Span::raw(
+            "Only the recipient can open the file. Password history is not included.",
+        )),
+        Line::from(Span::raw(
+            "Press 'Enter' to share, 'Tab' to switch fields, 'Esc' to cancel",
+        )),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title(format!("Share Entry: {}", service))
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White));
+    f.render_widget(paragraph, chunks[0]);
+
+    if let Some(ref msg) = app_state.error_message {
+        let status_block = Paragraph::new(msg.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status_block, chunks[1]);
+    }
+}
+
+fn draw_receive_share(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(9), Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(f.area());
+
+    let field_style = |index: usize| {
+        if app_state.input_field == index {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    };
+
+    let text = vec![
+        Line::from(Span::raw("Path of the shared entry (.age file) to add to this vault:")),
+        Line::from(Span::styled(&app_state.share_path, field_style(0))),
+        Line::from(Span::raw("")),
+        Line::from(Span::raw(
+            "Your public key; teammates share entries to it ('Enter' copies it):",
+        )),
+        Line::from(Span::styled(
+            app_state.sharing_public_key.as_deref().unwrap_or("-"),
+            field_style(1),
+        )),
+        Line::from(Span::raw("")),
+        Line::from(Span::raw(
+            "Press 'Enter' to add the entry, 'Tab' to switch fields, 'Esc' to cancel",
+        )),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title("Receive Shared Entry")
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White));
+    f.render_widget(paragraph, chunks[0]);
+
+    if let Some(ref msg) = app_state.error_message {
+        let status_block = Paragraph::new(msg.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status_block, chunks[1]);
+    }
+}
+
+/// Outcome of checking one vault file, for display
+fn file_check_summary(check: &FileCheck) -> (String, Color) {
+    match check.result {
+        Ok(entries) => (format!("OK, {} entries", entries), Color::Green),
+        Err(VaultError::WrongPassword) if check.snapshot.is_some() => (
+            "Encrypted with an earlier master password".to_string(),
+            Color::Yellow,
+        ),
+        Err(ref e) => (e.to_string(), Color::Red),
+    }
+}
+
+fn draw_vault_check(f: &mut Frame, app_state: &AppState) {
+    let Some(ref check) = app_state.vault_check else {
+        return;
+    };
+    let text_color = get_text_color();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(4), // Vault result
+            Constraint::Min(5),    // Snapshots
+            Constraint::Length(3), // Status
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    let (summary, color) = file_check_summary(&check.vault);
+    let vault_lines = vec![
+        Line::from(vec![
+            Span::styled("Vault file: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(check.vault.path.clone()),
+        ]),
+        Line::from(vec![
+            Span::styled("Structure, MAC and entries: ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(summary, Style::default().fg(color)),
+        ]),
+    ];
+    let title = match app_state.active_vault {
+        Some(ref name) => format!("Vault Check - {}", name),
+        None => "Vault Check".to_string(),
+    };
+    let border_color = if check.is_healthy() {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    f.render_widget(
+        Paragraph::new(vault_lines)
+            .style(Style::default().fg(text_color))
+            .block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(border_color)),
+            ),
+        chunks[0],
+    );
+
+    if check.snapshots.is_empty() {
+        let paragraph = Paragraph::new(
+            "No snapshots yet. One is taken before every change to the vault (security.vault_snapshots).",
+        )
+        .style(Style::default().fg(text_color))
+        .block(Block::default().title("Snapshots").borders(Borders::ALL));
+        f.render_widget(paragraph, chunks[1]);
+    } else {
+        let header = Row::new(vec![
+            Cell::from("Taken").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Result").style(Style::default().fg(Color::Yellow)),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .height(1);
+        let rows = check.snapshots.iter().map(|snapshot| {
+            let (summary, color) = file_check_summary(snapshot);
+            Row::new(vec![
+                Cell::from(snapshot_taken_at(snapshot)),
+                Cell::from(summary).style(Style::default().fg(color)),
+            ])
+            .style(Style::default().fg(text_color))
+        });
+
+        let mut state = ratatui::widgets::TableState::default();
+        state.select(Some(app_state.selected_snapshot_index));
+        let table = Table::new(rows, [Constraint::Length(18), Constraint::Min(20)])
+            .header(header)
+            .block(
+                Block::default()
+                    .title("Snapshots (newest first)")
+                    .borders(Borders::ALL),
+            )
+            .column_spacing(1)
+            .row_highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        f.render_stateful_widget(table, chunks[1], &mut state);
+    }
+
+    if let Some(ref msg) = app_state.error_message {
+        let status_block = Paragraph::new(msg.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status_block, chunks[2]);
+    } else if let Some(ref status) = app_state.status_message {
+        f.render_widget(
+            Paragraph::new(status.message.as_str()).style(Style::default().fg(Color::Yellow)),
+            chunks[2],
+        );
+    }
+
+    let controls = Paragraph::new(
+        "[↑↓]Select snapshot [r]Restore it (the current file is kept) [Esc]Back",
+    )
+    .block(Block::default().borders(Borders::TOP));
+    f.render_widget(controls, chunks[3]);
+
+    if app_state.confirmation_dialogue != ConfirmationDialogue::None {
+        draw_confirmation_dialogue(f, app_state);
+    }
+}
+
+fn draw_security_audit(f: &mut Frame, app_state: &AppState) {
+    let text_color = get_text_color();
+    let Some(ref report) = app_state.audit_report else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Title and summary
+            Constraint::Min(5),    // Findings
+            Constraint::Length(3), // Details of the selected entry
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    let breaches = match (&report.hibp_error, report.entries.first()) {
+        (Some(_), _) => "breach check failed".to_string(),
+        (None, Some(first)) if first.breach_count.is_none() => {
+            "breach check off (set security.hibp_range_path)".to_string()
+        }
+        _ => format!("{} breached", report.breached()),
+    };
+    let title = Paragraph::new(vec![
+        Line::from(vec![Span::styled(
+            "SECURITY AUDIT",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(Span::raw(format!(
+            "{} entries: {} weak, {} reused, {} older than rotation period, {}",
+            report.entries.len(),
+            report.weak(),
+            report.reused(),
+            report.stale(),
+            breaches
+        ))),
+    ])
+    .block(Block::default().borders(Borders::BOTTOM));
+    f.render_widget(title, chunks[0]);
+
+    let header = Row::new(vec![
+        Cell::from("Service").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Username").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Strength").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Reused").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Age").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Breached").style(Style::default().fg(Color::Yellow)),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .height(1);
+
+    let rows = report.entries.iter().map(|finding| {
+        let strength_color = match finding.strength.score {
+            0 | 1 => Color::Red,
+            2 => Color::Yellow,
+            _ => Color::Green,
+        };
+        let age = finding
+            .age_days
+            .map(|days| format!("{}d", days))
+            .unwrap_or_else(|| "unknown".to_string());
+        let breached = match finding.breach_count {
+            Some(0) => "no".to_string(),
+            Some(count) => format!("{} times", count),
+            None => "-".to_string(),
+        };
+
+        Row::new(vec![
+            Cell::from(finding.service.clone()),
+            Cell::from(finding.username.clone()),
+            Cell::from(finding.strength.label()).style(Style::default().fg(strength_color)),
+            Cell::from(if finding.reused_with.is_empty() {
+                "no".to_string()
+            } else {
+                format!("{} others", finding.reused_with.len())
+            }),
+            Cell::from(age).style(Style::default().fg(if finding.stale {
+                Color::Red
+            } else {
+                text_color
+            })),
+            Cell::from(breached).style(Style::default().fg(
+                if finding.breach_count.unwrap_or(0) > 0 {
+                    Color::Red
+                } else {
+                    text_color
+                },
+            )),
+        ])
+        .style(Style::default().fg(if finding.has_issues() {
+            text_color
+        } else {
+            Color::DarkGray
+        }))
+    });
+
+    let mut state = ratatui::widgets::TableState::default();
+    state.select(Some(app_state.selected_audit_index));
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(25),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(14),
+        ],
+    )
+    .header(header)
+    .block(Block::default().title("Findings").borders(Borders::ALL))
+    .column_spacing(1)
+    .row_highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(table, chunks[1], &mut state);
+
+    if let Some(finding) = report.entries.get(app_state.selected_audit_index) {
+        let mut notes: Vec<&str> = finding
+            .strength
+            .weaknesses
+            .iter()
+            .map(|weakness| weakness.description())
+            .collect();
+        if notes.is_empty() {
+            notes.push("no weak patterns found");
+        }
+        let details = Paragraph::new(Line::from(vec![
+            Span::styled("Password ", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(notes.join(", ")),
+            Span::raw(format!(
+                " (about 10^{:.0} guesses)",
+                finding.strength.guesses_log10
+            )),
+        ]))
+        .block(Block::default().title("Details").borders(Borders::ALL));
+        f.render_widget(details, chunks[2]);
+    }
+
+    let controls = Paragraph::new(vec![Line::from(vec![Span::raw(
+        "Actions: [e]Edit entry [r]Re-run audit [Esc]Back | [↑↓]Navigate",
+    )])])
+    .block(Block::default().borders(Borders::TOP));
+    f.render_widget(controls, chunks[3]);
+}
+
+fn draw_export(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(11), Constraint::Length(3), Constraint::Min(0)].as_ref())
+        .split(f.area());
+
+    let field_style = |index: usize| {
+        if app_state.input_field == index {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        }
+    };
+
+    let mut text = vec![
+        Line::from(vec![
+            Span::raw("Format (←/→ to change): "),
+            Span::styled(
+                app_state.export_format.name(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(Span::raw("Export to file:")),
+        Line::from(Span::styled(&app_state.export_path, field_style(0))),
+    ];
+
+    match app_state.export_format {
+        ExportFormat::Csv => {
+            text.push(Line::from(Span::raw("")));
+            text.push(Line::from(Span::styled(
+                "WARNING: the CSV file is NOT encrypted. Anyone who can read it sees every password.",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )));
+        }
+        ExportFormat::PortableVault => {
+            text.push(Line::from(Span::raw("Export password:")));
+            text.push(Line::from(Span::styled(
+                "*".repeat(app_state.export_password.chars().count()),
+                field_style(1),
+            )));
+            text.push(Line::from(Span::raw("Confirm export password:")));
+            text.push(Line::from(Span::styled(
+                "*".repeat(app_state.export_password_confirm.chars().count()),
+                field_style(2),
+            )));
+        }
+    }
+    text.push(Line::from(Span::raw("")));
+    text.push(Line::from(Span::raw(
+        "Press 'Enter' to export, 'Tab' to switch fields, 'Esc' to cancel",
+    )));
+
+    let paragraph = Paragraph::new(text)
+        .block(
+            Block::default()
+                .title("Export Passwords")
+                .borders(Borders::ALL),
+        )
+        .style(Style::default().fg(Color::White));
+    f.render_widget(paragraph, chunks[0]);
+
+    if let Some(ref msg) = app_state.error_message {
+        let status_block = Paragraph::new(msg.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status_block, chunks[1]);
+    }
+
+    // Draw the confirmation dialog if active
+    if app_state.confirmation_dialogue != ConfirmationDialogue::None {
+        draw_confirmation_dialogue(f, app_state);
+    }
+}
+
+fn draw_import_preview(f: &mut Frame, app_state: &AppState) {
+    let text_color = get_text_color();
+    let Some(ref session) = app_state.import_session else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Length(5), // CSV mapping / summary
+            Constraint::Min(5),    // Candidates
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
+
+    let selected = session.candidates.iter().filter(|c| c.include).count();
+    let duplicates = session.candidates.iter().filter(|c| c.duplicate).count();
+    let title = Paragraph::new(vec![
+        Line::from(vec![Span::styled(
+            format!("IMPORT PREVIEW - {}", session.format.name()),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(vec![
+            Span::raw(format!(
+                "{} of {} entries selected, {} duplicates, {} records skipped",
+                selected,
+                session.candidates.len(),
+                duplicates,
+                session.skipped
+            )),
+            Span::styled(
+                if session.unreadable_totp > 0 {
+                    format!(
+                        "  {} unreadable TOTP seed(s) kept in notes",
+                        session.unreadable_totp
+                    )
+                } else {
+                    String::new()
+                },
+                Style::default().fg(Color::Yellow),
+            ),
+        ]),
+    ])
+    .block(Block::default().borders(Borders::BOTTOM));
+    f.render_widget(title, chunks[0]);
+
+    let column = |index: Option<usize>| {
+        index
+            .and_then(|index| session.headers.get(index))
+            .map(|header| format!("'{}'", header))
+            .unwrap_or_else(|| "-".to_string())
+    };
+    let mapping = if session.format == ImportFormat::Csv {
+        vec![
+            Line::from(Span::raw(format!(
+                "Columns: [s]ervice {}  [u]sername {}  [p]assword {}  [t]otp {}",
+                column(session.mapping.service),
+                column(session.mapping.username),
+                column(session.mapping.password),
+                column(session.mapping.totp)
+            ))),
+            Line::from(Span::raw(format!(
+                "         u[r]l {}  [n]otes {}  ta[g]s {}  custom [f]ields {}",
+                column(session.mapping.url),
+                column(session.mapping.notes),
+                column(session.mapping.tags),
+                column(session.mapping.custom_fields)
+            ))),
+            Line::from(Span::raw(format!(
+                "         [k]ind {}  pri[v]ate key {}  pu[b]lic key {}",
+                column(session.mapping.kind),
+                column(session.mapping.private_key),
+                column(session.mapping.public_key)
+            ))),
+        ]
+    } else {
+        vec![Line::from(Span::raw(format!("From {}", session.path)))]
+    };
+    f.render_widget(
+        Paragraph::new(mapping).block(Block::default().borders(Borders::ALL)),
+        chunks[1],
+    );
+
+    if let Some(ref error) = session.mapping_error {
+        let paragraph = Paragraph::new(error.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().title("Entries").borders(Borders::ALL));
+        f.render_widget(paragraph, chunks[2]);
+    } else {
+        let header = Row::new(vec![
+            Cell::from("Import").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Service").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Username").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Status").style(Style::default().fg(Color::Yellow)),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .height(1);
+
+        let rows = session.candidates.iter().map(|candidate| {
+            let (status, color) = if candidate.duplicate {
+                ("duplicate", Color::Yellow)
+            } else {
+                ("new", Color::Green)
+            };
+            Row::new(vec![
+                Cell::from(if candidate.include { "[x]" } else { "[ ]" }),
+                Cell::from(candidate.entry.service.clone()),
+                Cell::from(candidate.entry.username.clone()),
+                Cell::from(status).style(Style::default().fg(color)),
+            ])
+            .style(Style::default().fg(text_color))
+        });
+
+        let mut state = ratatui::widgets::TableState::default();
+        state.select(Some(app_state.selected_import_index));
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(6),
+                Constraint::Percentage(45),
+                Constraint::Percentage(35),
+                Constraint::Length(10),
+            ],
+        )
+        .header(header)
+        .block(Block::default().title("Entries").borders(Borders::ALL))
+        .column_spacing(1)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(table, chunks[2], &mut state);
+    }
+
+    let controls = Paragraph::new(vec![Line::from(vec![Span::raw(
+        "Actions: [Space]Toggle [Enter]Import selected [Esc]Cancel | [↑↓]Navigate",
+    )])])
+    .block(Block::default().borders(Borders::TOP));
+    f.render_widget(controls, chunks[3]);
+}
+
+fn draw_vault_picker(f: &mut Frame, app_state: &AppState) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(3), Constraint::Length(4)].as_ref())
+        .split(f.area());
+
+    let block = Block::default()
+        .title(format!("Vaults in {}", security_config().vault_dir))
+        .borders(Borders::ALL);
+    if app_state.vault_names.is_empty() {
+        let paragraph = Paragraph::new("No vaults yet. Press 'n' to create one.")
+            .block(block)
+            .style(Style::default().fg(get_text_color()));
+        f.render_widget(paragraph, layout[0]);
+    } else {
+        let rows = app_state.vault_names.iter().map(|name| {
+            let marker = if app_state.active_vault.as_ref() == Some(name) {
+                "last used"
+            } else {
+                ""
+            };
+            Row::new(vec![Cell::from(name.as_str()), Cell::from(marker)])
+        });
+
+        let mut state = ratatui::widgets::TableState::default();
+        state.select(Some(app_state.selected_vault_index));
+
+        let table = Table::new(rows, [Constraint::Percentage(70), Constraint::Percentage(30)])
+            .block(block)
+            .row_highlight_style(
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD),
+            );
+        f.render_stateful_widget(table, layout[0], &mut state);
+    }
+
+    let name_line = match app_state.new_vault_name {
+        Some(ref name) => Line::from(vec![
+            Span::raw("New vault name: "),
+            Span::styled(name.as_str(), Style::default().fg(Color::Yellow).bg(Color::Blue)),
+        ]),
+        None => Line::from(Span::raw(
+            "Each vault has its own master password and entries.",
+        )),
+    };
+    let name_block = Paragraph::new(name_line).block(Block::default().borders(Borders::ALL));
+    f.render_widget(name_block, layout[1]);
+
+    let instructions = if app_state.new_vault_name.is_some() {
+        "Press 'Enter' to create, 'Esc' to cancel"
+    } else {
+        "[Enter]Open [n]New vault [↑↓]Navigate [Esc]Back"
+    };
+    let mut status_lines = vec![Line::from(Span::raw(instructions))];
+    if let Some(ref msg) = app_state.error_message {
+        status_lines.push(Line::from(Span::styled(
+            msg.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let status = Paragraph::new(status_lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(status, layout[2]);
+}
+
+fn draw_change_master_password(f: &mut Frame, app_state: &AppState) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(4),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+
+    let key_age = app_state
+        .vault
+        .as_ref()
+        .and_then(|vault| vault.key_age_days(Utc::now().timestamp()))
+        .map(|days| format!(" The current key is {} days old.", days))
+        .unwrap_or_default();
+    let prompt_block = Paragraph::new(format!(
+        "The vault is re-encrypted under a key derived from the new password.{}",
+        key_age
+    ))
+    .block(
+        Block::default()
+            .title("Change Master Password")
+            .borders(Borders::ALL),
+    )
+    .style(Style::default().fg(get_text_color()));
+    f.render_widget(prompt_block, layout[0]);
+
+    let highlight_style = Style::default().fg(Color::Yellow).bg(Color::Blue);
+    let normal_style = Style::default().fg(Color::White);
+
+    let fields = [
+        ("Current password: ", &app_state.master_password),
+        ("New password: ", &app_state.new_master_password),
+        ("Confirm new: ", &app_state.master_password_confirm),
+    ];
+    for (index, (label, value)) in fields.into_iter().enumerate() {
+        let style = if app_state.input_field == index {
+            highlight_style
+        } else {
+            normal_style
+        };
+        let text = Line::from(vec![
+            Span::raw(label),
+            Span::styled("*".repeat(value.chars().count()), style),
+        ]);
+        let paragraph = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(paragraph, layout[1 + index]);
+    }
+
+    let mut status_lines = vec![Line::from(Span::raw(
+        "Press 'Enter' to re-encrypt, 'Tab' to switch fields, 'Esc' to cancel",
+    ))];
+    if let Some(ref msg) = app_state.error_message {
+        status_lines.push(Line::from(Span::styled(
+            msg.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let status = Paragraph::new(status_lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(status, layout[4]);
 }
 
-// === Drawing Functions ===
+fn draw_ssh_command(f: &mut Frame, app_state: &AppState) {
+    let area = f.area();
+    let popup_width = 80.min(area.width.saturating_sub(4));
+    let popup_height = 20.min(area.height.saturating_sub(4));
+    let popup_area = Rect::new(
+        (area.width - popup_width) / 2,
+        (area.height - popup_height) / 2,
+        popup_width,
+        popup_height,
+    );
+    f.render_widget(Clear, popup_area);
+
+    let service = app_state
+        .password_entries
+        .get(app_state.selected_password_index)
+        .map(|entry| entry.service.as_str())
+        .unwrap_or_default();
+    let block = Block::default()
+        .title(format!("Run Command with SSH Key: {}", service))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
 
-static DARK_MODE: Lazy<bool> = Lazy::new(|| {
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg(
-            "tell application \"System Events\" to tell appearance preferences to return dark mode",
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+    let help = Paragraph::new(format!(
+        "{} is replaced by a temporary 0600 key file, removed when the command exits. \
+         The command gets no input, so keys with a passphrase need ssh-agent.",
+        KEY_PLACEHOLDER
+    ))
+    .wrap(Wrap { trim: true })
+    .style(Style::default().fg(get_text_color()));
+    f.render_widget(help, layout[0]);
+
+    let command = Paragraph::new(app_state.ssh_command_input.as_str())
+        .style(Style::default().fg(Color::Yellow))
+        .block(Block::default().title("Command").borders(Borders::ALL));
+    f.render_widget(command, layout[1]);
+
+    if let Some(ref output) = app_state.ssh_command_output {
+        let output = Paragraph::new(output.as_str())
+            .wrap(Wrap { trim: false })
+            .block(Block::default().title("Output").borders(Borders::ALL));
+        f.render_widget(output, layout[2]);
+    }
+
+    let controls = if app_state.ssh_command.is_some() {
+        "Running... 'Esc' to stop it and close"
+    } else {
+        "Press 'Enter' to run, 'Esc' to close"
+    };
+    f.render_widget(Paragraph::new(controls), layout[3]);
+}
+
+fn draw_unlock_screen(f: &mut Frame, app_state: &AppState) {
+    let (title, prompt) = match app_state.vault_status {
+        VaultStatus::Missing => (
+            "Create Vault",
+            "No vault found. Choose a master password to create one.",
+        ),
+        VaultStatus::Legacy => (
+            "Migrate Vault",
+            "This vault uses the old ENCRYPTION_KEY. Choose a master password to re-encrypt it.",
+        ),
+        VaultStatus::Protected => ("Unlock Vault", "Enter the master password."),
+    };
+    let title = match app_state.active_vault {
+        Some(ref name) => format!("{} '{}'", title, name),
+        None => title.to_string(),
+    };
+    let needs_confirmation = app_state.vault_status != VaultStatus::Protected;
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ]
+            .as_ref(),
         )
-        .output()
-        .unwrap_or_else(|_| {
-            eprintln!("Failed to execute osascript");
-            std::process::Output {
-                status: std::process::ExitStatus::from_raw(0),
-                stdout: b"false".to_vec(),
-                stderr: Vec::new(),
-            }
-        });
-    String::from_utf8_lossy(&output.stdout).trim() == "true"
-});
+        .split(f.area());
 
-fn get_text_color() -> ratatui::style::Color {
-    if *DARK_MODE {
-        ratatui::style::Color::White
+    let prompt_block = Paragraph::new(prompt)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .style(Style::default().fg(get_text_color()));
+    f.render_widget(prompt_block, layout[0]);
+
+    let highlight_style = Style::default().fg(Color::Yellow).bg(Color::Blue);
+    let normal_style = Style::default().fg(Color::White);
+
+    let mut fields = vec![("Master password: ", &app_state.master_password, 0)];
+    if needs_confirmation {
+        fields.push(("Confirm: ", &app_state.master_password_confirm, 1));
+    }
+
+    for (label, value, index) in fields {
+        let style = if app_state.input_field == index {
+            highlight_style
+        } else {
+            normal_style
+        };
+        let text = Line::from(vec![
+            Span::raw(label),
+            Span::styled("*".repeat(value.chars().count()), style),
+        ]);
+        let paragraph = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
+        f.render_widget(paragraph, layout[1 + index]);
+    }
+
+    let instructions = if needs_confirmation {
+        "Press 'Enter' to continue, 'Tab' to switch fields, 'Esc' to go back"
     } else {
-        ratatui::style::Color::Black
+        "Press 'Enter' to unlock, 'Esc' to go back"
+    };
+    let mut status_lines = vec![Line::from(Span::raw(instructions))];
+    if let Some(ref msg) = app_state.error_message {
+        status_lines.push(Line::from(Span::styled(
+            msg.clone(),
+            Style::default().fg(Color::Red),
+        )));
     }
+    let status = Paragraph::new(status_lines).block(Block::default().borders(Borders::ALL));
+    f.render_widget(status, layout[3]);
 }
 
-fn is_dark_mode() -> bool {
-    let Ok(output) = Command::new("osascript")
-        .arg("-e")
-        .arg(
-            "tell application \"System Events\" to tell appearance preferences to return dark mode",
+fn draw_input_modal(f: &mut Frame, app_state: &AppState) {
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Length(1),
+                Constraint::Length(3), // Service, username
+                Constraint::Length(3), // Password, TOTP
+                Constraint::Length(3), // URL, tags
+                Constraint::Length(5), // Notes, custom fields
+                Constraint::Length(6), // Generator
+                Constraint::Length(3), // Instructions
+                Constraint::Length(3), // Status
+                Constraint::Min(0),
+            ]
+            .as_ref(),
         )
-        .output()
-    else {
-        return false; // fallback if osascript unavailable
+        .split(f.area());
+
+    let highlight_style = Style::default().fg(Color::Yellow).bg(Color::Blue);
+    let normal_style = Style::default().fg(Color::White);
+
+    // The private key is never echoed once it has been pasted
+    let private_key_summary = if app_state.private_key_input.contains("PRIVATE KEY") {
+        format!(
+            "<private key, {} lines>",
+            app_state.private_key_input.trim_end().lines().count()
+        )
+    } else {
+        app_state.private_key_input.to_string()
+    };
+    let (service_label, password_label) = match app_state.entry_kind {
+        EntryKind::Login => ("Service", "Password"),
+        EntryKind::SecureNote => ("Title", "Password (optional)"),
+        EntryKind::SshKey => ("Name", "Key passphrase (optional)"),
+    };
+    let (field_3, field_6) = match app_state.entry_kind {
+        EntryKind::SshKey => (
+            ("Public key (optional, read from <file>.pub)", app_state.public_key_input.as_str()),
+            ("Private key: paste it, or the path of a key file", private_key_summary.as_str()),
+        ),
+        EntryKind::SecureNote => (
+            ("TOTP secret or otpauth:// URI (optional)", app_state.totp_input.as_str()),
+            ("Note", app_state.notes_input.as_str()),
+        ),
+        EntryKind::Login => (
+            ("TOTP secret or otpauth:// URI (optional)", app_state.totp_input.as_str()),
+            ("Notes", app_state.notes_input.as_str()),
+        ),
     };
+    let fields = [
+        (service_label, app_state.service.as_str()),
+        ("Username", app_state.username.as_str()),
+        (password_label, app_state.password.as_str()),
+        field_3,
+        ("URL (optional)", app_state.url_input.as_str()),
+        ("Tags, comma-separated (work/email nests)", app_state.tags_input.as_str()),
+        field_6,
+        (
+            "Custom fields: name=value per line, !name for secrets",
+            app_state.custom_fields_input.as_str(),
+        ),
+    ];
 
-    let result = String::from_utf8_lossy(&output.stdout);
-    result.trim() == "true"
+    // Two fields per row
+    for (i, (label, value)) in fields.iter().enumerate() {
+        let row = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(layout[i / 2 + 1]);
+
+        let style = if app_state.input_field == i {
+            highlight_style
+        } else {
+            normal_style
+        };
+        let lines: Vec<Line> = value
+            .split('\n')
+            .map(|line| Line::from(Span::styled(line.to_string(), style)))
+            .collect();
+        let paragraph =
+            Paragraph::new(lines).block(Block::default().title(*label).borders(Borders::ALL));
+        f.render_widget(paragraph, row[i % 2]);
+    }
+
+    draw_generator_panel(f, app_state, layout[5]);
+
+    // Display error or success message if present
+    if let Some(ref msg) = app_state.error_message {
+        let status_block = Paragraph::new(msg.clone())
+            .style(Style::default().fg(Color::Red))
+            .block(Block::default().borders(Borders::ALL).title("Status"));
+        f.render_widget(status_block, layout[7]);
+    }
+
+    let instructions = Paragraph::new(
+        "Press 'Tab' to switch fields, 'Enter' to Save (adds a line in Notes and Custom fields), 'Esc' to Cancel",
+    )
+    .block(Block::default().borders(Borders::ALL));
+    f.render_widget(instructions, layout[6]);
 }
 
-fn draw_main_menu(f: &mut Frame) {
-    let text_color = get_text_color();
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)].as_ref())
-        .split(f.size());
+/// `text` with the characters at `positions` (char indices) highlighted
+fn highlight_matches(text: &str, positions: &[usize]) -> Line<'static> {
+    let highlight = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+    Line::from(
+        text.chars()
+            .enumerate()
+            .map(|(i, c)| {
+                if positions.contains(&i) {
+                    Span::styled(c.to_string(), highlight)
+                } else {
+                    Span::raw(c.to_string())
+                }
+            })
+            .collect::<Vec<_>>(),
+    )
+}
 
-    let text = vec![
-        Line::from(Span::raw("1. Password Manager")),
-        Line::from(Span::raw("2. Network Tools")),
-        Line::from(Span::raw("3. System Utilities")),
-        Line::from(Span::raw("4. Task Manager")),
-        Line::from(Span::raw("Press 'q' to quit")),
+/// Local date and time of a stored Unix timestamp
+fn format_timestamp(timestamp: Option<i64>) -> String {
+    timestamp
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+        .map(|time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// URL, tags, timestamps, custom fields and notes of the selected entry
+///
+/// Secret custom fields stay masked until the entry's password is revealed.
+fn draw_password_details(
+    f: &mut Frame,
+    entry: &PasswordEntry,
+    revealed: bool,
+    url_matches: &[usize],
+    area: Rect,
+) {
+    let label = Style::default().add_modifier(Modifier::BOLD);
+    let url = entry.url.as_deref().unwrap_or("-");
+    let mut url_line = highlight_matches(url, url_matches);
+    url_line.spans.insert(0, Span::styled("URL: ", label));
+    let mut lines = vec![
+        url_line,
+        Line::from(vec![
+            Span::styled("Created: ", label),
+            Span::raw(format_timestamp(entry.created_at)),
+            Span::styled("  Modified: ", label),
+            Span::raw(format_timestamp(entry.modified_at)),
+            Span::styled("  History: ", label),
+            Span::raw(format!("{} previous passwords ('h')", entry.history.len())),
+        ]),
     ];
-    let paragraph = Paragraph::new(text)
-        .block(Block::default().title("Main Menu").borders(Borders::ALL))
-        .style(Style::default().fg(text_color)); // Use dynamic text color
-    f.render_widget(paragraph, chunks[0]);
+
+    for field in &entry.custom_fields {
+        let value = if field.secret && !revealed {
+            Span::raw(PASSWORD_MASK)
+        } else {
+            Span::raw(field.value.as_str())
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}: ", field.name), label),
+            value,
+        ]));
+    }
+    if entry.kind == EntryKind::SshKey {
+        lines.push(Line::from(vec![
+            Span::styled("Public key: ", label),
+            Span::raw(entry.public_key.clone().unwrap_or_else(|| "-".to_string())),
+        ]));
+        lines.push(Line::from(Span::raw(
+            "Private key stored ('x' runs a command with it)",
+        )));
+    }
+    // A secure note is the secret itself, so it stays hidden like a password
+    if entry.kind == EntryKind::SecureNote && !revealed {
+        lines.push(Line::from(Span::styled(
+            format!("<note, {} lines, 'r' to reveal>", entry.notes.lines().count()),
+            Style::default().fg(Color::Gray),
+        )));
+    } else {
+        for note in entry.notes.lines() {
+            lines.push(Line::from(Span::styled(
+                note.to_string(),
+                Style::default().fg(Color::Gray),
+            )));
+        }
+    }
+
+    let details = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().title("Details").borders(Borders::ALL));
+    f.render_widget(details, area);
+}
+
+/// Previous passwords of the selected entry, newest first
+fn draw_password_history(
+    f: &mut Frame,
+    entry: &PasswordEntry,
+    revealed: bool,
+    selected: usize,
+    area: Rect,
+) {
+    let block = Block::default()
+        .title("Password history - [Enter]Restore [r]Reveal [Esc]Back")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    if entry.history.is_empty() {
+        let paragraph =
+            Paragraph::new("No previous passwords. Changing the password keeps the old one here.")
+                .block(block);
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let header = Row::new(vec![
+        Cell::from("Password").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Set").style(Style::default().fg(Color::Yellow)),
+        Cell::from("Replaced").style(Style::default().fg(Color::Yellow)),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = entry.history.iter().map(|old| {
+        Row::new(vec![
+            Cell::from(if revealed {
+                old.password.as_str()
+            } else {
+                PASSWORD_MASK
+            }),
+            Cell::from(format_timestamp(old.set_at)),
+            Cell::from(format_timestamp(Some(old.replaced_at))),
+        ])
+    });
+
+    let mut state = ratatui::widgets::TableState::default();
+    state.select(Some(selected));
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(40),
+            Constraint::Percentage(30),
+            Constraint::Percentage(30),
+        ],
+    )
+    .header(header)
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(Color::DarkGray)
+            .add_modifier(Modifier::BOLD),
+    );
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+/// Current one-time code with a bar counting down to the next one
+fn draw_totp_code(f: &mut Frame, totp: &TotpConfig, area: Rect) {
+    let now = Utc::now().timestamp().max(0) as u64;
+    let remaining = totp.seconds_remaining(now);
+
+    let gauge = Gauge::default()
+        .block(Block::default().title("One-time code").borders(Borders::ALL))
+        .gauge_style(Style::default().fg(if remaining <= 5 {
+            Color::Red
+        } else {
+            Color::Green
+        }))
+        .ratio(remaining as f64 / totp.period.max(1) as f64)
+        .label(format!("{}  ({}s)", totp.code_at(now), remaining));
+
+    f.render_widget(gauge, area);
+}
+
+fn draw_generator_panel(f: &mut Frame, app_state: &AppState, area: Rect) {
+    let options = &app_state.generator_options;
+    let min_length = security_config().password_min_length;
+    let focused = app_state.input_field == 8;
+
+    let toggle = |key: &'static str, label: &'static str, enabled: bool| {
+        vec![
+            Span::styled(key, Style::default().add_modifier(Modifier::BOLD)),
+            Span::styled(
+                label,
+                Style::default().fg(if enabled { Color::Green } else { Color::DarkGray }),
+            ),
+            Span::raw(" "),
+        ]
+    };
+
+    let settings = match options.mode {
+        GeneratorMode::Characters => {
+            let mut spans = vec![Span::raw(format!(
+                "Characters, length {} (min {})  ",
+                options.length.max(min_length),
+                min_length
+            ))];
+            spans.extend(toggle("[l]", "a-z", options.lowercase));
+            spans.extend(toggle("[u]", "A-Z", options.uppercase));
+            spans.extend(toggle("[d]", "0-9", options.digits));
+            spans.extend(toggle("[s]", "!@#", options.symbols));
+            spans.extend(toggle("[a]", "no look-alikes", options.exclude_ambiguous));
+            Line::from(spans)
+        }
+        GeneratorMode::Passphrase => Line::from(vec![Span::raw(format!(
+            "Passphrase, {} words separated by '{}' (min {} characters)",
+            options.words, options.separator, min_length
+        ))]),
+    };
+
+    let strength = match app_state.generated_entropy {
+        Some(bits) => {
+            let (label, color) = if bits < 50.0 {
+                ("weak", Color::Red)
+            } else if bits < 80.0 {
+                ("fair", Color::Yellow)
+            } else {
+                ("strong", Color::Green)
+            };
+            Line::from(vec![
+                Span::raw("Estimated entropy: "),
+                Span::styled(
+                    format!("{:.0} bits ({})", bits, label),
+                    Style::default().fg(color),
+                ),
+            ])
+        }
+        None => Line::from(Span::raw("Estimated entropy: -")),
+    };
+
+    let controls = Line::from(Span::styled(
+        "[g]Generate [m]Mode [+/-]Length",
+        Style::default().fg(Color::DarkGray),
+    ));
+
+    let border_style = if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+
+    let panel = Paragraph::new(vec![settings, strength, controls]).block(
+        Block::default()
+            .title("Generator (Tab here)")
+            .borders(Borders::ALL)
+            .border_style(border_style),
+    );
+    f.render_widget(panel, area);
 }
 
-fn draw_password_manager_menu(f: &mut Frame) {
+fn draw_password_list(f: &mut Frame, app_state: &AppState) {
     let text_color = get_text_color();
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(100)].as_ref())
-        .split(f.size());
+        .margin(1)
+        .constraints([
+            Constraint::Length(3), // Title
+            Constraint::Min(5),    // Password table
+            Constraint::Length(7), // Details of the selected entry
+            Constraint::Length(3), // One-time code of the selected entry
+            Constraint::Length(1), // Status message
+            Constraint::Length(3), // Controls
+        ])
+        .split(f.area());
 
-    let text = vec![
-        Line::from(Span::raw("a. Add Password")),
-        Line::from(Span::raw("v. View Passwords")),
-        Line::from(Span::raw("Esc. Back to Main Menu")),
-        Line::from(Span::raw("Press 'q' to quit")),
-    ];
-    let paragraph = Paragraph::new(text)
-        .block(
-            Block::default()
-                .title("Password Manager")
-                .borders(Borders::ALL),
-        )
-        .style(Style::default().fg(text_color)); // Set text color to black
-    f.render_widget(paragraph, chunks[0]);
-}
+    let title = Paragraph::new(vec![
+        Line::from(vec![Span::styled(
+            "STORED PASSWORDS",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        if app_state.input_mode == InputMode::PasswordSearch {
+            Line::from(vec![
+                Span::styled("Search: ", Style::default().add_modifier(Modifier::BOLD)),
+                Span::styled(
+                    format!("{}_", app_state.search_query),
+                    Style::default().fg(Color::Yellow),
+                ),
+                Span::raw("  ('Enter' to select, 'Esc' to cancel)"),
+            ])
+        } else {
+            Line::from(vec![Span::raw(
+                "Press ↑↓ to navigate, 'r' to reveal, 'e' to edit, 'd' to delete, '/' to search, 't' to filter by tag, 'Esc' to go back",
+            )])
+        },
+    ])
+    .block(Block::default().borders(Borders::BOTTOM));
 
-fn draw_input_modal(f: &mut Frame, app_state: &AppState) {
-    let layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
+    f.render_widget(title, chunks[0]);
+
+    let search_hits = password_search_hits(app_state);
+    let matches_in = |index: usize, field: SearchField| -> &[usize] {
+        search_hits
+            .iter()
+            .find(|hit| hit.index == index && hit.field == field)
+            .map_or(&[], |hit| hit.positions.as_slice())
+    };
+
+    let list_title = match app_state.tag_filter {
+        Some(ref tag) => format!("Stored Passwords - tag: {}", tag),
+        None => "Stored Passwords".to_string(),
+    };
+
+    if app_state.password_entries.is_empty() {
+        let paragraph = Paragraph::new("No passwords found. Press 'a' from the menu to add one.")
+            .style(Style::default().fg(text_color))
+            .block(
+                Block::default()
+                    .title("Stored Passwords")
+                    .borders(Borders::ALL),
+            );
+        f.render_widget(paragraph, chunks[1]);
+    } else {
+        let header = Row::new(vec![
+            Cell::from("Type").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Service").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Username").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Password").style(Style::default().fg(Color::Yellow)),
+            Cell::from("2FA").style(Style::default().fg(Color::Yellow)),
+            Cell::from("Tags").style(Style::default().fg(Color::Yellow)),
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .height(1);
+
+        let visible = visible_password_indices(app_state);
+        let revealed_index = app_state.revealed_password.map(|(index, _)| index);
+        let rows = visible.iter().map(|&index| {
+            let entry = &app_state.password_entries[index];
+            // Fixed-width mask so the list does not give away password lengths
+            let password = if revealed_index == Some(index) {
+                Cell::from(entry.password.as_str()).style(Style::default().fg(Color::Yellow))
+            } else {
+                Cell::from(PASSWORD_MASK)
+            };
+
+            let kind = match entry.kind {
+                EntryKind::Login => "Login",
+                EntryKind::SecureNote => "Note",
+                EntryKind::SshKey => "SSH",
+            };
+
+            Row::new(vec![
+                Cell::from(kind),
+                Cell::from(highlight_matches(
+                    &entry.service,
+                    matches_in(index, SearchField::Service),
+                )),
+                Cell::from(highlight_matches(
+                    &entry.username,
+                    matches_in(index, SearchField::Username),
+                )),
+                password,
+                Cell::from(if entry.totp.is_some() { "TOTP" } else { "" }),
+                Cell::from(highlight_matches(
+                    &entry.tags.join(", "),
+                    matches_in(index, SearchField::Tags),
+                )),
+            ])
+            .style(Style::default().fg(text_color))
+        });
+
+        let mut state = ratatui::widgets::TableState::default();
+        state.select(
+            visible
+                .iter()
+                .position(|&index| index == app_state.selected_password_index),
+        );
+
+        let table = Table::new(
+            rows,
             [
-                Constraint::Percentage(10),
-                Constraint::Percentage(20),
-                Constraint::Percentage(20),
+                Constraint::Length(5),
+                Constraint::Percentage(23),
+                Constraint::Percentage(23),
                 Constraint::Percentage(20),
+                Constraint::Length(5),
                 Constraint::Percentage(20),
-                Constraint::Percentage(10),
-            ]
-            .as_ref(),
+            ],
         )
-        .split(f.size());
+        .header(header)
+        .block(Block::default().title(list_title).borders(Borders::ALL))
+        .column_spacing(1)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
 
-    let highlight_style = Style::default().fg(Color::Yellow).bg(Color::Blue);
-    let normal_style = Style::default().fg(Color::White);
+        f.render_stateful_widget(table, chunks[1], &mut state);
 
-    let fields = [
-        ("Service: ", &app_state.service, app_state.input_field == 0),
-        (
-            "Username: ",
-            &app_state.username,
-            app_state.input_field == 1,
-        ),
-        (
-            "Password: ",
-            &app_state.password,
-            app_state.input_field == 2,
-        ),
-    ];
+        if let Some(entry) = app_state
+            .password_entries
+            .get(app_state.selected_password_index)
+        {
+            let revealed = revealed_index == Some(app_state.selected_password_index);
+            if app_state.input_mode == InputMode::PasswordHistory {
+                let selected = app_state.selected_history_index;
+                draw_password_history(f, entry, revealed, selected, chunks[2]);
+            } else {
+                let url_matches = matches_in(app_state.selected_password_index, SearchField::Url);
+                draw_password_details(f, entry, revealed, url_matches, chunks[2]);
+            }
+            if let Some(ref totp) = entry.totp {
+                draw_totp_code(f, totp, chunks[3]);
+            }
+        }
+    }
 
-    for (i, (label, value, is_selected)) in fields.iter().enumerate() {
-        let text = Line::from(vec![
-            Span::raw(*label),
-            Span::styled(
-                (*value).clone(),
-                if *is_selected {
-                    highlight_style
-                } else {
-                    normal_style
-                },
-            ),
+    // Status message
+    if let Some(ref status) = app_state.status_message {
+        let message_color = match status.message_type {
+            StatusMessageType::Info => Color::Blue,
+            StatusMessageType::Success => Color::Green,
+            StatusMessageType::Warning => Color::Yellow,
+            StatusMessageType::Error => Color::Red,
+        };
+
+        let status_text = Line::from(vec![
+            Span::styled("◆ ", Style::default().fg(message_color)),
+            Span::styled(&status.message, Style::default().fg(message_color)),
         ]);
-        let paragraph = Paragraph::new(text).block(Block::default().borders(Borders::ALL));
-        f.render_widget(paragraph, layout[i + 1]);
-    }
 
-    // Display error or success message if present
-    if let Some(ref msg) = app_state.error_message {
-        let status_block = Paragraph::new(msg.clone())
-            .style(Style::default().fg(Color::Red))
-            .block(Block::default().borders(Borders::ALL).title("Status"));
-        f.render_widget(status_block, layout[5]); // Adjust index based on your layout
+        f.render_widget(Paragraph::new(status_text), chunks[4]);
     }
 
-    let instructions = Paragraph::new("Press 'Enter' to Save, 'Esc' to Cancel")
-        .block(Block::default().borders(Borders::ALL));
-    f.render_widget(instructions, layout[4]);
-}
-
-fn draw_password_list(f: &mut Frame) {
-    let text_color = get_text_color();
+    let controls = Paragraph::new(vec![Line::from(vec![Span::raw(
+        "Actions: [r]Reveal [c]Copy password [u]Copy username [o]Copy code [e]Edit [d]Delete [h]History [t]Tag filter [s]Share [x]Run with SSH key | [↑↓]Navigate",
+    )])])
+    .block(Block::default().borders(Borders::TOP));
 
-    match crate::modules::password_manager::model::retrieve_password() {
-        Ok(passwords) => {
-            if passwords.is_empty() {
-                let paragraph = Paragraph::new("No passwords found.")
-                    .block(
-                        Block::default()
-                            .title("Stored Passwords")
-                            .borders(Borders::ALL),
-                    )
-                    .style(Style::default().fg(Color::Black)); // Set text color to black
-                f.render_widget(paragraph, f.size());
-            } else {
-                let text: Vec<Line> = passwords
-                    .iter()
-                    .map(|entry| {
-                        Line::from(vec![
-                            Span::raw(format!("Service: {}, ", entry.service)),
-                            Span::raw(format!("Username: {}, ", entry.username)),
-                            Span::raw(format!("Password: {}", entry.password)),
-                        ])
-                    })
-                    .collect();
+    f.render_widget(controls, chunks[5]);
 
-                let paragraph = Paragraph::new(text)
-                    .block(
-                        Block::default()
-                            .title("Stored Passwords")
-                            .borders(Borders::ALL),
-                    )
-                    .style(Style::default().fg(text_color)); // Set text color to black
-                f.render_widget(paragraph, f.size());
-            }
-        }
-        Err(e) => {
-            let paragraph = Paragraph::new(format!("Error retrieving passwords: {:?}", e))
-                .block(Block::default().title("Error").borders(Borders::ALL))
-                .style(Style::default().fg(Color::Red)); // Keep error text red
-            f.render_widget(paragraph, f.size());
-        }
+    // Draw the confirmation dialog if active
+    if app_state.confirmation_dialogue != ConfirmationDialogue::None {
+        draw_confirmation_dialogue(f, app_state);
     }
 }
 
@@ -2954,7 +6966,11 @@ fn draw_network_tools_menu(f: &mut Frame) {
 
     let text = vec![
         Line::from(Span::raw("p. Ping")),
+        Line::from(Span::raw("c. Continuous Ping")),
+        Line::from(Span::raw("o. TCP Ping (host:port)")),
+        Line::from(Span::raw("r. Port Check (list of host:port)")),
         Line::from(Span::raw("t. Traceroute")),
+        Line::from(Span::raw("d. DNS Lookup")),
         Line::from(Span::raw("s. Speed Test")),
         Line::from(Span::raw("Esc. Back to Main Menu")),
         Line::from(Span::raw("Press 'q' to quit")),
@@ -2976,10 +6992,64 @@ fn draw_address_input(f: &mut Frame, app_state: &AppState) {
         .split(f.size());
 
     let tool_name = app_state.selected_tool.as_deref().unwrap_or("Unknown Tool");
-    let text = vec![
-        Line::from(Span::raw(format!("Enter address for {}:", tool_name))),
+    if tool_name == "continuous ping" {
+        let field = |index: usize, label: &str, value: &str| {
+            let style = if app_state.input_field == index {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(vec![
+                Span::styled(format!("{:<28}", label), style),
+                Span::styled(value.to_string(), style),
+            ])
+        };
+        let mut text = vec![
+            field(0, "Host or IP address:", &app_state.address),
+            field(1, "Interval (seconds):", &app_state.ping_interval_input),
+            field(2, "Count (0 = until stopped):", &app_state.ping_count_input),
+            Line::from(Span::raw("")),
+            Line::from(Span::styled(
+                "Tab: next field, Enter: start, Esc: back",
+                Style::default().fg(Color::Gray),
+            )),
+        ];
+        if let Some(ref error) = app_state.error_message {
+            text.push(Line::from(Span::styled(
+                error.clone(),
+                Style::default().fg(Color::Red),
+            )));
+        }
+        let paragraph = Paragraph::new(text).block(
+            Block::default()
+                .title("Continuous Ping")
+                .borders(Borders::ALL),
+        );
+        f.render_widget(paragraph, chunks[0]);
+        return;
+    }
+    let prompt = match tool_name {
+        "tcp ping" => "Enter host:port for TCP ping (e.g. example.com:443):".to_string(),
+        "port check" => {
+            "Enter host:port targets separated by commas or spaces (e.g. db:5432, [::1]:22):"
+                .to_string()
+        }
+        "dns lookup" => {
+            draw_dns_form(f, app_state, chunks[0]);
+            return;
+        }
+        _ => format!("Enter address for {}:", tool_name),
+    };
+    let mut text = vec![
+        Line::from(Span::raw(prompt)),
         Line::from(Span::raw(&app_state.address)),
     ];
+    if let Some(ref error) = app_state.error_message {
+        text.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
     let paragraph = Paragraph::new(text)
         .block(
             Block::default()
@@ -2990,6 +7060,366 @@ fn draw_address_input(f: &mut Frame, app_state: &AppState) {
     f.render_widget(paragraph, chunks[0]);
 }
 
+fn draw_continuous_ping(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Min(6),
+            Constraint::Length(8),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+
+    let host = app_state.address.trim();
+    let (title, mut summary) = match app_state.live_ping {
+        Some(ref live_ping) => (
+            format!(
+                "Continuous Ping: {} ({}) via {}",
+                host, live_ping.address, live_ping.method
+            ),
+            vec![
+                Line::from(format!(
+                    "Sent {}   Received {}   Loss {:.1}%",
+                    live_ping.packets_transmitted,
+                    live_ping.packets_received,
+                    live_ping.packet_loss
+                )),
+                Line::from(format!(
+                    "Min {:.3} ms   Avg {:.3} ms   Max {:.3} ms   Jitter {:.3} ms",
+                    live_ping.round_trip_min,
+                    live_ping.round_trip_avg,
+                    live_ping.round_trip_max,
+                    live_ping.jitter
+                )),
+            ],
+        ),
+        None => (
+            format!("Continuous Ping: {}", host),
+            vec![Line::from(format!("Resolving {}...", host))],
+        ),
+    };
+    if let Some(ref error) = app_state.error_message {
+        summary.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    f.render_widget(
+        Paragraph::new(summary).block(Block::default().title(title).borders(Borders::ALL)),
+        chunks[0],
+    );
+
+    let replies = app_state
+        .live_ping
+        .as_ref()
+        .map(|live_ping| live_ping.replies.as_slice())
+        .unwrap_or_default();
+
+    // The newest replies that fit, in microseconds so sub-millisecond times still show
+    let width = chunks[1].width.saturating_sub(2) as usize;
+    let latencies: Vec<Option<u64>> = replies[replies.len().saturating_sub(width)..]
+        .iter()
+        .map(|reply| reply.rtt_ms.map(|rtt_ms| (rtt_ms * 1000.0) as u64))
+        .collect();
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title("Latency (lost packets marked x)")
+                .borders(Borders::ALL),
+        )
+        .data(latencies)
+        .style(Style::default().fg(Color::Green))
+        .absent_value_symbol("x")
+        .absent_value_style(Style::default().fg(Color::Red));
+    f.render_widget(sparkline, chunks[1]);
+
+    let visible = chunks[2].height.saturating_sub(2) as usize;
+    let recent: Vec<Line> = replies
+        .iter()
+        .rev()
+        .take(visible)
+        .map(|reply| match reply.rtt_ms {
+            Some(rtt_ms) => Line::from(format!("seq={} time={:.3} ms", reply.sequence, rtt_ms)),
+            None => Line::from(Span::styled(
+                format!("seq={} timeout", reply.sequence),
+                Style::default().fg(Color::Red),
+            )),
+        })
+        .collect();
+    f.render_widget(
+        Paragraph::new(recent).block(Block::default().title("Replies").borders(Borders::ALL)),
+        chunks[2],
+    );
+
+    let footer = if app_state.ping_receiver.is_some() {
+        "Press any key to stop"
+    } else {
+        "Stopped. Enter: change settings and run again, Esc: back to menu, q: quit"
+    };
+    f.render_widget(
+        Paragraph::new(footer).style(Style::default().fg(Color::Gray)),
+        chunks[3],
+    );
+}
+
+fn draw_dns_form(f: &mut Frame, app_state: &AppState, area: Rect) {
+    let field = |index: usize, label: &str, value: String| {
+        let style = if app_state.input_field == index {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::White)
+        };
+        Line::from(vec![
+            Span::styled(format!("{:<22}", label), style),
+            Span::styled(value, style),
+        ])
+    };
+    let resolvers = if app_state.dns_resolvers_input.is_empty() && app_state.input_field != 2 {
+        "(system resolvers)".to_string()
+    } else {
+        app_state.dns_resolvers_input.clone()
+    };
+    let mut text = vec![
+        field(0, "Name or IP address:", app_state.address.clone()),
+        field(
+            1,
+            "Record type:",
+            format!("< {} >", app_state.dns_record_type),
+        ),
+        field(2, "Resolvers:", resolvers),
+        Line::from(Span::raw("")),
+        Line::from(Span::styled(
+            "Resolvers are IP addresses with an optional port, separated by commas, e.g. \
+             1.1.1.1, 127.0.0.1:5353, [::1]:53. Several resolvers are compared side by side.",
+            Style::default().fg(Color::Gray),
+        )),
+        Line::from(Span::styled(
+            "Tab: next field, \u{2190}/\u{2192}: record type, Enter: look up, Esc: back",
+            Style::default().fg(Color::Gray),
+        )),
+    ];
+    if let Some(ref error) = app_state.error_message {
+        text.push(Line::from(Span::styled(
+            error.clone(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let paragraph = Paragraph::new(text)
+        .wrap(Wrap { trim: false })
+        .block(Block::default().title("DNS Lookup").borders(Borders::ALL));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_dns_lookup(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(1),
+        ])
+        .split(f.area());
+
+    // Answers are compared with the first resolver that responded
+    let reference = app_state
+        .dns_results
+        .iter()
+        .find_map(|answer| answer.result.as_ref().ok());
+    let mut summary = vec![Span::raw(format!(
+        "{} {}  ",
+        app_state.address.trim(),
+        app_state.dns_record_type
+    ))];
+    if app_state.dns_results.len() > 1 {
+        let agree = app_state.dns_results.iter().all(|answer| {
+            matches!((&answer.result, reference), (Ok(response), Some(first)) if response.same_answers(first))
+        });
+        summary.push(if agree {
+            Span::styled("All resolvers agree", Style::default().fg(Color::Green))
+        } else {
+            Span::styled(
+                "Answers differ between resolvers",
+                Style::default().fg(Color::Yellow),
+            )
+        });
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(summary))
+            .block(Block::default().title("DNS Lookup").borders(Borders::ALL)),
+        chunks[0],
+    );
+
+    if !app_state.dns_results.is_empty() {
+        let count = app_state.dns_results.len() as u32;
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, count); count as usize])
+            .split(chunks[1]);
+        for (answer, column) in app_state.dns_results.iter().zip(columns.iter()) {
+            let (title, color, text) = match &answer.result {
+                Ok(response) => {
+                    let color = match reference {
+                        Some(first) if !response.same_answers(first) => Color::Yellow,
+                        _ => Color::Green,
+                    };
+                    let title = format!(
+                        "{}  {}  {:.1} ms{}{}",
+                        answer.resolver,
+                        response.response_code,
+                        response.response_ms,
+                        if response.over_tcp { "  TCP" } else { "" },
+                        if response.authoritative { "  AA" } else { "" },
+                    );
+                    let question = response.question.trim_end_matches('.');
+                    let mut text = Vec::new();
+                    for record in &response.answers {
+                        // Owner names are shown only where they differ, e.g. after a CNAME
+                        if !record.name.trim_end_matches('.').eq_ignore_ascii_case(question) {
+                            text.push(Line::from(Span::styled(
+                                record.name.clone(),
+                                Style::default().fg(Color::Gray),
+                            )));
+                        }
+                        text.push(Line::from(format!(
+                            "{:>7}  {:<5}  {}",
+                            record.ttl, record.record_type, record.data
+                        )));
+                    }
+                    if text.is_empty() {
+                        text.push(Line::from(Span::styled(
+                            "(no records)",
+                            Style::default().fg(Color::Gray),
+                        )));
+                    }
+                    (title, color, text)
+                }
+                Err(e) => (
+                    answer.resolver.to_string(),
+                    Color::Red,
+                    vec![Line::from(e.to_string())],
+                ),
+            };
+            let paragraph = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+                Block::default()
+                    .title(Span::styled(title, Style::default().fg(color)))
+                    .borders(Borders::ALL),
+            );
+            f.render_widget(paragraph, *column);
+        }
+    }
+
+    let footer = if let Some(ref error) = app_state.error_message {
+        Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red)))
+    } else if app_state.dns_receiver.is_some() {
+        Line::from("Querying...")
+    } else {
+        Line::from("\u{2190}/\u{2192}: record type, Enter: edit query, Esc: back to menu, q: quit")
+    };
+    f.render_widget(
+        Paragraph::new(footer).style(Style::default().fg(Color::Gray)),
+        chunks[2],
+    );
+}
+
+fn draw_traceroute(f: &mut Frame, app_state: &AppState) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(2)])
+        .split(f.area());
+
+    let host = app_state.address.trim();
+    let title = match app_state.trace_target {
+        Some((address, max_hops)) => format!(
+            "Traceroute to {} ({}), {} hops max",
+            host, address, max_hops
+        ),
+        None => format!("Traceroute to {}", host),
+    };
+
+    let rows: Vec<Row> = app_state
+        .trace_hops
+        .iter()
+        .map(|hop| {
+            let probes = hop
+                .rtts_ms
+                .iter()
+                .map(|rtt| match rtt {
+                    Some(rtt_ms) => format!("{:.3} ms", rtt_ms),
+                    None => "*".to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+            let color = match hop.status {
+                HopStatus::Router => Color::White,
+                HopStatus::Destination => Color::Green,
+                HopStatus::Unreachable => Color::Red,
+                HopStatus::NoReply => Color::Gray,
+            };
+            let address = match hop.status {
+                HopStatus::Unreachable => format!("{} !", hop.address.as_deref().unwrap_or("")),
+                _ => hop.address.clone().unwrap_or_else(|| "*".to_string()),
+            };
+            Row::new(vec![
+                Cell::from(hop.ttl.to_string()),
+                Cell::from(address),
+                Cell::from(hop.hostname.clone().unwrap_or_default()),
+                Cell::from(probes),
+                Cell::from(
+                    hop.average_ms()
+                        .map(|average| format!("{:.3} ms", average))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(format!("{:.0}%", hop.loss())),
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Length(24),
+            Constraint::Percentage(30),
+            Constraint::Min(20),
+            Constraint::Length(12),
+            Constraint::Length(5),
+        ],
+    )
+    .header(
+        Row::new(vec!["Hop", "Address", "Host name", "Probes", "Avg", "Loss"])
+            .style(Style::default().fg(Color::Yellow)),
+    )
+    .block(Block::default().title(title).borders(Borders::ALL));
+    f.render_widget(table, chunks[0]);
+
+    let last_status = app_state.trace_hops.last().map(|hop| hop.status);
+    let footer = if let Some(ref error) = app_state.error_message {
+        Line::from(Span::styled(error.clone(), Style::default().fg(Color::Red)))
+    } else if app_state.trace_receiver.is_some() {
+        Line::from("Tracing... press any key to stop")
+    } else {
+        let outcome = match last_status {
+            Some(HopStatus::Destination) => format!(
+                "Reached {} in {} hops.",
+                host,
+                app_state.trace_hops.len()
+            ),
+            Some(HopStatus::Unreachable) => "A router reported the destination unreachable.".to_string(),
+            _ => "Stopped.".to_string(),
+        };
+        Line::from(format!(
+            "{} Enter: trace again, Esc: back to menu, q: quit",
+            outcome
+        ))
+    };
+    f.render_widget(
+        Paragraph::new(footer).style(Style::default().fg(Color::Gray)),
+        chunks[1],
+    );
+}
+
 fn draw_speed_test(f: &mut Frame, app_state: &AppState) {
     let display_text = if let Some(ref result) = app_state.result {
         result.clone()
@@ -3068,64 +7498,100 @@ fn draw_speed_test(f: &mut Frame, app_state: &AppState) {
 }
 
 fn draw_confirmation_dialogue(f: &mut Frame, app_state: &AppState) {
-    if let ConfirmationDialogue::KillProcess(pid, ref name) = &app_state.confirmation_dialogue {
-        // Create a centered box for the dialog
-        let area = f.size();
-        let dialog_width = 60.min(area.width.saturating_sub(4));
-        let dialog_height = 10.min(area.height.saturating_sub(4));
-
-        let dialog_area = Rect::new(
-            ((area.width - dialog_width) / 2).max(0),
-            ((area.height - dialog_height) / 2).max(0),
-            dialog_width,
-            dialog_height,
-        );
-
-        // Draw dialog box
-        let dialog = Block::default()
-            .title("Confirm Process Termination")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Red));
-
-        f.render_widget(Clear, dialog_area); // Clear the area
-        f.render_widget(dialog, dialog_area);
-
-        // Dialog content
-        let content_area = Rect::new(
-            dialog_area.x + 2,
-            dialog_area.y + 2,
-            dialog_area.width.saturating_sub(4),
-            dialog_area.height.saturating_sub(4),
-        );
-
-        let message = vec![
-            Line::from(vec![
+    let (title, question, confirm_label) = match &app_state.confirmation_dialogue {
+        ConfirmationDialogue::KillProcess(pid, ref name) => (
+            "Confirm Process Termination",
+            vec![
                 Span::raw("Are you sure you want to terminate process "),
                 Span::styled(name.clone(), Style::default().fg(Color::Yellow)),
                 Span::raw(" (PID: "),
                 Span::styled(pid.to_string(), Style::default().fg(Color::Yellow)),
                 Span::raw(")?"),
-            ]),
-            Line::from(Span::raw("")),
-            Line::from(Span::styled(
-                "This action cannot be undone.",
-                Style::default().fg(Color::Red),
-            )),
-            Line::from(Span::raw("")),
-            Line::from(vec![
-                Span::styled("y", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" - Yes, terminate the process"),
-            ]),
-            Line::from(vec![
-                Span::styled("n", Style::default().add_modifier(Modifier::BOLD)),
-                Span::raw(" - No, cancel"),
-            ]),
-        ];
+            ],
+            " - Yes, terminate the process",
+        ),
+        ConfirmationDialogue::DeletePassword(_, ref service) => (
+            "Confirm Password Deletion",
+            vec![
+                Span::raw("Are you sure you want to delete the entry for "),
+                Span::styled(service.clone(), Style::default().fg(Color::Yellow)),
+                Span::raw("?"),
+            ],
+            " - Yes, delete the entry",
+        ),
+        ConfirmationDialogue::RestoreSnapshot(_, ref taken_at) => (
+            "Confirm Snapshot Restore",
+            vec![
+                Span::raw("Replace the vault with the snapshot taken "),
+                Span::styled(taken_at.clone(), Style::default().fg(Color::Yellow)),
+                Span::raw("? Changes made since then are lost."),
+            ],
+            " - Yes, restore the snapshot",
+        ),
+        ConfirmationDialogue::ExportCsv(ref path) => (
+            "Confirm Unencrypted Export",
+            vec![
+                Span::raw("Write every password in plain text to "),
+                Span::styled(path.clone(), Style::default().fg(Color::Yellow)),
+                Span::raw("?"),
+            ],
+            " - Yes, export unencrypted",
+        ),
+        ConfirmationDialogue::None => return,
+    };
 
-        let content = Paragraph::new(message).alignment(ratatui::layout::Alignment::Center);
+    // Create a centered box for the dialog
+    let area = f.area();
+    let dialog_width = 60.min(area.width.saturating_sub(4));
+    let dialog_height = 10.min(area.height.saturating_sub(4));
+
+    let dialog_area = Rect::new(
+        (area.width - dialog_width) / 2,
+        (area.height - dialog_height) / 2,
+        dialog_width,
+        dialog_height,
+    );
+
+    // Draw dialog box
+    let dialog = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+
+    f.render_widget(Clear, dialog_area); // Clear the area
+    f.render_widget(dialog, dialog_area);
+
+    // Dialog content
+    let content_area = Rect::new(
+        dialog_area.x + 2,
+        dialog_area.y + 2,
+        dialog_area.width.saturating_sub(4),
+        dialog_area.height.saturating_sub(4),
+    );
+
+    let message = vec![
+        Line::from(question),
+        Line::from(Span::raw("")),
+        Line::from(Span::styled(
+            "This action cannot be undone.",
+            Style::default().fg(Color::Red),
+        )),
+        Line::from(Span::raw("")),
+        Line::from(vec![
+            Span::styled("y", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(confirm_label),
+        ]),
+        Line::from(vec![
+            Span::styled("n", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" - No, cancel"),
+        ]),
+    ];
 
-        f.render_widget(content, content_area);
-    }
+    let content = Paragraph::new(message)
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(content, content_area);
 }
 
 fn draw_view_results(f: &mut Frame, app_state: &AppState) {
@@ -3133,11 +7599,15 @@ fn draw_view_results(f: &mut Frame, app_state: &AppState) {
         // Deserialize the PingResult
         if let Ok(ping_result) = serde_json::from_str::<PingResult>(result) {
             let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(100)].as_ref())
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
                 .split(f.size());
 
-            let mut rows = vec![
+            let rows = vec![
+                Row::new(vec![
+                    Cell::from("Method"),
+                    Cell::from(ping_result.method.to_string()),
+                ]),
                 Row::new(vec![
                     Cell::from("Packets Transmitted"),
                     Cell::from(ping_result.packets_transmitted.to_string()),
@@ -3148,41 +7618,63 @@ fn draw_view_results(f: &mut Frame, app_state: &AppState) {
                 ]),
                 Row::new(vec![
                     Cell::from("Packet Loss"),
-                    Cell::from(format!("{}%", ping_result.packet_loss)),
+                    Cell::from(format!("{:.1}%", ping_result.packet_loss)),
                 ]),
-            ];
-
-            if let Some(time) = ping_result.time {
-                rows.push(Row::new(vec![
+                Row::new(vec![
                     Cell::from("Total Time"),
-                    Cell::from(format!("{} ms", time)),
-                ]));
-            }
-
-            rows.extend(vec![
+                    Cell::from(format!("{} ms", ping_result.time)),
+                ]),
                 Row::new(vec![
                     Cell::from("Round-Trip Min"),
-                    Cell::from(format!("{} ms", ping_result.round_trip_min)),
+                    Cell::from(format!("{:.3} ms", ping_result.round_trip_min)),
                 ]),
                 Row::new(vec![
                     Cell::from("Round-Trip Avg"),
-                    Cell::from(format!("{} ms", ping_result.round_trip_avg)),
+                    Cell::from(format!("{:.3} ms", ping_result.round_trip_avg)),
                 ]),
                 Row::new(vec![
                     Cell::from("Round-Trip Max"),
-                    Cell::from(format!("{} ms", ping_result.round_trip_max)),
+                    Cell::from(format!("{:.3} ms", ping_result.round_trip_max)),
                 ]),
                 Row::new(vec![
                     Cell::from("Round-Trip Mdev"),
-                    Cell::from(format!("{} ms", ping_result.round_trip_mdev)),
+                    Cell::from(format!("{:.3} ms", ping_result.round_trip_mdev)),
                 ]),
-            ]);
+            ];
 
-            let table = Table::new(rows, [Constraint::Length(20), Constraint::Length(20)])
+            let table = Table::new(rows, [Constraint::Length(20), Constraint::Min(20)])
                 .header(Row::new(vec!["Metric", "Value"]).style(Style::default().fg(Color::Yellow)))
-                .block(Block::default().title("Ping Results").borders(Borders::ALL));
+                .block(
+                    Block::default()
+                        .title(format!("Ping Results: {}", ping_result.address))
+                        .borders(Borders::ALL),
+                );
 
             f.render_widget(table, chunks[0]);
+
+            let reply_rows: Vec<Row> = ping_result
+                .replies
+                .iter()
+                .map(|reply| match reply.rtt_ms {
+                    Some(rtt_ms) => Row::new(vec![
+                        Cell::from(reply.sequence.to_string()),
+                        Cell::from(format!("{:.3} ms", rtt_ms)),
+                    ]),
+                    None => Row::new(vec![
+                        Cell::from(reply.sequence.to_string()),
+                        Cell::from("timeout"),
+                    ])
+                    .style(Style::default().fg(Color::Red)),
+                })
+                .collect();
+
+            let replies = Table::new(reply_rows, [Constraint::Length(8), Constraint::Min(12)])
+                .header(Row::new(vec!["Seq", "Time"]).style(Style::default().fg(Color::Yellow)))
+                .block(Block::default().title("Replies").borders(Borders::ALL));
+
+            f.render_widget(replies, chunks[1]);
+        } else if let Ok(checks) = serde_json::from_str::<Vec<PortCheck>>(result) {
+            draw_port_checks(f, &checks);
         } else {
             // If deserialization fails, display the raw output
             let paragraph = Paragraph::new(result.clone())
@@ -3198,6 +7690,58 @@ fn draw_view_results(f: &mut Frame, app_state: &AppState) {
     }
 }
 
+fn draw_port_checks(f: &mut Frame, checks: &[PortCheck]) {
+    let count = |state: PortState| checks.iter().filter(|check| check.state == state).count();
+    let title = format!(
+        "Port Check: {} open, {} closed, {} filtered",
+        count(PortState::Open),
+        count(PortState::Closed),
+        count(PortState::Filtered)
+    );
+
+    let rows: Vec<Row> = checks
+        .iter()
+        .map(|check| {
+            let (state, color) = match check.state {
+                PortState::Open => ("open", Color::Green),
+                PortState::Closed => ("closed", Color::Yellow),
+                PortState::Filtered => ("filtered", Color::Red),
+                PortState::Invalid => ("invalid", Color::Red),
+            };
+            Row::new(vec![
+                Cell::from(check.target.clone()),
+                Cell::from(check.address.clone().unwrap_or_default()),
+                Cell::from(state).style(Style::default().fg(color)),
+                Cell::from(
+                    check
+                        .connect_ms
+                        .map(|connect_ms| format!("{:.3} ms", connect_ms))
+                        .unwrap_or_default(),
+                ),
+                Cell::from(check.detail.clone().unwrap_or_default()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Percentage(25),
+            Constraint::Percentage(20),
+            Constraint::Length(9),
+            Constraint::Length(12),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(vec!["Target", "Address", "State", "Connect", "Detail"])
+            .style(Style::default().fg(Color::Yellow)),
+    )
+    .block(Block::default().title(title).borders(Borders::ALL));
+
+    f.render_widget(table, f.area());
+}
+
 fn draw_system_utilities_menu(f: &mut Frame) {
     let text_color = get_text_color();
 
@@ -3854,11 +8398,17 @@ fn cleanup_resources(app_state: &mut AppState) {
     app_state.system_monitor = None;
     app_state.system_snapshot = None;
 
+    // Kill a running SSH key command so its key file is removed before exiting
+    app_state.ssh_command = None;
+
     // Clean up network tools resources
     app_state.speed_test_receiver = None;
     app_state.selected_tool = None;
     app_state.result = None;
 
+    // Don't leave a copied password behind on the clipboard
+    clear_clipboard(app_state);
+
     // Reset state
     app_state.input_mode = InputMode::Normal;
     app_state.error_message = None;
@@ -3982,6 +8532,7 @@ fn render_notifications(f: &mut Frame, notifications: &[Notification]) {
         let (border_color, text_color) = match notification.severity {
             NotificationSeverity::Error => (Color::Red, Color::Red),
             NotificationSeverity::Warning => (Color::Yellow, Color::Yellow),
+            NotificationSeverity::Info => (Color::Green, Color::Green),
         };
 
         // Truncate message to fit within the widget width (accounting for borders)
@@ -4019,3 +8570,66 @@ fn update_ui_with_error(error_message: String) {
     println!("Error: {}", error_message);
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::password_manager::model::{CustomField, PasswordHistoryEntry};
+
+    #[test]
+    fn test_debug_output_never_contains_secrets() {
+        let mut app_state = AppState {
+            password: "typed-secret".into(),
+            master_password: "master-secret".into(),
+            master_password_confirm: "master-secret".into(),
+            import_password: "import-secret".into(),
+            export_password: "export-secret".into(),
+            email_password: "smtp-secret".into(),
+            totp_input: "JBSWY3DPEHPK3PXP".into(),
+            custom_fields_input: "!pin=4321".into(),
+            private_key_input: "pasted-key-secret".into(),
+            ..Default::default()
+        };
+        app_state.password_entries.push(PasswordEntry {
+            service: "example.com".to_string(),
+            username: "alice".to_string(),
+            password: "entry-secret".into(),
+            totp: TotpConfig::parse("JBSWY3DPEHPK3PXP").ok(),
+            notes: "notes-secret".into(),
+            private_key: "key-secret".into(),
+            custom_fields: vec![CustomField {
+                name: "pin".to_string(),
+                value: "4321".into(),
+                secret: true,
+            }],
+            history: vec![PasswordHistoryEntry {
+                password: "old-secret".into(),
+                set_at: None,
+                replaced_at: 0,
+            }],
+            ..Default::default()
+        });
+        let email_config = EmailConfig {
+            password: "smtp-secret".into(),
+            ..Default::default()
+        };
+
+        let output = format!("{:?} {:?}", app_state, email_config);
+        for secret in [
+            "typed-secret",
+            "master-secret",
+            "import-secret",
+            "export-secret",
+            "smtp-secret",
+            "JBSWY3DPEHPK3PXP",
+            "4321",
+            "entry-secret",
+            "notes-secret",
+            "key-secret",
+            "old-secret",
+        ] {
+            assert!(!output.contains(secret), "Debug output leaks {}", secret);
+        }
+        assert!(output.contains("example.com"));
+    }
+}
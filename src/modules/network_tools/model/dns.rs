@@ -0,0 +1,693 @@
+use rand::Rng;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const DNS_PORT: u16 = 53;
+/// How long to wait for each resolver
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+/// UDP payload size advertised with EDNS(0), the size recommended for avoiding fragmentation
+const EDNS_PAYLOAD_SIZE: u16 = 1232;
+/// Compression pointers followed in one name before it is considered a loop
+const MAX_POINTER_JUMPS: usize = 64;
+
+const FLAG_RESPONSE: u16 = 0x8000;
+const FLAG_AUTHORITATIVE: u16 = 0x0400;
+const FLAG_TRUNCATED: u16 = 0x0200;
+const FLAG_RECURSION_DESIRED: u16 = 0x0100;
+
+/// Record types the lookup tool can ask for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordType {
+    A,
+    Aaaa,
+    Mx,
+    Txt,
+    Cname,
+    Ns,
+    Srv,
+    Ptr,
+}
+
+impl RecordType {
+    pub const ALL: [RecordType; 8] = [
+        RecordType::A,
+        RecordType::Aaaa,
+        RecordType::Mx,
+        RecordType::Txt,
+        RecordType::Cname,
+        RecordType::Ns,
+        RecordType::Srv,
+        RecordType::Ptr,
+    ];
+
+    pub fn code(self) -> u16 {
+        match self {
+            RecordType::A => 1,
+            RecordType::Ns => 2,
+            RecordType::Cname => 5,
+            RecordType::Ptr => 12,
+            RecordType::Mx => 15,
+            RecordType::Txt => 16,
+            RecordType::Aaaa => 28,
+            RecordType::Srv => 33,
+        }
+    }
+
+    fn from_code(code: u16) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|record_type| record_type.code() == code)
+    }
+
+    /// The type after this one in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The type before this one in `ALL`, wrapping around
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for RecordType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            RecordType::A => "A",
+            RecordType::Aaaa => "AAAA",
+            RecordType::Mx => "MX",
+            RecordType::Txt => "TXT",
+            RecordType::Cname => "CNAME",
+            RecordType::Ns => "NS",
+            RecordType::Srv => "SRV",
+            RecordType::Ptr => "PTR",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug)]
+pub enum DnsError {
+    /// The name cannot be encoded (empty label, label over 63 or name over 255 bytes)
+    InvalidName(String),
+    /// The resolver did not answer in time
+    Timeout,
+    /// The response does not parse
+    Malformed(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for DnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DnsError::InvalidName(name) => write!(f, "Invalid domain name: {}", name),
+            DnsError::Timeout => write!(f, "No response (timed out)"),
+            DnsError::Malformed(e) => write!(f, "Malformed response: {}", e),
+            DnsError::Io(e) => write!(f, "Query failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for DnsError {}
+
+impl From<io::Error> for DnsError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => DnsError::Timeout,
+            _ => DnsError::Io(e),
+        }
+    }
+}
+
+/// One record of the answer section
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsRecord {
+    /// Owner name, fully qualified with a trailing dot
+    pub name: String,
+    /// Type name, or `TYPE<n>` for types the tool does not know
+    pub record_type: String,
+    pub ttl: u32,
+    /// Record data in zone file notation
+    pub data: String,
+}
+
+/// A resolver's response to one query
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsResponse {
+    /// Name that was asked for; an IP address becomes its reverse name for PTR
+    pub question: String,
+    /// NOERROR, NXDOMAIN, SERVFAIL, ...
+    pub response_code: String,
+    pub authoritative: bool,
+    pub answers: Vec<DnsRecord>,
+    /// Time from sending the query to receiving the full response
+    pub response_ms: f32,
+    /// The UDP response was truncated and the query was repeated over TCP
+    pub over_tcp: bool,
+}
+
+impl DnsResponse {
+    /// True if both responses hold the same records, ignoring TTLs and order
+    pub fn same_answers(&self, other: &DnsResponse) -> bool {
+        let records = |response: &DnsResponse| {
+            let mut records: Vec<(String, String, String)> = response
+                .answers
+                .iter()
+                .map(|record| {
+                    (
+                        record.name.to_lowercase(),
+                        record.record_type.clone(),
+                        record.data.to_lowercase(),
+                    )
+                })
+                .collect();
+            records.sort();
+            records
+        };
+        self.response_code == other.response_code && records(self) == records(other)
+    }
+}
+
+/// The name to query: for PTR an IP address is turned into its `in-addr.arpa` or
+/// `ip6.arpa` name
+pub fn query_name(name: &str, record_type: RecordType) -> String {
+    let name = name.trim();
+    match (record_type, name.parse::<IpAddr>()) {
+        (RecordType::Ptr, Ok(IpAddr::V4(address))) => {
+            let [a, b, c, d] = address.octets();
+            format!("{}.{}.{}.{}.in-addr.arpa", d, c, b, a)
+        }
+        (RecordType::Ptr, Ok(IpAddr::V6(address))) => {
+            let mut labels: Vec<String> = address
+                .octets()
+                .iter()
+                .flat_map(|byte| [byte >> 4, byte & 0x0f])
+                .map(|nibble| format!("{:x}", nibble))
+                .collect();
+            labels.reverse();
+            format!("{}.ip6.arpa", labels.join("."))
+        }
+        _ => name.to_string(),
+    }
+}
+
+fn encode_name(name: &str) -> Result<Vec<u8>, DnsError> {
+    let invalid = || DnsError::InvalidName(name.to_string());
+    let trimmed = name.trim().trim_end_matches('.');
+    let mut encoded = Vec::new();
+    if !trimmed.is_empty() {
+        for label in trimmed.split('.') {
+            if label.is_empty() || label.len() > 63 {
+                return Err(invalid());
+            }
+            encoded.push(label.len() as u8);
+            encoded.extend_from_slice(label.as_bytes());
+        }
+    } else if name.trim() != "." {
+        return Err(invalid());
+    }
+    encoded.push(0);
+    if encoded.len() > 255 {
+        return Err(invalid());
+    }
+    Ok(encoded)
+}
+
+fn encode_query(id: u16, name: &str, record_type: RecordType) -> Result<Vec<u8>, DnsError> {
+    let mut query = Vec::with_capacity(64);
+    query.extend_from_slice(&id.to_be_bytes());
+    query.extend_from_slice(&FLAG_RECURSION_DESIRED.to_be_bytes());
+    // One question, no answers or authority, and the EDNS(0) OPT record
+    for count in [1u16, 0, 0, 1] {
+        query.extend_from_slice(&count.to_be_bytes());
+    }
+    query.extend_from_slice(&encode_name(name)?);
+    query.extend_from_slice(&record_type.code().to_be_bytes());
+    query.extend_from_slice(&1u16.to_be_bytes());
+    // OPT: root owner, type 41, the payload size as class, no extended flags, no options
+    query.push(0);
+    query.extend_from_slice(&41u16.to_be_bytes());
+    query.extend_from_slice(&EDNS_PAYLOAD_SIZE.to_be_bytes());
+    query.extend_from_slice(&[0, 0, 0, 0, 0, 0]);
+    Ok(query)
+}
+
+fn truncated_at(what: &str) -> DnsError {
+    DnsError::Malformed(format!("ends inside {}", what))
+}
+
+fn read_u16(message: &[u8], position: usize) -> Result<u16, DnsError> {
+    message
+        .get(position..position + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| truncated_at("a field"))
+}
+
+/// Reads the possibly compressed name at `start`; returns it and the position after it
+fn read_name(message: &[u8], start: usize) -> Result<(String, usize), DnsError> {
+    let mut labels = Vec::new();
+    let mut position = start;
+    let mut end = None;
+    let mut jumps = 0;
+    loop {
+        let length = *message
+            .get(position)
+            .ok_or_else(|| truncated_at("a name"))?;
+        match length & 0xc0 {
+            0xc0 => {
+                let low = *message
+                    .get(position + 1)
+                    .ok_or_else(|| truncated_at("a name"))?;
+                end.get_or_insert(position + 2);
+                position = (usize::from(length & 0x3f) << 8) | usize::from(low);
+                jumps += 1;
+                if jumps > MAX_POINTER_JUMPS {
+                    return Err(DnsError::Malformed("compression loop".to_string()));
+                }
+            }
+            0x00 if length == 0 => {
+                position += 1;
+                break;
+            }
+            0x00 => {
+                let label = message
+                    .get(position + 1..position + 1 + usize::from(length))
+                    .ok_or_else(|| truncated_at("a name"))?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                position += 1 + usize::from(length);
+            }
+            _ => return Err(DnsError::Malformed("unknown label type".to_string())),
+        }
+    }
+    let name = if labels.is_empty() {
+        ".".to_string()
+    } else {
+        format!("{}.", labels.join("."))
+    };
+    Ok((name, end.unwrap_or(position)))
+}
+
+/// Formats the data of a record in zone file notation
+fn format_rdata(
+    message: &[u8],
+    code: u16,
+    start: usize,
+    length: usize,
+) -> Result<String, DnsError> {
+    let rdata = message
+        .get(start..start + length)
+        .ok_or_else(|| truncated_at("record data"))?;
+    let data = match (code, rdata.len()) {
+        (1, 4) => Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]).to_string(),
+        (28, 16) => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(rdata);
+            Ipv6Addr::from(octets).to_string()
+        }
+        // NS, CNAME and PTR hold a single name
+        (2 | 5 | 12, _) => read_name(message, start)?.0,
+        (15, 3..) => format!(
+            "{} {}",
+            read_u16(message, start)?,
+            read_name(message, start + 2)?.0
+        ),
+        (16, _) => {
+            let mut strings = Vec::new();
+            let mut rest = rdata;
+            while let Some((&length, tail)) = rest.split_first() {
+                let text = tail
+                    .get(..usize::from(length))
+                    .ok_or_else(|| truncated_at("a TXT string"))?;
+                strings.push(format!(
+                    "\"{}\"",
+                    String::from_utf8_lossy(text).replace('"', "\\\"")
+                ));
+                rest = &tail[usize::from(length)..];
+            }
+            strings.join(" ")
+        }
+        (33, 7..) => format!(
+            "{} {} {} {}",
+            read_u16(message, start)?,
+            read_u16(message, start + 2)?,
+            read_u16(message, start + 4)?,
+            read_name(message, start + 6)?.0
+        ),
+        // RFC 3597 notation for everything else
+        _ => format!(
+            "\\# {} {}",
+            rdata.len(),
+            rdata
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect::<String>()
+        ),
+    };
+    Ok(data)
+}
+
+fn response_code_name(code: u16) -> String {
+    match code {
+        0 => "NOERROR".to_string(),
+        1 => "FORMERR".to_string(),
+        2 => "SERVFAIL".to_string(),
+        3 => "NXDOMAIN".to_string(),
+        4 => "NOTIMP".to_string(),
+        5 => "REFUSED".to_string(),
+        code => format!("RCODE{}", code),
+    }
+}
+
+/// Parses a response to query `id` into its response code, authoritative flag and answers
+fn parse_response(message: &[u8], id: u16) -> Result<(String, bool, Vec<DnsRecord>), DnsError> {
+    if read_u16(message, 0)? != id {
+        return Err(DnsError::Malformed(
+            "response to a different query".to_string(),
+        ));
+    }
+    let flags = read_u16(message, 2)?;
+    if flags & FLAG_RESPONSE == 0 {
+        return Err(DnsError::Malformed("not a response".to_string()));
+    }
+    let questions = read_u16(message, 4)?;
+    let answer_count = read_u16(message, 6)?;
+
+    let mut position = 12;
+    for _ in 0..questions {
+        position = read_name(message, position)?.1 + 4;
+    }
+    let mut answers = Vec::new();
+    for _ in 0..answer_count {
+        let (name, after_name) = read_name(message, position)?;
+        let code = read_u16(message, after_name)?;
+        let ttl = message
+            .get(after_name + 4..after_name + 8)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or_else(|| truncated_at("a record"))?;
+        let length = usize::from(read_u16(message, after_name + 8)?);
+        let data_start = after_name + 10;
+        answers.push(DnsRecord {
+            name,
+            record_type: RecordType::from_code(code)
+                .map(|record_type| record_type.to_string())
+                .unwrap_or_else(|| format!("TYPE{}", code)),
+            ttl,
+            data: format_rdata(message, code, data_start, length)?,
+        });
+        position = data_start + length;
+    }
+    Ok((
+        response_code_name(flags & 0x000f),
+        flags & FLAG_AUTHORITATIVE != 0,
+        answers,
+    ))
+}
+
+fn udp_exchange(
+    resolver: SocketAddr,
+    query: &[u8],
+    id: u16,
+    timeout: Duration,
+) -> Result<Vec<u8>, DnsError> {
+    let local = match resolver {
+        SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(local, 0))?;
+    socket.connect(resolver)?;
+    socket.send(query)?;
+
+    let deadline = Instant::now() + timeout;
+    let mut buffer = vec![0u8; 65535];
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(DnsError::Timeout);
+        }
+        socket.set_read_timeout(Some(remaining))?;
+        let length = socket.recv(&mut buffer)?;
+        // Stray answers to earlier queries from the same port are skipped
+        if length >= 2 && u16::from_be_bytes([buffer[0], buffer[1]]) == id {
+            buffer.truncate(length);
+            return Ok(buffer);
+        }
+    }
+}
+
+fn tcp_exchange(
+    resolver: SocketAddr,
+    query: &[u8],
+    timeout: Duration,
+) -> Result<Vec<u8>, DnsError> {
+    let mut stream = TcpStream::connect_timeout(&resolver, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    // Over TCP every message is preceded by its length
+    let mut framed = (query.len() as u16).to_be_bytes().to_vec();
+    framed.extend_from_slice(query);
+    stream.write_all(&framed)?;
+
+    let mut length = [0u8; 2];
+    stream.read_exact(&mut length)?;
+    let mut message = vec![0u8; usize::from(u16::from_be_bytes(length))];
+    stream.read_exact(&mut message)?;
+    Ok(message)
+}
+
+/// Asks `resolver` for the `record_type` records of `name`
+///
+/// The query goes over UDP and is repeated over TCP if the response was truncated.
+pub fn query(
+    resolver: SocketAddr,
+    name: &str,
+    record_type: RecordType,
+    timeout: Duration,
+) -> Result<DnsResponse, DnsError> {
+    let question = query_name(name, record_type);
+    let id: u16 = rand::thread_rng().gen();
+    let request = encode_query(id, &question, record_type)?;
+
+    let start = Instant::now();
+    let mut message = udp_exchange(resolver, &request, id, timeout)?;
+    let over_tcp = read_u16(&message, 2)? & FLAG_TRUNCATED != 0;
+    if over_tcp {
+        message = tcp_exchange(resolver, &request, timeout)?;
+    }
+    let response_ms = start.elapsed().as_secs_f32() * 1000.0;
+
+    let (response_code, authoritative, answers) = parse_response(&message, id)?;
+    Ok(DnsResponse {
+        question,
+        response_code,
+        authoritative,
+        answers,
+        response_ms,
+        over_tcp,
+    })
+}
+
+/// One resolver's result in a comparison
+#[derive(Debug)]
+pub struct ResolverAnswer {
+    pub resolver: SocketAddr,
+    pub result: Result<DnsResponse, DnsError>,
+}
+
+/// Sends the same query to every resolver at once; results are in the order given
+pub fn compare_resolvers(
+    resolvers: &[SocketAddr],
+    name: &str,
+    record_type: RecordType,
+    timeout: Duration,
+) -> Vec<ResolverAnswer> {
+    thread::scope(|scope| {
+        let handles: Vec<_> = resolvers
+            .iter()
+            .map(|resolver| scope.spawn(move || query(*resolver, name, record_type, timeout)))
+            .collect();
+        handles
+            .into_iter()
+            .zip(resolvers)
+            .map(|(handle, resolver)| ResolverAnswer {
+                resolver: *resolver,
+                result: handle.join().unwrap_or_else(|_| {
+                    Err(DnsError::Io(io::Error::other("query thread panicked")))
+                }),
+            })
+            .collect()
+    })
+}
+
+/// Reads a resolver address: an IP address, optionally with a port (`[v6]:port` for IPv6)
+pub fn parse_resolver(input: &str) -> Option<SocketAddr> {
+    let input = input.trim();
+    input.parse::<SocketAddr>().ok().or_else(|| {
+        input
+            .parse::<IpAddr>()
+            .ok()
+            .map(|address| SocketAddr::new(address, DNS_PORT))
+    })
+}
+
+/// The nameservers listed in `/etc/resolv.conf`
+pub fn system_resolvers() -> Vec<SocketAddr> {
+    fs::read_to_string("/etc/resolv.conf")
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            match (fields.next(), fields.next()) {
+                (Some("nameserver"), Some(address)) => parse_resolver(address),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Answers a query the way a server for example.com would; TXT answers only fit over TCP
+    fn respond(query: &[u8], over_tcp: bool) -> Vec<u8> {
+        let question_end = read_name(query, 12).unwrap().1 + 4;
+        let record_type = read_u16(query, question_end - 4).unwrap();
+        let truncate = record_type == 16 && !over_tcp;
+
+        let mut rdata_records: Vec<(u16, Vec<u8>)> = Vec::new();
+        match record_type {
+            1 => rdata_records.push((1, vec![192, 0, 2, 1])),
+            15 => {
+                // Preference 10, exchange "mail" + pointer to the question name
+                let mut rdata = vec![0, 10, 4];
+                rdata.extend_from_slice(b"mail");
+                rdata.extend_from_slice(&[0xc0, 12]);
+                rdata_records.push((15, rdata));
+            }
+            16 if !truncate => {
+                let mut rdata = vec![5];
+                rdata.extend_from_slice(b"hello");
+                rdata.push(5);
+                rdata.extend_from_slice(b"world");
+                rdata_records.push((16, rdata));
+            }
+            _ => {}
+        }
+
+        let mut flags = 0x8180u16;
+        if truncate {
+            flags |= FLAG_TRUNCATED;
+        }
+        if record_type == 28 {
+            flags |= 3;
+        }
+        let mut response = query[..2].to_vec();
+        response.extend_from_slice(&flags.to_be_bytes());
+        for count in [1, rdata_records.len() as u16, 0, 0] {
+            response.extend_from_slice(&count.to_be_bytes());
+        }
+        response.extend_from_slice(&query[12..question_end]);
+        for (code, rdata) in rdata_records {
+            response.extend_from_slice(&[0xc0, 12]);
+            response.extend_from_slice(&code.to_be_bytes());
+            response.extend_from_slice(&1u16.to_be_bytes());
+            response.extend_from_slice(&300u32.to_be_bytes());
+            response.extend_from_slice(&(rdata.len() as u16).to_be_bytes());
+            response.extend_from_slice(&rdata);
+        }
+        response
+    }
+
+    #[test]
+    fn test_names_and_reverse_names() {
+        assert_eq!(
+            encode_name("example.com.").unwrap(),
+            b"\x07example\x03com\x00".to_vec()
+        );
+        assert_eq!(encode_name(".").unwrap(), vec![0]);
+        assert!(encode_name("a..b").is_err());
+        assert!(encode_name(&"a".repeat(64)).is_err());
+
+        assert_eq!(
+            query_name("192.0.2.1", RecordType::Ptr),
+            "1.2.0.192.in-addr.arpa"
+        );
+        assert!(query_name("2001:db8::1", RecordType::Ptr)
+            .starts_with("1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6"));
+        assert_eq!(query_name("192.0.2.1", RecordType::A), "192.0.2.1");
+
+        assert_eq!(parse_resolver("1.1.1.1"), "1.1.1.1:53".parse().ok());
+        assert_eq!(parse_resolver("[::1]:5353"), "[::1]:5353".parse().ok());
+        assert_eq!(parse_resolver("dns.example"), None);
+        assert_eq!(RecordType::Ptr.next(), RecordType::A);
+        assert_eq!(RecordType::A.previous(), RecordType::Ptr);
+    }
+
+    #[test]
+    fn test_queries_against_a_local_server() {
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let resolver = udp.local_addr().unwrap();
+        let tcp = TcpListener::bind(resolver).unwrap();
+        thread::spawn(move || {
+            let mut buffer = [0u8; 512];
+            while let Ok((length, peer)) = udp.recv_from(&mut buffer) {
+                let _ = udp.send_to(&respond(&buffer[..length], false), peer);
+            }
+        });
+        thread::spawn(move || {
+            for mut stream in tcp.incoming().flatten() {
+                let mut length = [0u8; 2];
+                stream.read_exact(&mut length).unwrap();
+                let mut query = vec![0u8; usize::from(u16::from_be_bytes(length))];
+                stream.read_exact(&mut query).unwrap();
+                let response = respond(&query, true);
+                stream
+                    .write_all(&(response.len() as u16).to_be_bytes())
+                    .unwrap();
+                stream.write_all(&response).unwrap();
+            }
+        });
+
+        let a = query(resolver, "example.com", RecordType::A, QUERY_TIMEOUT).unwrap();
+        assert_eq!(a.response_code, "NOERROR");
+        assert_eq!(
+            a.answers,
+            vec![DnsRecord {
+                name: "example.com.".to_string(),
+                record_type: "A".to_string(),
+                ttl: 300,
+                data: "192.0.2.1".to_string(),
+            }]
+        );
+        assert!(!a.over_tcp);
+
+        let mx = query(resolver, "example.com", RecordType::Mx, QUERY_TIMEOUT).unwrap();
+        assert_eq!(mx.answers[0].data, "10 mail.example.com.");
+
+        let txt = query(resolver, "example.com", RecordType::Txt, QUERY_TIMEOUT).unwrap();
+        assert!(txt.over_tcp);
+        assert_eq!(txt.answers[0].data, "\"hello\" \"world\"");
+
+        let missing = query(resolver, "example.com", RecordType::Aaaa, QUERY_TIMEOUT).unwrap();
+        assert_eq!(missing.response_code, "NXDOMAIN");
+        assert!(missing.answers.is_empty());
+
+        let compared = compare_resolvers(
+            &[resolver, resolver],
+            "example.com",
+            RecordType::A,
+            QUERY_TIMEOUT,
+        );
+        let first = compared[0].result.as_ref().unwrap();
+        assert!(first.same_answers(compared[1].result.as_ref().unwrap()));
+        assert!(!first.same_answers(&mx));
+    }
+}
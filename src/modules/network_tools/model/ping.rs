@@ -0,0 +1,607 @@
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::fmt;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Port the UDP fallback sends to; nothing listens there, so the host answers with
+/// "port unreachable"
+const UDP_PROBE_PORT: u16 = 33434;
+/// Longest a continuous ping sleeps before it checks whether it was stopped
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// Port the TCP fallback connects to
+pub const TCP_PROBE_PORT: u16 = 443;
+/// Payload of every probe, used to tell our echo replies from other ICMP traffic
+const PAYLOAD: &[u8] = b"toolbox ping payload";
+
+/// How the round trip is measured
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PingMethod {
+    /// ICMP echo over an unprivileged datagram socket (or a raw one when allowed)
+    Icmp,
+    /// Time until a UDP datagram to a closed port is refused
+    Udp,
+    /// Time until a TCP connection to the port is accepted or refused
+    Tcp(u16),
+}
+
+impl fmt::Display for PingMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PingMethod::Icmp => write!(f, "ICMP echo"),
+            PingMethod::Udp => write!(f, "UDP port {}", UDP_PROBE_PORT),
+            PingMethod::Tcp(port) => write!(f, "TCP connect to port {}", port),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PingError {
+    /// The host name did not resolve to an address
+    Resolve(String),
+    /// A `host:port` target was expected
+    InvalidTarget(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for PingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PingError::Resolve(host) => write!(f, "Cannot resolve host: {}", host),
+            PingError::InvalidTarget(target) => write!(f, "Expected host:port, got {}", target),
+            PingError::Io(e) => write!(f, "Ping failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PingError {}
+
+impl From<io::Error> for PingError {
+    fn from(e: io::Error) -> Self {
+        PingError::Io(e)
+    }
+}
+
+/// One probe and its round-trip time, `None` if no reply arrived in time
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PingReply {
+    pub sequence: u16,
+    pub rtt_ms: Option<f32>,
+}
+
+/// Per-packet replies of a ping run and the statistics over them
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PingResult {
+    /// Address that was pinged
+    pub address: String,
+    pub method: PingMethod,
+    pub replies: Vec<PingReply>,
+    pub packets_transmitted: u32,
+    pub packets_received: u32,
+    pub packet_loss: f32,
+    /// Duration of the whole run (ms)
+    pub time: u32,
+    pub round_trip_min: f32,
+    pub round_trip_avg: f32,
+    pub round_trip_max: f32,
+    pub round_trip_mdev: f32,
+    pub jitter: f32,
+}
+
+impl PingResult {
+    /// A result without replies yet, for a continuous ping to fill in
+    pub fn new(address: IpAddr, method: PingMethod) -> Self {
+        Self {
+            address: address.to_string(),
+            method,
+            replies: Vec::new(),
+            packets_transmitted: 0,
+            packets_received: 0,
+            packet_loss: 0.0,
+            time: 0,
+            round_trip_min: 0.0,
+            round_trip_avg: 0.0,
+            round_trip_max: 0.0,
+            round_trip_mdev: 0.0,
+            jitter: 0.0,
+        }
+    }
+
+    pub fn from_replies(
+        address: IpAddr,
+        method: PingMethod,
+        replies: Vec<PingReply>,
+        elapsed: Duration,
+    ) -> Self {
+        let mut result = Self::new(address, method);
+        result.replies = replies;
+        result.update_statistics(elapsed);
+        result
+    }
+
+    /// Adds the next reply of a continuous ping and updates the statistics
+    pub fn record(&mut self, reply: PingReply, method: PingMethod, elapsed: Duration) {
+        self.method = method;
+        self.replies.push(reply);
+        self.update_statistics(elapsed);
+    }
+
+    /// Aggregates the replies the way iputils ping does, with mdev as the standard
+    /// deviation and jitter as the mean difference between consecutive round trips
+    fn update_statistics(&mut self, elapsed: Duration) {
+        let rtts: Vec<f32> = self
+            .replies
+            .iter()
+            .filter_map(|reply| reply.rtt_ms)
+            .collect();
+        self.packets_transmitted = self.replies.len() as u32;
+        self.packets_received = rtts.len() as u32;
+        self.packet_loss = if self.packets_transmitted == 0 {
+            0.0
+        } else {
+            100.0 * (self.packets_transmitted - self.packets_received) as f32
+                / self.packets_transmitted as f32
+        };
+        self.time = elapsed.as_millis() as u32;
+
+        if rtts.is_empty() {
+            return;
+        }
+        let count = rtts.len() as f32;
+        let avg = rtts.iter().sum::<f32>() / count;
+        let mean_square = rtts.iter().map(|rtt| rtt * rtt).sum::<f32>() / count;
+        self.round_trip_min = rtts.iter().copied().fold(f32::INFINITY, f32::min);
+        self.round_trip_avg = avg;
+        self.round_trip_max = rtts.iter().copied().fold(0.0, f32::max);
+        self.round_trip_mdev = (mean_square - avg * avg).max(0.0).sqrt();
+        self.jitter = if rtts.len() < 2 {
+            0.0
+        } else {
+            rtts.windows(2)
+                .map(|pair| (pair[1] - pair[0]).abs())
+                .sum::<f32>()
+                / (rtts.len() - 1) as f32
+        };
+    }
+}
+
+/// Settings of a ping run
+#[derive(Debug, Clone)]
+pub struct PingOptions {
+    /// Probes to send; 0 keeps a continuous ping going until it is stopped
+    pub count: u16,
+    /// Time between the start of two probes
+    pub interval: Duration,
+    /// How long to wait for each reply
+    pub timeout: Duration,
+}
+
+impl Default for PingOptions {
+    fn default() -> Self {
+        Self {
+            count: 4,
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// True for errors that mean the probe got no answer rather than that it could not be sent
+fn no_reply(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::HostUnreachable
+            | io::ErrorKind::NetworkUnreachable
+    )
+}
+
+/// Internet checksum (RFC 1071) of an ICMPv4 message
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|pair| u32::from(u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+fn echo_request(ipv6: bool, ident: u16, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![if ipv6 { 128 } else { 8 }, 0, 0, 0];
+    packet.extend_from_slice(&ident.to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(PAYLOAD);
+    // The kernel fills in the ICMPv6 checksum, which covers a pseudo header
+    if !ipv6 {
+        let checksum = checksum(&packet);
+        packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+    }
+    packet
+}
+
+/// Identifier and sequence number of an echo reply to one of our requests
+///
+/// Raw sockets, and datagram sockets on macOS, hand IPv4 packets over with their IP
+/// header, which is skipped.
+fn parse_echo_reply(packet: &[u8], ipv6: bool) -> Option<(u16, u16)> {
+    let packet = match packet.first() {
+        Some(first) if !ipv6 && first >> 4 == 4 => packet.get(usize::from(first & 0x0f) * 4..)?,
+        _ => packet,
+    };
+    let reply_type = if ipv6 { 129 } else { 0 };
+    if packet.len() < 8 || packet[0] != reply_type || packet[1] != 0 || &packet[8..] != PAYLOAD {
+        return None;
+    }
+    Some((
+        u16::from_be_bytes([packet[4], packet[5]]),
+        u16::from_be_bytes([packet[6], packet[7]]),
+    ))
+}
+
+/// An ICMP socket connected to the target
+struct IcmpSocket {
+    socket: UdpSocket,
+    /// Raw sockets see every echo reply, not just the ones meant for this socket
+    raw: bool,
+}
+
+impl IcmpSocket {
+    fn open(target: IpAddr) -> io::Result<Self> {
+        let (domain, protocol) = match target {
+            IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+            IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+        };
+        let (socket, raw) = match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+            Ok(socket) => (socket, false),
+            Err(_) => (Socket::new(domain, Type::RAW, Some(protocol))?, true),
+        };
+        socket.connect(&SocketAddr::new(target, 0).into())?;
+        Ok(Self {
+            socket: socket.into(),
+            raw,
+        })
+    }
+}
+
+/// Sends probes to one address and times the replies
+pub struct Pinger {
+    target: IpAddr,
+    method: PingMethod,
+    icmp: Option<IcmpSocket>,
+    ident: u16,
+    timeout: Duration,
+    /// Switch from UDP to TCP if the first UDP probe goes unanswered
+    tcp_fallback: bool,
+    answered: bool,
+}
+
+impl Pinger {
+    /// Pings with ICMP echo where the system allows it, and with UDP or TCP otherwise
+    pub fn new(target: IpAddr, timeout: Duration) -> Self {
+        Self::with_method(target, PingMethod::Icmp, timeout).unwrap_or_else(|_| Self {
+            tcp_fallback: true,
+            ..Self::unconnected(target, PingMethod::Udp, timeout)
+        })
+    }
+
+    /// Pings with `method` only; fails if an ICMP socket cannot be opened
+    pub fn with_method(target: IpAddr, method: PingMethod, timeout: Duration) -> io::Result<Self> {
+        let mut pinger = Self::unconnected(target, method, timeout);
+        if method == PingMethod::Icmp {
+            pinger.icmp = Some(IcmpSocket::open(target)?);
+        }
+        Ok(pinger)
+    }
+
+    fn unconnected(target: IpAddr, method: PingMethod, timeout: Duration) -> Self {
+        Self {
+            target,
+            method,
+            icmp: None,
+            ident: std::process::id() as u16,
+            timeout,
+            tcp_fallback: false,
+            answered: false,
+        }
+    }
+
+    pub fn method(&self) -> PingMethod {
+        self.method
+    }
+
+    /// Sends one probe and waits for its reply; `None` means it timed out
+    pub fn probe(&mut self, sequence: u16) -> io::Result<Option<Duration>> {
+        let rtt = match (&self.icmp, self.method) {
+            (Some(icmp), _) => self.icmp_probe(icmp, sequence)?,
+            (None, PingMethod::Tcp(port)) => self.tcp_probe(port)?,
+            (None, _) => self.udp_probe()?,
+        };
+        if rtt.is_none() && self.method == PingMethod::Udp && self.tcp_fallback && !self.answered {
+            // Hosts that drop UDP silently often still answer TCP
+            self.method = PingMethod::Tcp(TCP_PROBE_PORT);
+            return self.probe(sequence);
+        }
+        self.answered |= rtt.is_some();
+        Ok(rtt)
+    }
+
+    fn icmp_probe(&self, icmp: &IcmpSocket, sequence: u16) -> io::Result<Option<Duration>> {
+        let ipv6 = self.target.is_ipv6();
+        let start = Instant::now();
+        icmp.socket
+            .send(&echo_request(ipv6, self.ident, sequence))?;
+
+        let deadline = start + self.timeout;
+        let mut buffer = [0u8; 1500];
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            icmp.socket.set_read_timeout(Some(remaining))?;
+            match icmp.socket.recv(&mut buffer) {
+                Ok(len) => {
+                    // Late replies to earlier probes and other programs' pings are skipped
+                    if let Some((ident, reply_sequence)) = parse_echo_reply(&buffer[..len], ipv6) {
+                        if reply_sequence == sequence && (!icmp.raw || ident == self.ident) {
+                            return Ok(Some(start.elapsed()));
+                        }
+                    }
+                }
+                Err(e) if no_reply(&e) => return Ok(None),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn udp_probe(&self) -> io::Result<Option<Duration>> {
+        let local = match self.target {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind(SocketAddr::new(local, 0))?;
+        socket.connect(SocketAddr::new(self.target, UDP_PROBE_PORT))?;
+        socket.set_read_timeout(Some(self.timeout))?;
+
+        let start = Instant::now();
+        socket.send(PAYLOAD)?;
+        let mut buffer = [0u8; 512];
+        match socket.recv(&mut buffer) {
+            Ok(_) => Ok(Some(start.elapsed())),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Ok(Some(start.elapsed())),
+            Err(e) if no_reply(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn tcp_probe(&self, port: u16) -> io::Result<Option<Duration>> {
+        let start = Instant::now();
+        match TcpStream::connect_timeout(&SocketAddr::new(self.target, port), self.timeout) {
+            Ok(_) => Ok(Some(start.elapsed())),
+            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => Ok(Some(start.elapsed())),
+            Err(e) if no_reply(&e) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Resolves a host name or IP address, preferring the first address returned
+pub fn resolve(address: &str) -> Result<IpAddr, PingError> {
+    let address = address.trim();
+    (address, 0)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .map(|address| address.ip())
+        .ok_or_else(|| PingError::Resolve(address.to_string()))
+}
+
+/// Sends a probe every `options.interval` and hands each reply to `on_reply`
+///
+/// Stops after `options.count` probes (never, if it is 0), once `stop` is set or when
+/// `on_reply` returns false.
+fn run_probes(
+    pinger: &mut Pinger,
+    options: &PingOptions,
+    stop: &AtomicBool,
+    mut on_reply: impl FnMut(PingReply, PingMethod) -> bool,
+) -> io::Result<()> {
+    let start = Instant::now();
+    let mut index: u32 = 0;
+    while options.count == 0 || index < u32::from(options.count) {
+        let send_at = start + options.interval * index;
+        loop {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            let wait = send_at.saturating_duration_since(Instant::now());
+            if wait.is_zero() {
+                break;
+            }
+            thread::sleep(wait.min(STOP_CHECK_INTERVAL));
+        }
+
+        // Sequence numbers wrap like ping's do
+        let sequence = (index as u16).wrapping_add(1);
+        let rtt = pinger.probe(sequence)?;
+        let reply = PingReply {
+            sequence,
+            rtt_ms: rtt.map(|rtt| rtt.as_secs_f32() * 1000.0),
+        };
+        if !on_reply(reply, pinger.method()) {
+            break;
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Runs `options.count` probes with `pinger` and aggregates their replies
+pub(super) fn run_counted(mut pinger: Pinger, options: &PingOptions) -> io::Result<PingResult> {
+    let start = Instant::now();
+    let mut replies = Vec::new();
+    run_probes(&mut pinger, options, &AtomicBool::new(false), |reply, _| {
+        replies.push(reply);
+        true
+    })?;
+    Ok(PingResult::from_replies(
+        pinger.target,
+        pinger.method(),
+        replies,
+        start.elapsed(),
+    ))
+}
+
+/// Runs `options.count` probes against `address`; the count must be above 0
+pub fn ping_with(address: &str, options: &PingOptions) -> Result<PingResult, PingError> {
+    let target = resolve(address)?;
+    run_counted(Pinger::new(target, options.timeout), options).map_err(PingError::from)
+}
+
+/// What a continuous ping sends over its channel
+#[derive(Debug, Clone, PartialEq)]
+pub enum PingEvent {
+    /// The host resolved and the first probe is about to go out
+    Started { address: IpAddr, method: PingMethod },
+    /// A probe was answered or timed out; the method changes if UDP fell back to TCP
+    Reply(PingReply, PingMethod),
+    /// Probing ended early with this error
+    Failed(String),
+}
+
+/// Pings `address` every `options.interval`, sending each reply to `events` as it arrives
+///
+/// Runs `options.count` probes, or until `stop` is set when the count is 0. It also ends
+/// once the receiver is dropped.
+pub fn ping_continuous(
+    address: &str,
+    options: &PingOptions,
+    stop: &AtomicBool,
+    events: &Sender<PingEvent>,
+) {
+    let result = resolve(address).and_then(|target| {
+        let mut pinger = Pinger::new(target, options.timeout);
+        let _ = events.send(PingEvent::Started {
+            address: target,
+            method: pinger.method(),
+        });
+        run_probes(&mut pinger, options, stop, |reply, method| {
+            events.send(PingEvent::Reply(reply, method)).is_ok()
+        })
+        .map_err(PingError::from)
+    });
+    if let Err(e) = result {
+        let _ = events.send(PingEvent::Failed(e.to_string()));
+    }
+}
+
+/// Sends four probes one second apart, like `ping -c 4`
+pub fn ping(address: &str) -> Result<PingResult, PingError> {
+    ping_with(address, &PingOptions::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback_answers_every_method() {
+        let options = PingOptions {
+            count: 3,
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_secs(1),
+        };
+        let result = ping_with("127.0.0.1", &options).expect("Ping failed");
+        assert_eq!(result.address, "127.0.0.1");
+        assert_eq!(result.packets_transmitted, 3);
+        assert_eq!(result.packets_received, 3);
+        assert_eq!(result.packet_loss, 0.0);
+        assert!(result.round_trip_min <= result.round_trip_avg);
+        assert!(result.round_trip_avg <= result.round_trip_max);
+
+        // The fallbacks work whether or not ICMP sockets are allowed here
+        let loopback = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        for method in [PingMethod::Udp, PingMethod::Tcp(TCP_PROBE_PORT)] {
+            let mut pinger = Pinger::with_method(loopback, method, options.timeout).unwrap();
+            assert!(
+                pinger.probe(1).unwrap().is_some(),
+                "{} got no reply",
+                method
+            );
+        }
+        assert!(resolve("no-such-host.invalid").is_err());
+    }
+
+    #[test]
+    fn test_continuous_ping_streams_until_stopped() {
+        let options = PingOptions {
+            count: 0,
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_secs(1),
+        };
+        let stop = std::sync::Arc::new(AtomicBool::new(false));
+        let (tx, rx) = std::sync::mpsc::channel();
+        let worker = {
+            let stop = stop.clone();
+            thread::spawn(move || ping_continuous("localhost", &options, &stop, &tx))
+        };
+
+        let mut result = match rx.recv().unwrap() {
+            PingEvent::Started { address, method } => PingResult::new(address, method),
+            other => panic!("Unexpected first event {:?}", other),
+        };
+        for expected in 1..=3 {
+            match rx.recv().unwrap() {
+                PingEvent::Reply(reply, method) => {
+                    assert_eq!(reply.sequence, expected);
+                    result.record(reply, method, Duration::from_millis(30));
+                }
+                other => panic!("Unexpected event {:?}", other),
+            }
+        }
+        assert_eq!(result.packets_received, 3);
+
+        stop.store(true, Ordering::Relaxed);
+        worker.join().unwrap();
+    }
+
+    #[test]
+    fn test_statistics_and_packets() {
+        let replies = [Some(1.0), None, Some(3.0), None]
+            .iter()
+            .zip(1..)
+            .map(|(&rtt_ms, sequence)| PingReply { sequence, rtt_ms })
+            .collect();
+        let result = PingResult::from_replies(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            PingMethod::Icmp,
+            replies,
+            Duration::from_millis(3000),
+        );
+        assert_eq!(
+            (result.packets_transmitted, result.packets_received),
+            (4, 2)
+        );
+        assert_eq!(result.packet_loss, 50.0);
+        assert_eq!(result.round_trip_min, 1.0);
+        assert_eq!(result.round_trip_avg, 2.0);
+        assert_eq!(result.round_trip_max, 3.0);
+        assert_eq!(result.round_trip_mdev, 1.0);
+        assert_eq!(result.jitter, 2.0);
+
+        let request = echo_request(false, 0x1234, 7);
+        // A correct checksum makes the checksum over the whole message zero
+        assert_eq!(checksum(&request), 0);
+        let mut reply = request.clone();
+        reply[0] = 0;
+        assert_eq!(parse_echo_reply(&reply, false), Some((0x1234, 7)));
+        assert_eq!(parse_echo_reply(&request, false), None);
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::ping::{run_counted, PingError, PingMethod, PingOptions, PingResult, Pinger};
+
+/// How long the port checker waits for a connection before calling the port filtered
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Most connections the port checker has open at once
+const CHECKS_AT_ONCE: usize = 16;
+
+/// Splits `host:port` (or `[v6 address]:port`) into its host and port
+pub fn parse_target(target: &str) -> Option<(&str, u16)> {
+    let (host, port) = target.trim().rsplit_once(':')?;
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.strip_suffix(']')?,
+        // An IPv6 address without brackets cannot be told apart from its port
+        None if host.contains(':') => return None,
+        None => host,
+    };
+    let port = port.parse::<u16>().ok().filter(|port| *port > 0)?;
+    (!host.is_empty()).then_some((host, port))
+}
+
+/// Resolves `host:port`, preferring the first address returned
+fn resolve_target(target: &str) -> Result<SocketAddr, PingError> {
+    let (host, port) =
+        parse_target(target).ok_or_else(|| PingError::InvalidTarget(target.trim().to_string()))?;
+    (host, port)
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| PingError::Resolve(host.to_string()))
+}
+
+/// Times `options.count` TCP connects to `host:port`, for hosts that block ICMP
+///
+/// A refused connection still comes back from the host, so it counts as a reply.
+pub fn tcp_ping(target: &str, options: &PingOptions) -> Result<PingResult, PingError> {
+    let address = resolve_target(target)?;
+    let pinger = Pinger::with_method(
+        address.ip(),
+        PingMethod::Tcp(address.port()),
+        options.timeout,
+    )?;
+    run_counted(pinger, options).map_err(PingError::from)
+}
+
+/// What a connect to a port found
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortState {
+    /// The connection was accepted
+    Open,
+    /// The host refused the connection
+    Closed,
+    /// No answer before the timeout, or the host was unreachable
+    Filtered,
+    /// Not `host:port`, or the host did not resolve
+    Invalid,
+}
+
+/// Result of checking one `host:port`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PortCheck {
+    /// The target as it was entered
+    pub target: String,
+    /// Address that was connected to
+    pub address: Option<String>,
+    pub state: PortState,
+    /// Time until the connection was accepted or refused
+    pub connect_ms: Option<f32>,
+    /// Why the port is filtered or the target invalid
+    pub detail: Option<String>,
+}
+
+/// Connects to `target` once and reports whether the port is open, closed or filtered
+pub fn check_port(target: &str, timeout: Duration) -> PortCheck {
+    let mut check = PortCheck {
+        target: target.trim().to_string(),
+        address: None,
+        state: PortState::Invalid,
+        connect_ms: None,
+        detail: None,
+    };
+    let address = match resolve_target(target) {
+        Ok(address) => address,
+        Err(e) => {
+            check.detail = Some(e.to_string());
+            return check;
+        }
+    };
+    check.address = Some(address.to_string());
+
+    let start = Instant::now();
+    let result = TcpStream::connect_timeout(&address, timeout);
+    let elapsed = start.elapsed().as_secs_f32() * 1000.0;
+    match result {
+        Ok(_) => {
+            check.state = PortState::Open;
+            check.connect_ms = Some(elapsed);
+        }
+        Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+            check.state = PortState::Closed;
+            check.connect_ms = Some(elapsed);
+        }
+        Err(e) => {
+            check.state = PortState::Filtered;
+            check.detail = Some(e.to_string());
+        }
+    }
+    check
+}
+
+/// Splits a list of targets separated by commas or whitespace
+pub fn parse_targets(input: &str) -> Vec<&str> {
+    input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|target| !target.is_empty())
+        .collect()
+}
+
+/// Checks the targets in parallel, `CHECKS_AT_ONCE` at a time, so each batch takes at
+/// most about one timeout however long the list is
+///
+/// Results are in the order of `targets`.
+pub fn check_ports(targets: &[&str], timeout: Duration) -> Vec<PortCheck> {
+    let mut checks = Vec::with_capacity(targets.len());
+    for batch in targets.chunks(CHECKS_AT_ONCE) {
+        thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|target| scope.spawn(move || check_port(target, timeout)))
+                .collect();
+            checks.extend(handles.into_iter().zip(batch).map(|(handle, target)| {
+                handle.join().unwrap_or_else(|_| PortCheck {
+                    target: target.to_string(),
+                    address: None,
+                    state: PortState::Invalid,
+                    connect_ms: None,
+                    detail: Some("check failed".to_string()),
+                })
+            }));
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn test_targets_are_parsed() {
+        assert_eq!(parse_target("example.com:443"), Some(("example.com", 443)));
+        assert_eq!(parse_target(" [::1]:22 "), Some(("::1", 22)));
+        assert_eq!(parse_target("::1:22"), None);
+        assert_eq!(parse_target("example.com"), None);
+        assert_eq!(parse_target("example.com:0"), None);
+        assert_eq!(parse_target(":80"), None);
+        assert_eq!(parse_targets("a:1, b:2\nc:3,,"), vec!["a:1", "b:2", "c:3"]);
+    }
+
+    #[test]
+    fn test_loopback_ports_are_open_or_closed() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let open = listener.local_addr().unwrap().to_string();
+        // A port that was just released has nothing listening on it
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+
+        let checks = check_ports(&[&open, &closed, "nonsense"], CONNECT_TIMEOUT);
+        assert_eq!(checks[0].state, PortState::Open);
+        assert!(checks[0].connect_ms.is_some());
+        assert_eq!(checks[1].state, PortState::Closed);
+        assert_eq!(checks[2].state, PortState::Invalid);
+
+        // Long lists are checked in batches and still come back in order
+        let mut targets = vec!["nonsense"; CHECKS_AT_ONCE];
+        targets.push(&open);
+        let checks = check_ports(&targets, CONNECT_TIMEOUT);
+        assert_eq!(checks.len(), CHECKS_AT_ONCE + 1);
+        assert_eq!(checks[CHECKS_AT_ONCE].state, PortState::Open);
+
+        let options = PingOptions {
+            count: 2,
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_secs(1),
+        };
+        let result = tcp_ping(&open, &options).expect("TCP ping failed");
+        assert_eq!(result.packets_received, 2);
+        assert_eq!(
+            result.method,
+            PingMethod::Tcp(listener.local_addr().unwrap().port())
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::CStr;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use super::ping::{resolve, PingError};
+
+/// First destination port of the probes, as in traceroute; each probe uses the next one
+const BASE_PORT: u16 = 33434;
+/// Longest host name getnameinfo returns
+const MAX_HOST_NAME: usize = 1025;
+
+/// Settings of a traceroute run
+#[derive(Debug, Clone)]
+pub struct TraceOptions {
+    pub max_hops: u8,
+    pub probes_per_hop: u8,
+    /// How long to wait for the answers of one hop
+    pub timeout: Duration,
+    /// Look up the host name of every router
+    pub resolve_names: bool,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        Self {
+            max_hops: 30,
+            probes_per_hop: 3,
+            timeout: Duration::from_secs(2),
+            resolve_names: true,
+        }
+    }
+}
+
+/// Who answered the probes of one hop
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HopStatus {
+    /// A router on the way, whose time-to-live ran out
+    Router,
+    /// The destination itself
+    Destination,
+    /// A router reported the destination unreachable
+    Unreachable,
+    /// Nothing answered in time
+    NoReply,
+}
+
+/// One hop of a trace, with the round trip of each of its probes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hop {
+    pub ttl: u8,
+    /// First address that answered
+    pub address: Option<String>,
+    /// Reverse DNS name of the address
+    pub hostname: Option<String>,
+    /// `None` for probes that went unanswered
+    pub rtts_ms: Vec<Option<f32>>,
+    pub status: HopStatus,
+}
+
+impl Hop {
+    /// Share of the probes that went unanswered, in percent
+    pub fn loss(&self) -> f32 {
+        if self.rtts_ms.is_empty() {
+            return 0.0;
+        }
+        let lost = self.rtts_ms.iter().filter(|rtt| rtt.is_none()).count();
+        100.0 * lost as f32 / self.rtts_ms.len() as f32
+    }
+
+    pub fn average_ms(&self) -> Option<f32> {
+        let rtts: Vec<f32> = self.rtts_ms.iter().flatten().copied().collect();
+        (!rtts.is_empty()).then(|| rtts.iter().sum::<f32>() / rtts.len() as f32)
+    }
+}
+
+/// What a traceroute sends over its channel
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent {
+    /// The host resolved and the first hop is being probed
+    Started {
+        address: IpAddr,
+        max_hops: u8,
+    },
+    Hop(Hop),
+    /// Tracing ended early with this error
+    Failed(String),
+}
+
+/// The answer to one probe
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Answer {
+    from: IpAddr,
+    rtt: Duration,
+    status: HopStatus,
+}
+
+/// Host name of `address` from the system resolver, if it has one
+pub fn reverse_lookup(address: IpAddr) -> Option<String> {
+    let socket_address: socket2::SockAddr = SocketAddr::new(address, 0).into();
+    let mut host = [0 as libc::c_char; MAX_HOST_NAME];
+    // SAFETY: the address and buffer pointers are valid for the lengths passed along
+    let result = unsafe {
+        libc::getnameinfo(
+            socket_address.as_ptr(),
+            socket_address.len(),
+            host.as_mut_ptr(),
+            host.len() as libc::socklen_t,
+            std::ptr::null_mut(),
+            0,
+            libc::NI_NAMEREQD,
+        )
+    };
+    if result != 0 {
+        return None;
+    }
+    // SAFETY: getnameinfo wrote a NUL-terminated name into the buffer
+    let name = unsafe { CStr::from_ptr(host.as_ptr()) };
+    name.to_str().ok().map(str::to_string)
+}
+
+/// Sends UDP probes with the time-to-live set to `ttl` and collects the ICMP errors the
+/// kernel queues for them (`IP_RECVERR`), which needs no privileges
+#[cfg(target_os = "linux")]
+fn probe_hop(
+    target: IpAddr,
+    ttl: u8,
+    first_port: u16,
+    options: &TraceOptions,
+) -> io::Result<Vec<Option<Answer>>> {
+    use socket2::{Domain, Protocol, Socket, Type};
+    use std::net::{Ipv4Addr, Ipv6Addr, UdpSocket};
+    use std::os::unix::io::AsRawFd;
+    use std::time::Instant;
+
+    let mut probes = Vec::new();
+    for index in 0..options.probes_per_hop {
+        let (domain, local, level, option) = match target {
+            IpAddr::V4(_) => (
+                Domain::IPV4,
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                libc::SOL_IP,
+                libc::IP_RECVERR,
+            ),
+            IpAddr::V6(_) => (
+                Domain::IPV6,
+                IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                libc::SOL_IPV6,
+                libc::IPV6_RECVERR,
+            ),
+        };
+        let socket = Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?;
+        match target {
+            IpAddr::V4(_) => socket.set_ttl(u32::from(ttl))?,
+            IpAddr::V6(_) => socket.set_unicast_hops_v6(u32::from(ttl))?,
+        }
+        let enable: libc::c_int = 1;
+        // SAFETY: the option value is a c_int that outlives the call
+        let result = unsafe {
+            libc::setsockopt(
+                socket.as_raw_fd(),
+                level,
+                option,
+                &enable as *const libc::c_int as *const libc::c_void,
+                std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        socket.set_nonblocking(true)?;
+        socket.bind(&SocketAddr::new(local, 0).into())?;
+        let port = first_port.wrapping_add(u16::from(index));
+        socket.connect(&SocketAddr::new(target, port).into())?;
+
+        let socket: UdpSocket = socket.into();
+        let sent_at = Instant::now();
+        socket.send(b"toolbox traceroute")?;
+        probes.push((socket, sent_at));
+    }
+
+    let mut answers = vec![None; probes.len()];
+    let mut poll_fds: Vec<libc::pollfd> = probes
+        .iter()
+        .map(|(socket, _)| libc::pollfd {
+            fd: socket.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+    let deadline = Instant::now() + options.timeout;
+    while answers.iter().any(Option::is_none) {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        // SAFETY: poll_fds is a valid array of pollfd of the length passed
+        let ready = unsafe {
+            libc::poll(
+                poll_fds.as_mut_ptr(),
+                poll_fds.len() as libc::nfds_t,
+                remaining.as_millis().clamp(1, i32::MAX as u128) as libc::c_int,
+            )
+        };
+        if ready < 0 {
+            let e = io::Error::last_os_error();
+            if e.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(e);
+        }
+
+        for (index, poll_fd) in poll_fds.iter_mut().enumerate() {
+            if poll_fd.revents == 0 {
+                continue;
+            }
+            poll_fd.revents = 0;
+            let (socket, sent_at) = &probes[index];
+            let rtt = sent_at.elapsed();
+            let answer = match read_queued_error(socket.as_raw_fd())? {
+                Some((from, time_exceeded)) => Some(Answer {
+                    from,
+                    rtt,
+                    status: if from == target {
+                        HopStatus::Destination
+                    } else if time_exceeded {
+                        HopStatus::Router
+                    } else {
+                        HopStatus::Unreachable
+                    },
+                }),
+                // A port that is open and answers also means the destination was reached
+                None => match socket.recv(&mut [0u8; 512]) {
+                    Ok(_) => Some(Answer {
+                        from: target,
+                        rtt,
+                        status: HopStatus::Destination,
+                    }),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => None,
+                    Err(e) => return Err(e),
+                },
+            };
+            if answer.is_some() {
+                answers[index] = answer;
+                // Negative descriptors are ignored by poll
+                poll_fd.fd = -1;
+            }
+        }
+    }
+    Ok(answers)
+}
+
+/// Reads one ICMP error from the socket's error queue: the address that sent it and
+/// whether it was "time exceeded"
+#[cfg(target_os = "linux")]
+fn read_queued_error(fd: libc::c_int) -> io::Result<Option<(IpAddr, bool)>> {
+    use std::mem::{size_of_val, zeroed};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let mut data = [0u8; 512];
+    // u64 keeps the control buffer aligned for the cmsghdr structures
+    let mut control = [0u64; 64];
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr() as *mut libc::c_void,
+        iov_len: data.len(),
+    };
+    // SAFETY: msghdr is plain data; the pointers set below stay valid during recvmsg
+    let mut message: libc::msghdr = unsafe { zeroed() };
+    message.msg_iov = &mut iov;
+    message.msg_iovlen = 1;
+    message.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    message.msg_controllen = size_of_val(&control) as _;
+
+    // SAFETY: message points at buffers owned by this function
+    let received =
+        unsafe { libc::recvmsg(fd, &mut message, libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT) };
+    if received < 0 {
+        let e = io::Error::last_os_error();
+        return match e.kind() {
+            io::ErrorKind::WouldBlock => Ok(None),
+            _ => Err(e),
+        };
+    }
+
+    // SAFETY: the control messages were filled in by recvmsg and are walked with the
+    // CMSG macros, which stay within msg_controllen
+    unsafe {
+        let mut header = libc::CMSG_FIRSTHDR(&message);
+        while !header.is_null() {
+            let level = (*header).cmsg_level;
+            let kind = (*header).cmsg_type;
+            if (level == libc::SOL_IP && kind == libc::IP_RECVERR)
+                || (level == libc::SOL_IPV6 && kind == libc::IPV6_RECVERR)
+            {
+                let error_ptr = libc::CMSG_DATA(header) as *const libc::sock_extended_err;
+                let error = std::ptr::read_unaligned(error_ptr);
+                let offender = libc::SO_EE_OFFENDER(error_ptr) as *const libc::sockaddr;
+                let from = match (error.ee_origin, (*offender).sa_family as libc::c_int) {
+                    (libc::SO_EE_ORIGIN_ICMP, libc::AF_INET) => {
+                        let address =
+                            std::ptr::read_unaligned(offender as *const libc::sockaddr_in);
+                        IpAddr::V4(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr)))
+                    }
+                    (libc::SO_EE_ORIGIN_ICMP6, libc::AF_INET6) => {
+                        let address =
+                            std::ptr::read_unaligned(offender as *const libc::sockaddr_in6);
+                        IpAddr::V6(Ipv6Addr::from(address.sin6_addr.s6_addr))
+                    }
+                    // Local errors, such as a full send buffer, say nothing about the path
+                    _ => return Ok(None),
+                };
+                let time_exceeded = match from {
+                    IpAddr::V4(_) => error.ee_type == 11,
+                    IpAddr::V6(_) => error.ee_type == 3,
+                };
+                return Ok(Some((from, time_exceeded)));
+            }
+            header = libc::CMSG_NXTHDR(&message, header);
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_hop(
+    _target: IpAddr,
+    _ttl: u8,
+    _first_port: u16,
+    _options: &TraceOptions,
+) -> io::Result<Vec<Option<Answer>>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "traceroute reads ICMP errors through IP_RECVERR, which only Linux has",
+    ))
+}
+
+/// Traces the path to `address`, sending each hop to `events` as soon as it is probed
+///
+/// Ends at the destination, when a router reports it unreachable, after
+/// `options.max_hops`, once `stop` is set or when the receiver is dropped.
+pub fn traceroute(
+    address: &str,
+    options: &TraceOptions,
+    stop: &AtomicBool,
+    events: &Sender<TraceEvent>,
+) {
+    let result = resolve(address).and_then(|target| {
+        let _ = events.send(TraceEvent::Started {
+            address: target,
+            max_hops: options.max_hops,
+        });
+        for ttl in 1..=options.max_hops {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            let first_port =
+                BASE_PORT.wrapping_add(u16::from(ttl - 1) * u16::from(options.probes_per_hop));
+            let answers = probe_hop(target, ttl, first_port, options)?;
+
+            let first = answers.iter().flatten().next();
+            let address = first.map(|answer| answer.from);
+            let status = answers
+                .iter()
+                .flatten()
+                .map(|answer| answer.status)
+                .find(|status| *status != HopStatus::Router)
+                .or(first.map(|answer| answer.status))
+                .unwrap_or(HopStatus::NoReply);
+            let hop = Hop {
+                ttl,
+                address: address.map(|address| address.to_string()),
+                hostname: address
+                    .filter(|_| options.resolve_names)
+                    .and_then(reverse_lookup),
+                rtts_ms: answers
+                    .iter()
+                    .map(|answer| answer.map(|answer| answer.rtt.as_secs_f32() * 1000.0))
+                    .collect(),
+                status,
+            };
+
+            let finished = matches!(status, HopStatus::Destination | HopStatus::Unreachable);
+            if events.send(TraceEvent::Hop(hop)).is_err() || finished {
+                break;
+            }
+        }
+        Ok::<(), PingError>(())
+    });
+    if let Err(e) = result {
+        let _ = events.send(TraceEvent::Failed(e.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_loopback_is_one_hop() {
+        let options = TraceOptions {
+            timeout: Duration::from_secs(1),
+            resolve_names: false,
+            ..TraceOptions::default()
+        };
+        let (tx, rx) = mpsc::channel();
+        traceroute("127.0.0.1", &options, &AtomicBool::new(false), &tx);
+        drop(tx);
+
+        let events: Vec<TraceEvent> = rx.iter().collect();
+        assert_eq!(
+            events[0],
+            TraceEvent::Started {
+                address: "127.0.0.1".parse().unwrap(),
+                max_hops: 30
+            }
+        );
+        let TraceEvent::Hop(ref hop) = events[1] else {
+            panic!("Expected a hop, got {:?}", events[1]);
+        };
+        assert_eq!(events.len(), 2);
+        assert_eq!(hop.ttl, 1);
+        assert_eq!(hop.address.as_deref(), Some("127.0.0.1"));
+        assert_eq!(hop.status, HopStatus::Destination);
+        assert_eq!(hop.rtts_ms.len(), 3);
+        assert_eq!(hop.loss(), 0.0);
+        assert!(hop.average_ms().is_some());
+    }
+}
@@ -0,0 +1,333 @@
+use data_encoding::HEXUPPER;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::Path;
+
+use super::strength::{estimate, Strength};
+use super::PasswordEntry;
+
+/// Strength scores below this are reported as weak
+pub const WEAK_SCORE: u8 = 3;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Audit result for one vault entry
+#[derive(Debug, Clone)]
+pub struct EntryAudit {
+    /// Position of the entry in the vault
+    pub index: usize,
+    pub service: String,
+    pub username: String,
+    pub strength: Strength,
+    /// Other entries (by index) that use the same password
+    pub reused_with: Vec<usize>,
+    /// Days since the password was last changed, if known
+    pub age_days: Option<i64>,
+    /// Older than the configured rotation period
+    pub stale: bool,
+    /// Times the password appears in the HIBP file (`None` if no file was checked)
+    pub breach_count: Option<u64>,
+}
+
+impl EntryAudit {
+    pub fn is_weak(&self) -> bool {
+        self.strength.score < WEAK_SCORE
+    }
+
+    /// True if anything about this entry needs attention
+    pub fn has_issues(&self) -> bool {
+        self.is_weak()
+            || !self.reused_with.is_empty()
+            || self.stale
+            || self.breach_count.unwrap_or(0) > 0
+    }
+}
+
+/// Findings for the whole vault, worst entries first
+#[derive(Debug, Clone)]
+pub struct AuditReport {
+    pub entries: Vec<EntryAudit>,
+    /// Why the HIBP file could not be checked, if one is configured
+    pub hibp_error: Option<String>,
+}
+
+impl AuditReport {
+    pub fn weak(&self) -> usize {
+        self.entries.iter().filter(|e| e.is_weak()).count()
+    }
+
+    pub fn reused(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| !e.reused_with.is_empty())
+            .count()
+    }
+
+    pub fn stale(&self) -> usize {
+        self.entries.iter().filter(|e| e.stale).count()
+    }
+
+    pub fn breached(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.breach_count.unwrap_or(0) > 0)
+            .count()
+    }
+}
+
+/// Scores every entry and flags reuse, stale passwords and known breaches
+///
+/// `rotation_days` of 0 disables the staleness check. `hibp_path` is an optional local
+/// copy of the Have I Been Pwned password list; nothing is sent over the network.
+/// Entries without a password (secure notes, SSH keys without a passphrase) are left out.
+pub fn audit(
+    entries: &[PasswordEntry],
+    rotation_days: u32,
+    now: i64,
+    hibp_path: Option<&str>,
+) -> AuditReport {
+    let audited: Vec<(usize, &PasswordEntry)> = entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| !entry.password.is_empty())
+        .collect();
+
+    let mut by_password: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, entry) in &audited {
+        by_password.entry(&entry.password).or_default().push(*index);
+    }
+
+    let (breaches, hibp_error) = match hibp_path {
+        Some(path) => {
+            let passwords: Vec<&str> = by_password.keys().copied().collect();
+            match hibp_lookup(path, &passwords) {
+                Ok(counts) => (Some(counts), None),
+                Err(e) => (None, Some(format!("{}: {}", path, e))),
+            }
+        }
+        None => (None, None),
+    };
+
+    let mut audits: Vec<EntryAudit> = audited
+        .into_iter()
+        .map(|(index, entry)| {
+            let age_days = entry
+                .password_changed_at
+                .map(|changed| (now - changed).max(0) / SECONDS_PER_DAY);
+
+            EntryAudit {
+                index,
+                service: entry.service.clone(),
+                username: entry.username.clone(),
+                strength: estimate(&entry.password),
+                reused_with: by_password[entry.password.as_str()]
+                    .iter()
+                    .copied()
+                    .filter(|&other| other != index)
+                    .collect(),
+                age_days,
+                stale: rotation_days > 0
+                    && age_days.is_some_and(|days| days >= i64::from(rotation_days)),
+                breach_count: breaches
+                    .as_ref()
+                    .map(|counts| counts.get(&sha1_hex(&entry.password)).copied().unwrap_or(0)),
+            }
+        })
+        .collect();
+
+    audits.sort_by_key(|audit| {
+        (
+            audit.breach_count.unwrap_or(0) == 0,
+            audit.strength.score,
+            audit.reused_with.is_empty(),
+            !audit.stale,
+        )
+    });
+
+    AuditReport {
+        entries: audits,
+        hibp_error,
+    }
+}
+
+/// Uppercase hex SHA-1, the form used by Have I Been Pwned
+pub fn sha1_hex(password: &str) -> String {
+    HEXUPPER.encode(&Sha1::digest(password.as_bytes()))
+}
+
+/// Looks passwords up in a local Have I Been Pwned download, returning breach counts by hash
+///
+/// `path` is either the single file ordered by hash (`HASH:COUNT` lines, searched with
+/// a binary search) or a directory of range files named `<5-char prefix>.txt` holding
+/// `SUFFIX:COUNT` lines, as returned by the range API.
+pub fn hibp_lookup(path: &str, passwords: &[&str]) -> io::Result<HashMap<String, u64>> {
+    let mut counts = HashMap::new();
+    let is_dir = Path::new(path).is_dir();
+    let mut sorted_file = if is_dir {
+        None
+    } else {
+        Some(File::open(path)?)
+    };
+
+    for password in passwords {
+        let hash = sha1_hex(password);
+        let count = match sorted_file {
+            Some(ref mut file) => search_sorted_file(file, &hash)?,
+            None => search_range_file(path, &hash)?,
+        };
+        if let Some(count) = count {
+            counts.insert(hash, count);
+        }
+    }
+    Ok(counts)
+}
+
+fn parse_line(line: &str) -> Option<(&str, u64)> {
+    let (hash, count) = line.trim().split_once(':')?;
+    Some((hash, count.trim().parse().ok()?))
+}
+
+fn search_range_file(dir: &str, hash: &str) -> io::Result<Option<u64>> {
+    let (prefix, suffix) = hash.split_at(5);
+    let contents = match fs::read_to_string(Path::new(dir).join(format!("{}.txt", prefix))) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    Ok(contents
+        .lines()
+        .filter_map(parse_line)
+        .find(|(line_suffix, _)| line_suffix.eq_ignore_ascii_case(suffix))
+        .map(|(_, count)| count))
+}
+
+/// Binary search over byte offsets of a file whose lines are sorted by hash
+fn search_sorted_file(file: &mut File, hash: &str) -> io::Result<Option<u64>> {
+    let (mut low, mut high) = (0, file.metadata()?.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match first_line_from(file, mid)? {
+            Some(line) if line.trim().to_uppercase().as_str() < hash => low = mid + 1,
+            _ => high = mid,
+        }
+    }
+
+    Ok(first_line_from(file, low)?
+        .as_deref()
+        .and_then(parse_line)
+        .filter(|(line_hash, _)| line_hash.eq_ignore_ascii_case(hash))
+        .map(|(_, count)| count))
+}
+
+/// The first complete line that starts at or after `offset`
+fn first_line_from(file: &mut File, offset: u64) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(&mut *file);
+    let mut line = String::new();
+    if offset > 0 {
+        // Land on `offset - 1` so a line starting exactly at `offset` is not skipped
+        reader.seek(SeekFrom::Start(offset - 1))?;
+        reader.read_line(&mut line)?;
+        line.clear();
+    } else {
+        reader.seek(SeekFrom::Start(0))?;
+    }
+
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::EntryKind;
+    use super::*;
+
+    fn entry(service: &str, password: &str, changed_days_ago: Option<i64>) -> PasswordEntry {
+        PasswordEntry {
+            service: service.to_string(),
+            username: "alice".to_string(),
+            password: password.into(),
+            password_changed_at: changed_days_ago
+                .map(|days| 1_000 * SECONDS_PER_DAY - days * SECONDS_PER_DAY),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_audit_flags_reuse_age_and_breaches() {
+        let dir = std::env::temp_dir().join(format!("toolbox-hibp-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // Sorted single-file download with "password" in it
+        let password_hash = sha1_hex("password");
+        let mut lines = [
+            format!("{}:3", "0".repeat(40)),
+            format!("{}:9545824", password_hash),
+            format!("{}:1", "F".repeat(40)),
+        ];
+        lines.sort();
+        let sorted_path = dir.join("pwned.txt");
+        fs::write(&sorted_path, lines.join("\r\n") + "\r\n").unwrap();
+
+        let entries = vec![
+            entry("a.com", "password", Some(10)),
+            entry("b.com", "password", None),
+            entry("c.com", "vX7#qLp2!mRw9zTk", Some(200)),
+        ];
+        let report = audit(&entries, 90, 1_000 * SECONDS_PER_DAY, sorted_path.to_str());
+
+        assert!(report.hibp_error.is_none());
+        assert_eq!(report.weak(), 2);
+        assert_eq!(report.reused(), 2);
+        assert_eq!(report.stale(), 1);
+        assert_eq!(report.breached(), 2);
+
+        let strong = report.entries.iter().find(|e| e.index == 2).unwrap();
+        assert_eq!(strong.age_days, Some(200));
+        assert!(strong.stale && !strong.is_weak());
+        assert_eq!(strong.breach_count, Some(0));
+        assert_eq!(report.entries[0].breach_count, Some(9545824));
+
+        // Range-file directory layout
+        let (prefix, suffix) = password_hash.split_at(5);
+        fs::write(
+            dir.join(format!("{}.txt", prefix)),
+            format!("{}:42\n", suffix),
+        )
+        .unwrap();
+        let counts = hibp_lookup(dir.to_str().unwrap(), &["password", "other"]).unwrap();
+        assert_eq!(counts.get(&password_hash), Some(&42));
+        assert_eq!(counts.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_audit_skips_entries_without_a_password() {
+        let entries = vec![
+            PasswordEntry {
+                kind: EntryKind::SecureNote,
+                service: "Recovery codes".to_string(),
+                notes: "1111-2222".into(),
+                ..Default::default()
+            },
+            PasswordEntry {
+                kind: EntryKind::SshKey,
+                service: "deploy key".to_string(),
+                ..Default::default()
+            },
+            entry("a.com", "vX7#qLp2!mRw9zTk", None),
+        ];
+        let report = audit(&entries, 90, 1_000 * SECONDS_PER_DAY, None);
+
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].index, 2);
+        assert_eq!(report.weak(), 0);
+        assert_eq!(report.reused(), 0);
+    }
+}
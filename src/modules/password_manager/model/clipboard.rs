@@ -0,0 +1,169 @@
+use data_encoding::BASE64;
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use zeroize::Zeroizing;
+
+use crate::core::SecretString;
+
+/// Local clipboard programs used next to OSC 52
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipboardTool {
+    WlCopy,
+    Xclip,
+}
+
+impl ClipboardTool {
+    pub fn name(self) -> &'static str {
+        match self {
+            ClipboardTool::WlCopy => "wl-copy",
+            ClipboardTool::Xclip => "xclip",
+        }
+    }
+
+    /// The tool for the current graphical session, if one is installed
+    ///
+    /// Over SSH the local display belongs to the remote machine, so only OSC 52 is used.
+    pub fn detect() -> Option<Self> {
+        if env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some() {
+            return None;
+        }
+        if env::var_os("WAYLAND_DISPLAY").is_some() && in_path("wl-copy") {
+            Some(ClipboardTool::WlCopy)
+        } else if env::var_os("DISPLAY").is_some() && in_path("xclip") {
+            Some(ClipboardTool::Xclip)
+        } else {
+            None
+        }
+    }
+
+    fn write(self, text: &str) -> io::Result<()> {
+        let mut command = match self {
+            ClipboardTool::WlCopy => Command::new("wl-copy"),
+            ClipboardTool::Xclip => {
+                let mut command = Command::new("xclip");
+                command.args(["-selection", "clipboard"]);
+                command
+            }
+        };
+        // Keep the tools' output off the TUI
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("clipboard tool has no stdin"))?
+            .write_all(text.as_bytes())?;
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "{} failed: {}",
+                self.name(),
+                status
+            )));
+        }
+        Ok(())
+    }
+
+    fn read(self) -> io::Result<SecretString> {
+        let output = match self {
+            ClipboardTool::WlCopy => Command::new("wl-paste").arg("--no-newline").output()?,
+            ClipboardTool::Xclip => Command::new("xclip")
+                .args(["-selection", "clipboard", "-o"])
+                .output()?,
+        };
+        let contents = Zeroizing::new(output.stdout);
+        Ok(String::from_utf8_lossy(&contents).into_owned().into())
+    }
+}
+
+fn in_path(program: &str) -> bool {
+    env::var_os("PATH").is_some_and(|paths| {
+        env::split_paths(&paths).any(|dir| Path::new(&dir).join(program).is_file())
+    })
+}
+
+/// Escape sequence asking the terminal to put `text` on the system clipboard
+///
+/// tmux only forwards it to the outer terminal when wrapped in a DCS passthrough
+/// (and `allow-passthrough` is on).
+pub fn osc52_sequence(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", BASE64.encode(text.as_bytes()));
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+/// A value placed on the clipboard, kept until it is cleared again
+#[derive(Debug)]
+pub struct ClipboardCopy {
+    contents: SecretString,
+    tool: Option<ClipboardTool>,
+}
+
+impl ClipboardCopy {
+    /// Copies `text` with OSC 52 written to `terminal`, and also with the local
+    /// clipboard tool when there is one
+    ///
+    /// Terminals give no answer to OSC 52, so the local tool is what makes the copy
+    /// work in terminals that ignore it.
+    pub fn copy(terminal: &mut impl Write, text: &str) -> io::Result<Self> {
+        let tmux = env::var_os("TMUX").is_some();
+        terminal.write_all(osc52_sequence(text, tmux).as_bytes())?;
+        terminal.flush()?;
+
+        let tool = ClipboardTool::detect().filter(|tool| tool.write(text).is_ok());
+        Ok(Self {
+            contents: text.into(),
+            tool,
+        })
+    }
+
+    /// How the value was copied, for status messages
+    pub fn method(&self) -> String {
+        match self.tool {
+            Some(tool) => format!("OSC 52 and {}", tool.name()),
+            None => "OSC 52".to_string(),
+        }
+    }
+
+    /// Empties the clipboard again
+    ///
+    /// The local clipboard is left alone if something else has been copied since.
+    pub fn clear(self, terminal: &mut impl Write) -> io::Result<()> {
+        let tmux = env::var_os("TMUX").is_some();
+        terminal.write_all(osc52_sequence("", tmux).as_bytes())?;
+        terminal.flush()?;
+
+        if let Some(tool) = self.tool {
+            if tool.read().is_ok_and(|current| current == self.contents) {
+                tool.write("")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("hunter2", false),
+            "\x1b]52;c;aHVudGVyMg==\x07"
+        );
+        assert_eq!(osc52_sequence("", false), "\x1b]52;c;\x07");
+        assert_eq!(
+            osc52_sequence("hunter2", true),
+            "\x1bPtmux;\x1b\x1b]52;c;aHVudGVyMg==\x07\x1b\\"
+        );
+    }
+}
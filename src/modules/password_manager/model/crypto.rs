@@ -0,0 +1,202 @@
+use aes::Aes256;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose, Engine as _};
+use cbc::Decryptor;
+use cipher::{block_padding::Pkcs7, KeyIvInit};
+use cipher::BlockDecryptMut;
+use dotenv::dotenv;
+use rand::{thread_rng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::io;
+use zeroize::Zeroizing;
+
+/// Length in bytes of every key used to encrypt a vault
+pub const KEY_LEN: usize = 32;
+
+/// Length in bytes of the random salt fed to Argon2id
+const SALT_LEN: usize = 16;
+
+/// Length in bytes of an AES-GCM nonce
+pub const NONCE_LEN: usize = 12;
+
+/// Argon2id settings used to turn the master password into a vault key
+///
+/// Stored in the vault header so that a vault keeps unlocking with the
+/// parameters it was created with, even if the defaults change later.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KdfParams {
+    /// Key derivation function name (always "argon2id")
+    pub algorithm: String,
+    /// Base64-encoded random salt
+    pub salt: String,
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over memory
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Fresh parameters with a random salt and the recommended Argon2id costs
+    pub fn generate() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        thread_rng().fill_bytes(&mut salt);
+
+        Self {
+            algorithm: "argon2id".to_string(),
+            salt: general_purpose::STANDARD.encode(salt),
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Derives the 32-byte vault key from a master password
+///
+/// # Errors
+///
+/// Returns an error if the header names an unknown algorithm, the salt is not
+/// valid base64, or the cost parameters are rejected by Argon2.
+pub fn derive_key(master_password: &str, params: &KdfParams) -> io::Result<Zeroizing<Vec<u8>>> {
+    if params.algorithm != "argon2id" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported key derivation function: {}", params.algorithm),
+        ));
+    }
+
+    let salt = general_purpose::STANDARD.decode(&params.salt).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid salt in vault header: {:?}", e),
+        )
+    })?;
+
+    let argon_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        Some(KEY_LEN),
+    )
+    .map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid Argon2 parameters: {}", e),
+        )
+    })?;
+
+    let mut key = Zeroizing::new(vec![0u8; KEY_LEN]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(master_password.as_bytes(), &salt, &mut key)
+        .map_err(|e| io::Error::other(format!("Key derivation failed: {}", e)))?;
+
+    Ok(key)
+}
+
+/// Encrypts `plaintext` with AES-256-GCM under a fresh random nonce
+///
+/// `aad` is authenticated but not encrypted; the vault passes its header here so that
+/// edits to the KDF parameters are caught just like edits to the ciphertext.
+/// Returns `(nonce, ciphertext || tag)`.
+pub fn seal(plaintext: &[u8], key: &[u8], aad: &[u8]) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Key must be 32 bytes long"))?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    thread_rng().fill_bytes(&mut nonce);
+
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| io::Error::other("Encryption failed"))?;
+
+    Ok((nonce.to_vec(), ciphertext))
+}
+
+/// Decrypts and authenticates the output of [`seal`]
+///
+/// Returns `None` when the tag does not verify, i.e. the ciphertext, nonce or
+/// associated data were altered (or the key is wrong).
+pub fn open(ciphertext: &[u8], nonce: &[u8], key: &[u8], aad: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    if nonce.len() != NONCE_LEN {
+        return None;
+    }
+    let cipher = Aes256Gcm::new_from_slice(key).ok()?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .ok()
+        .map(Zeroizing::new)
+}
+
+/// Fingerprint of the vault key stored in the header
+///
+/// Lets an unlock attempt tell a wrong master password apart from a tampered
+/// vault before the ciphertext is touched. Knowing it does not help an attacker:
+/// they still have to run Argon2id for every guess.
+pub fn key_check(key: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(b"toolbox-vault-key-check");
+    hasher.update(key);
+    general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+/// Decrypts the legacy AES-256-CBC format: base64(IV || ciphertext) with PKCS7 padding
+///
+/// Only used to read vaults written before the authenticated format; CBC has no MAC,
+/// so a successful decrypt here says nothing about integrity.
+pub fn decrypt_cbc(data: &[u8], key: &[u8]) -> io::Result<Zeroizing<Vec<u8>>> {
+    let decoded = general_purpose::STANDARD.decode(data).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Base64 decoding failed: {:?}", e),
+        )
+    })?;
+
+    if decoded.len() < 16 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid data length",
+        ));
+    }
+
+    let (iv, ciphertext) = decoded.split_at(16);
+
+    let cipher = Decryptor::<Aes256>::new_from_slices(key, iv).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cipher creation failed: {:?}", e),
+        )
+    })?;
+
+    let mut decrypted = Zeroizing::new(vec![0u8; ciphertext.len()]);
+    let decrypted_len = cipher
+        .decrypt_padded_b2b_mut::<Pkcs7>(ciphertext, &mut decrypted)
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Decryption failed: {:?}", e),
+            )
+        })?
+        .len();
+
+    decrypted.truncate(decrypted_len);
+
+    Ok(decrypted)
+}
+
+/// Reads the raw 32-character `ENCRYPTION_KEY` that pre-master-password vaults were encrypted with
+///
+/// Only used to migrate those vaults; new vaults never touch `.env`.
+pub fn legacy_env_key() -> Result<Zeroizing<Vec<u8>>, Box<dyn std::error::Error>> {
+    dotenv().ok(); // Load environment variables from .env file
+    let key = env::var("ENCRYPTION_KEY")?;
+    if key.len() != KEY_LEN {
+        return Err("Key must be 32 bytes long".into());
+    }
+    Ok(Zeroizing::new(key.into_bytes()))
+}
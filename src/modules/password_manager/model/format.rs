@@ -0,0 +1,199 @@
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::Path;
+use std::{fs, io};
+use zeroize::Zeroizing;
+
+use super::crypto::{key_check, open, seal, KdfParams};
+use super::export::create_new;
+use super::VaultError;
+
+/// Identifies a toolbox vault file
+pub const VAULT_MAGIC: &str = "TBXVAULT";
+
+/// Current container version; version 1 was the unauthenticated AES-256-CBC layout
+pub const VAULT_VERSION: u32 = 2;
+
+/// AEAD used for the vault body
+pub const VAULT_CIPHER: &str = "aes-256-gcm";
+
+/// Plaintext vault header
+///
+/// Everything needed to re-derive the key from the master password. The whole header
+/// is authenticated as associated data, so it cannot be edited without detection.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VaultHeader {
+    pub magic: String,
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub cipher: String,
+    /// Fingerprint of the derived key, used to report a wrong master password
+    pub key_check: String,
+    /// When the key was derived (Unix seconds); missing in vaults from older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_created_at: Option<i64>,
+}
+
+impl VaultHeader {
+    pub fn new(kdf: KdfParams, key: &[u8]) -> Self {
+        Self {
+            magic: VAULT_MAGIC.to_string(),
+            version: VAULT_VERSION,
+            kdf,
+            cipher: VAULT_CIPHER.to_string(),
+            key_check: key_check(key),
+            key_created_at: Some(Utc::now().timestamp()),
+        }
+    }
+
+    /// Bytes bound to the ciphertext as AES-GCM associated data
+    fn aad(&self) -> Result<Vec<u8>, VaultError> {
+        serde_json::to_vec(self).map_err(|e| VaultError::Malformed(e.to_string()))
+    }
+}
+
+/// Version 2 vault file: header, nonce and authenticated ciphertext
+#[derive(Serialize, Deserialize)]
+pub struct VaultFile {
+    pub header: VaultHeader,
+    /// Base64-encoded 96-bit nonce
+    pub nonce: String,
+    /// Base64-encoded ciphertext with the GCM tag appended
+    pub ciphertext: String,
+}
+
+impl VaultFile {
+    /// Encrypts `plaintext` under `key` with a fresh nonce
+    pub fn seal(header: &VaultHeader, plaintext: &[u8], key: &[u8]) -> Result<Self, VaultError> {
+        let (nonce, ciphertext) = seal(plaintext, key, &header.aad()?)?;
+        Ok(Self {
+            header: header.clone(),
+            nonce: general_purpose::STANDARD.encode(nonce),
+            ciphertext: general_purpose::STANDARD.encode(ciphertext),
+        })
+    }
+
+    /// Checks the key against the header, then decrypts and authenticates the body
+    ///
+    /// # Errors
+    ///
+    /// `WrongPassword` if the key fingerprint does not match, `Tampered` if the
+    /// right key fails to authenticate the header or ciphertext.
+    pub fn open(&self, key: &[u8]) -> Result<Zeroizing<Vec<u8>>, VaultError> {
+        if key_check(key) != self.header.key_check {
+            return Err(VaultError::WrongPassword);
+        }
+
+        let nonce = general_purpose::STANDARD
+            .decode(&self.nonce)
+            .map_err(|_| VaultError::Tampered)?;
+        let ciphertext = general_purpose::STANDARD
+            .decode(&self.ciphertext)
+            .map_err(|_| VaultError::Tampered)?;
+
+        open(&ciphertext, &nonce, key, &self.header.aad()?).ok_or(VaultError::Tampered)
+    }
+}
+
+/// Header of the version 1 layout (Argon2id key, AES-256-CBC body, no MAC)
+#[derive(Deserialize)]
+pub struct LegacyVaultHeader {
+    pub kdf: KdfParams,
+}
+
+/// Version 1 vault file, read only so it can be upgraded
+#[derive(Deserialize)]
+pub struct LegacyVaultFile {
+    pub header: LegacyVaultHeader,
+    /// base64(IV || AES-256-CBC ciphertext)
+    pub data: String,
+}
+
+/// Every layout a vault file can be found in on disk
+pub enum StoredVault {
+    /// No file, or an empty one
+    Empty,
+    /// Pre-master-password vault: base64 CBC data keyed by `.env` ENCRYPTION_KEY
+    EnvKeyed(Vec<u8>),
+    /// Version 1: master password, unauthenticated CBC body
+    CbcV1(LegacyVaultFile),
+    /// Current authenticated container
+    Aead(VaultFile),
+}
+
+/// Reads a vault file and works out which layout it uses, without decrypting it
+///
+/// # Errors
+///
+/// Returns `UnsupportedVersion` for a container written by a newer toolbox, and
+/// `Malformed` for anything that is not a recognisable vault.
+pub fn read_vault(file_path: &str) -> Result<StoredVault, VaultError> {
+    let contents = match fs::read(file_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(StoredVault::Empty),
+        Err(e) => return Err(VaultError::Io(e)),
+    };
+
+    if contents.iter().all(|b| b.is_ascii_whitespace()) {
+        return Ok(StoredVault::Empty);
+    }
+
+    let json = serde_json::from_slice::<serde_json::Value>(&contents)
+        .ok()
+        .filter(|value| value.is_object());
+    if let Some(value) = json {
+        let header = &value["header"];
+
+        if header.get("magic").is_some() {
+            if header["magic"] != VAULT_MAGIC {
+                return Err(VaultError::Malformed("Unknown vault magic".to_string()));
+            }
+            let version = header["version"].as_u64().unwrap_or(0) as u32;
+            if version != VAULT_VERSION {
+                return Err(VaultError::UnsupportedVersion(version));
+            }
+            return serde_json::from_value(value)
+                .map(StoredVault::Aead)
+                .map_err(|e| VaultError::Malformed(e.to_string()));
+        }
+
+        return serde_json::from_value(value)
+            .map(StoredVault::CbcV1)
+            .map_err(|e| VaultError::Malformed(e.to_string()));
+    }
+
+    let trimmed = String::from_utf8_lossy(&contents).trim().to_string();
+    if general_purpose::STANDARD.decode(&trimmed).is_ok() {
+        return Ok(StoredVault::EnvKeyed(trimmed.into_bytes()));
+    }
+
+    Err(VaultError::Malformed(
+        "File is neither a vault container nor a legacy vault".to_string(),
+    ))
+}
+
+/// Writes `contents` to a fresh temporary file next to `file_path` and returns its path
+///
+/// The temporary file is created readable only by the current user, so the file it
+/// later replaces never becomes readable by others.
+pub fn write_temporary(file_path: &str, contents: &[u8]) -> io::Result<String> {
+    let tmp_path = format!("{}.tmp", file_path);
+    // A leftover temporary file would keep its old permissions
+    match fs::remove_file(&tmp_path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    create_new(&tmp_path)
+        .and_then(|mut file| file.write_all(contents))
+        .map_err(|_| io::Error::other(format!("Failed to write to {}", tmp_path)))?;
+    Ok(tmp_path)
+}
+
+/// Writes `contents` to a temporary file next to `file_path` and renames it into place
+pub fn write_atomic(file_path: &str, contents: &[u8]) -> io::Result<()> {
+    let tmp_path = write_temporary(file_path, contents)?;
+    fs::rename(&tmp_path, Path::new(file_path))
+        .map_err(|_| io::Error::other(format!("Failed to replace {}", file_path)))
+}
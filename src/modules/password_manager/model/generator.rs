@@ -0,0 +1,237 @@
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use std::fmt;
+use zeroize::Zeroizing;
+
+use crate::core::SecretString;
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!@#$%^&*()-_=+[]{};:,.<>?/~";
+
+/// Characters that are easy to misread or mistype when copied by hand
+const AMBIGUOUS: &str = "Il1O0o|";
+
+/// Longest password the generator will produce
+pub const MAX_LENGTH: usize = 128;
+
+/// Most words the passphrase mode will produce
+pub const MAX_WORDS: usize = 16;
+
+/// Bundled diceware-style wordlist: 1296 short words, one per line (four dice per word)
+static WORDLIST: Lazy<Vec<&'static str>> =
+    Lazy::new(|| include_str!("wordlist.txt").lines().collect());
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeneratorMode {
+    /// Random characters from the enabled classes
+    Characters,
+    /// Random words from the bundled wordlist
+    Passphrase,
+}
+
+/// Settings for the password generator panel
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorOptions {
+    pub mode: GeneratorMode,
+    /// Number of characters (character mode)
+    pub length: usize,
+    pub lowercase: bool,
+    pub uppercase: bool,
+    pub digits: bool,
+    pub symbols: bool,
+    /// Leave out look-alike characters such as `l`, `1` and `O`
+    pub exclude_ambiguous: bool,
+    /// Number of words (passphrase mode)
+    pub words: usize,
+    pub separator: char,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            mode: GeneratorMode::Characters,
+            length: 20,
+            lowercase: true,
+            uppercase: true,
+            digits: true,
+            symbols: true,
+            exclude_ambiguous: true,
+            words: 6,
+            separator: '-',
+        }
+    }
+}
+
+impl GeneratorOptions {
+    /// Enabled character classes, with ambiguous characters removed if requested
+    fn classes(&self) -> Vec<Vec<char>> {
+        [
+            (self.lowercase, LOWERCASE),
+            (self.uppercase, UPPERCASE),
+            (self.digits, DIGITS),
+            (self.symbols, SYMBOLS),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, chars)| {
+            chars
+                .chars()
+                .filter(|c| !self.exclude_ambiguous || !AMBIGUOUS.contains(*c))
+                .collect()
+        })
+        .collect()
+    }
+}
+
+/// A generated password together with its estimated strength
+pub struct GeneratedPassword {
+    pub password: SecretString,
+    /// Estimated entropy in bits, assuming the attacker knows the generator settings
+    pub entropy_bits: f64,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum GeneratorError {
+    /// Character mode with every character class switched off
+    NoCharacterClasses,
+}
+
+impl fmt::Display for GeneratorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeneratorError::NoCharacterClasses => {
+                write!(f, "Enable at least one character class")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GeneratorError {}
+
+/// Generates a password or passphrase that is at least `min_length` characters long
+///
+/// Character mode raises the length to the floor and includes at least one character
+/// from every enabled class; passphrase mode adds words until the floor is reached.
+pub fn generate(
+    options: &GeneratorOptions,
+    min_length: usize,
+) -> Result<GeneratedPassword, GeneratorError> {
+    match options.mode {
+        GeneratorMode::Characters => generate_characters(options, min_length),
+        GeneratorMode::Passphrase => Ok(generate_passphrase(options, min_length)),
+    }
+}
+
+fn generate_characters(
+    options: &GeneratorOptions,
+    min_length: usize,
+) -> Result<GeneratedPassword, GeneratorError> {
+    let classes = options.classes();
+    if classes.is_empty() {
+        return Err(GeneratorError::NoCharacterClasses);
+    }
+
+    let pool: Vec<char> = classes.iter().flatten().copied().collect();
+    let length = options
+        .length
+        .max(min_length)
+        .max(classes.len())
+        .min(MAX_LENGTH.max(min_length));
+
+    let mut rng = thread_rng();
+    let mut chars: Zeroizing<Vec<char>> = Zeroizing::new(
+        classes
+            .iter()
+            .filter_map(|class| class.choose(&mut rng).copied())
+            .collect(),
+    );
+    while chars.len() < length {
+        chars.extend(pool.choose(&mut rng));
+    }
+    chars.shuffle(&mut rng);
+
+    Ok(GeneratedPassword {
+        password: chars.iter().collect::<String>().into(),
+        entropy_bits: length as f64 * (pool.len() as f64).log2(),
+    })
+}
+
+fn generate_passphrase(options: &GeneratorOptions, min_length: usize) -> GeneratedPassword {
+    let mut rng = thread_rng();
+    let mut words: Vec<&str> = Vec::new();
+    // Counted rather than joined, so no partial passphrase is left behind in memory
+    let length = |words: &[&str]| {
+        words.iter().map(|word| word.chars().count()).sum::<usize>() + words.len().saturating_sub(1)
+    };
+
+    while words.len() < options.words.max(1) || length(&words) < min_length {
+        words.extend(WORDLIST.choose(&mut rng));
+    }
+
+    GeneratedPassword {
+        entropy_bits: words.len() as f64 * (WORDLIST.len() as f64).log2(),
+        password: words.join(&options.separator.to_string()).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_wordlist_is_diceware_sized() {
+        let unique: HashSet<&str> = WORDLIST.iter().copied().collect();
+        assert_eq!(WORDLIST.len(), 1296);
+        assert_eq!(unique.len(), WORDLIST.len());
+    }
+
+    #[test]
+    fn test_characters_respect_classes_and_floor() {
+        let options = GeneratorOptions {
+            length: 8,
+            symbols: false,
+            ..GeneratorOptions::default()
+        };
+
+        let generated = generate(&options, 16).unwrap();
+        assert_eq!(generated.password.chars().count(), 16);
+        assert!(generated
+            .password
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric()));
+        assert!(generated.password.chars().any(|c| c.is_ascii_digit()));
+        assert!(!generated.password.chars().any(|c| AMBIGUOUS.contains(c)));
+        assert!(generated.entropy_bits > 90.0);
+
+        let none = GeneratorOptions {
+            lowercase: false,
+            uppercase: false,
+            digits: false,
+            symbols: false,
+            ..GeneratorOptions::default()
+        };
+        assert_eq!(
+            generate(&none, 0).err(),
+            Some(GeneratorError::NoCharacterClasses)
+        );
+    }
+
+    #[test]
+    fn test_passphrase_adds_words_up_to_floor() {
+        let options = GeneratorOptions {
+            mode: GeneratorMode::Passphrase,
+            words: 2,
+            ..GeneratorOptions::default()
+        };
+
+        let generated = generate(&options, 40).unwrap();
+        let words: Vec<&str> = generated.password.split('-').collect();
+        assert!(generated.password.len() >= 40);
+        assert!(words.iter().all(|word| WORDLIST.contains(word)));
+        assert!((generated.entropy_bits - words.len() as f64 * 1296f64.log2()).abs() < 1e-9);
+    }
+}
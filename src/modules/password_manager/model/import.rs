@@ -0,0 +1,790 @@
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use super::format::VAULT_MAGIC;
+use super::totp::TotpConfig;
+use super::{parse_custom_fields, parse_tags, EntryKind, PasswordEntry, Vault, VaultError};
+
+/// Export formats the importer understands
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImportFormat {
+    /// Any CSV with a header row; columns are mapped to entry fields
+    Csv,
+    /// Bitwarden unencrypted JSON export
+    Bitwarden,
+    /// KeePass 2 XML export
+    KeePass,
+    /// Toolbox vault file written by the portable export
+    PortableVault,
+}
+
+impl ImportFormat {
+    /// Picks the format from the file extension, falling back to sniffing the contents
+    pub fn detect(path: &str, contents: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase());
+
+        if is_toolbox_vault(contents) {
+            return ImportFormat::PortableVault;
+        }
+
+        match extension.as_deref() {
+            Some("json") => ImportFormat::Bitwarden,
+            Some("xml") => ImportFormat::KeePass,
+            Some("csv") => ImportFormat::Csv,
+            _ => match contents.trim_start().chars().next() {
+                Some('{') => ImportFormat::Bitwarden,
+                Some('<') => ImportFormat::KeePass,
+                _ => ImportFormat::Csv,
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImportFormat::Csv => "CSV",
+            ImportFormat::Bitwarden => "Bitwarden JSON",
+            ImportFormat::KeePass => "KeePass XML",
+            ImportFormat::PortableVault => "Toolbox portable vault",
+        }
+    }
+}
+
+fn is_toolbox_vault(contents: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(contents)
+        .map(|value| value["header"]["magic"] == VAULT_MAGIC)
+        .unwrap_or(false)
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    Io(std::io::Error),
+    /// The file is not a valid export in the detected format
+    Parse(String),
+    /// Bitwarden exports protected with a password or account key
+    EncryptedExport,
+    /// The CSV has no column that can be used as the password
+    MissingColumn(&'static str),
+    /// The file is an encrypted toolbox vault and its export password is needed
+    PasswordRequired,
+    /// The portable vault could not be opened
+    Vault(VaultError),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(e) => write!(f, "Failed to read import file: {}", e),
+            ImportError::Parse(reason) => write!(f, "Failed to parse import file: {}", reason),
+            ImportError::EncryptedExport => write!(
+                f,
+                "Encrypted Bitwarden exports are not supported; export as unencrypted JSON"
+            ),
+            ImportError::MissingColumn(field) => {
+                write!(f, "No CSV column could be mapped to the {} field", field)
+            }
+            ImportError::PasswordRequired => {
+                write!(
+                    f,
+                    "This file is an encrypted vault; enter its export password"
+                )
+            }
+            ImportError::Vault(e) => write!(f, "Failed to open portable vault: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<std::io::Error> for ImportError {
+    fn from(error: std::io::Error) -> Self {
+        ImportError::Io(error)
+    }
+}
+
+impl From<VaultError> for ImportError {
+    fn from(error: VaultError) -> Self {
+        ImportError::Vault(error)
+    }
+}
+
+/// Entry fields a CSV column can be mapped to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsvField {
+    Service,
+    Username,
+    Password,
+    Totp,
+    Url,
+    Notes,
+    Tags,
+    CustomFields,
+    Kind,
+    PrivateKey,
+    PublicKey,
+}
+
+/// Which CSV column (by index) feeds each entry field
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvMapping {
+    pub service: Option<usize>,
+    pub username: Option<usize>,
+    pub password: Option<usize>,
+    pub totp: Option<usize>,
+    pub url: Option<usize>,
+    pub notes: Option<usize>,
+    pub tags: Option<usize>,
+    pub custom_fields: Option<usize>,
+    pub kind: Option<usize>,
+    pub private_key: Option<usize>,
+    pub public_key: Option<usize>,
+}
+
+impl CsvMapping {
+    /// Guesses the mapping from header names used by common password managers
+    pub fn guess(headers: &[String]) -> Self {
+        let find = |names: &[&str]| {
+            names.iter().find_map(|name| {
+                headers
+                    .iter()
+                    .position(|header| header.trim().eq_ignore_ascii_case(name))
+            })
+        };
+
+        Self {
+            service: find(&[
+                "service",
+                "name",
+                "title",
+                "account",
+                "url",
+                "login_uri",
+                "website",
+            ]),
+            username: find(&["username", "login_username", "user", "login", "email"]),
+            password: find(&["password", "login_password", "pass"]),
+            totp: find(&["totp", "login_totp", "otp", "otpauth"]),
+            url: find(&["url", "login_uri", "website", "uri"]),
+            notes: find(&["notes", "note", "comments", "extra"]),
+            tags: find(&["tags", "tag", "folder", "group"]),
+            custom_fields: find(&["custom_fields", "fields"]),
+            kind: find(&["kind", "type"]),
+            private_key: find(&["private_key"]),
+            public_key: find(&["public_key"]),
+        }
+    }
+
+    /// Moves `field` to the next column, wrapping through "unmapped"
+    pub fn cycle(&mut self, field: CsvField, columns: usize) {
+        let slot = match field {
+            CsvField::Service => &mut self.service,
+            CsvField::Username => &mut self.username,
+            CsvField::Password => &mut self.password,
+            CsvField::Totp => &mut self.totp,
+            CsvField::Url => &mut self.url,
+            CsvField::Notes => &mut self.notes,
+            CsvField::Tags => &mut self.tags,
+            CsvField::CustomFields => &mut self.custom_fields,
+            CsvField::Kind => &mut self.kind,
+            CsvField::PrivateKey => &mut self.private_key,
+            CsvField::PublicKey => &mut self.public_key,
+        };
+        *slot = match *slot {
+            None if columns > 0 => Some(0),
+            Some(column) if column + 1 < columns => Some(column + 1),
+            _ => None,
+        };
+    }
+}
+
+/// Entries read from an export, plus how many records could not be used
+#[derive(Default)]
+pub struct ParsedImport {
+    pub entries: Vec<PasswordEntry>,
+    /// Records without a password, or of a type the vault does not store
+    pub skipped: usize,
+    /// TOTP seeds that could not be read; the raw value is kept in the entry's notes
+    pub unreadable_totp: usize,
+}
+
+/// Splits a CSV export into its header row and data rows
+pub fn read_csv(contents: &str) -> Result<(Vec<String>, Vec<Vec<String>>), ImportError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(contents.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| ImportError::Parse(e.to_string()))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+
+    let rows = reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|e| ImportError::Parse(e.to_string()))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((headers, rows))
+}
+
+/// Builds entries from CSV rows using `mapping`
+pub fn parse_csv_rows(
+    rows: &[Vec<String>],
+    mapping: &CsvMapping,
+) -> Result<ParsedImport, ImportError> {
+    let password_column = mapping
+        .password
+        .ok_or(ImportError::MissingColumn("password"))?;
+    let cell = |row: &Vec<String>, column: Option<usize>| {
+        column
+            .and_then(|column| row.get(column))
+            .map(|value| value.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let mut parsed = ParsedImport::default();
+    for row in rows {
+        let url = cell(row, mapping.url);
+        let public_key = cell(row, mapping.public_key);
+        let mut notes = cell(row, mapping.notes);
+        let totp = import_totp(&mut parsed, &cell(row, mapping.totp), &mut notes);
+        let custom_fields = cell(row, mapping.custom_fields);
+        // Fields written in another manager's notation are kept as notes rather than lost
+        let custom_fields = parse_custom_fields(&custom_fields).unwrap_or_else(|_| {
+            if !notes.is_empty() {
+                notes.push('\n');
+            }
+            notes.push_str(&custom_fields);
+            Vec::new()
+        });
+        let entry = PasswordEntry {
+            kind: EntryKind::from_id(&cell(row, mapping.kind)),
+            service: cell(row, mapping.service),
+            username: cell(row, mapping.username),
+            password: cell(row, Some(password_column)).into(),
+            totp,
+            url: (!url.is_empty()).then_some(url),
+            notes: notes.into(),
+            tags: parse_tags(&cell(row, mapping.tags)),
+            custom_fields,
+            private_key: cell(row, mapping.private_key).into(),
+            public_key: (!public_key.is_empty()).then_some(public_key),
+            ..Default::default()
+        };
+        push_entry(&mut parsed, entry);
+    }
+    Ok(parsed)
+}
+
+#[derive(Deserialize)]
+struct BitwardenExport {
+    #[serde(default)]
+    encrypted: bool,
+    #[serde(default)]
+    items: Vec<BitwardenItem>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenItem {
+    /// 1 = login, 2 = secure note, 3 = card, 4 = identity
+    #[serde(rename = "type")]
+    item_type: u8,
+    #[serde(default)]
+    name: String,
+    notes: Option<String>,
+    login: Option<BitwardenLogin>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenLogin {
+    username: Option<String>,
+    password: Option<String>,
+    totp: Option<String>,
+    #[serde(default)]
+    uris: Vec<BitwardenUri>,
+}
+
+#[derive(Deserialize)]
+struct BitwardenUri {
+    uri: Option<String>,
+}
+
+/// Reads the login and secure note items of a Bitwarden unencrypted JSON export
+///
+/// Cards and identities have no counterpart in the vault and are skipped.
+pub fn parse_bitwarden(contents: &str) -> Result<ParsedImport, ImportError> {
+    let export: BitwardenExport =
+        serde_json::from_str(contents).map_err(|e| ImportError::Parse(e.to_string()))?;
+    if export.encrypted {
+        return Err(ImportError::EncryptedExport);
+    }
+
+    let mut parsed = ParsedImport::default();
+    for item in export.items {
+        if item.item_type == 2 {
+            push_entry(
+                &mut parsed,
+                PasswordEntry {
+                    kind: EntryKind::SecureNote,
+                    service: item.name,
+                    notes: item.notes.unwrap_or_default().into(),
+                    ..Default::default()
+                },
+            );
+            continue;
+        }
+        let Some(login) = item.login.filter(|_| item.item_type == 1) else {
+            parsed.skipped += 1;
+            continue;
+        };
+
+        let url = login.uris.iter().find_map(|uri| uri.uri.clone());
+        let service = if item.name.trim().is_empty() {
+            url.clone().unwrap_or_default()
+        } else {
+            item.name
+        };
+
+        let mut notes = item.notes.unwrap_or_default();
+        let totp = import_totp(&mut parsed, &login.totp.unwrap_or_default(), &mut notes);
+        let entry = PasswordEntry {
+            service,
+            username: login.username.unwrap_or_default(),
+            password: login.password.unwrap_or_default().into(),
+            totp,
+            url,
+            notes: notes.into(),
+            ..Default::default()
+        };
+        push_entry(&mut parsed, entry);
+    }
+    Ok(parsed)
+}
+
+/// Reads the entries of a KeePass 2 XML export
+///
+/// Old versions kept in `<History>` and anything in the recycle bin are left out.
+pub fn parse_keepass(contents: &str) -> Result<ParsedImport, ImportError> {
+    let document =
+        roxmltree::Document::parse(contents).map_err(|e| ImportError::Parse(e.to_string()))?;
+    if !document.root_element().has_tag_name("KeePassFile") {
+        return Err(ImportError::Parse("not a KeePass XML export".to_string()));
+    }
+
+    let recycle_bin = document
+        .descendants()
+        .find(|node| node.has_tag_name("RecycleBinUUID"))
+        .and_then(|node| node.text())
+        .map(str::trim)
+        .filter(|uuid| !uuid.is_empty());
+
+    let mut parsed = ParsedImport::default();
+    for node in document
+        .descendants()
+        .filter(|node| node.has_tag_name("Entry"))
+    {
+        let in_history = node
+            .ancestors()
+            .any(|ancestor| ancestor.has_tag_name("History"));
+        // Without a recycle bin UUID nothing is in it, even groups that have no UUID either
+        let in_recycle_bin = recycle_bin.is_some_and(|recycle_bin| {
+            node.ancestors().any(|ancestor| {
+                ancestor.has_tag_name("Group")
+                    && ancestor
+                        .children()
+                        .find(|child| child.has_tag_name("UUID"))
+                        .and_then(|uuid| uuid.text())
+                        .map(str::trim)
+                        == Some(recycle_bin)
+            })
+        });
+        if in_history || in_recycle_bin {
+            continue;
+        }
+
+        let field = |key: &str| {
+            node.children()
+                .filter(|child| child.has_tag_name("String"))
+                .find(|string| {
+                    string
+                        .children()
+                        .any(|child| child.has_tag_name("Key") && child.text() == Some(key))
+                })
+                .and_then(|string| string.children().find(|child| child.has_tag_name("Value")))
+                .and_then(|value| value.text())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let title = field("Title");
+        let url = field("URL");
+        let mut notes = field("Notes");
+        // KeePassXC stores the seed as an otpauth URI under "otp"
+        let totp = import_totp(&mut parsed, &field("otp"), &mut notes);
+        let entry = PasswordEntry {
+            service: if title.is_empty() { url.clone() } else { title },
+            username: field("UserName"),
+            password: field("Password").into(),
+            totp,
+            url: (!url.is_empty()).then_some(url),
+            notes: notes.into(),
+            ..Default::default()
+        };
+        push_entry(&mut parsed, entry);
+    }
+    Ok(parsed)
+}
+
+/// Reads an exported TOTP seed, keeping one it cannot read (Steam codes, unsupported
+/// algorithms) at the end of `notes` instead of dropping it
+fn import_totp(parsed: &mut ParsedImport, raw: &str, notes: &mut String) -> Option<TotpConfig> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return None;
+    }
+    TotpConfig::parse(raw)
+        .map_err(|_| {
+            parsed.unreadable_totp += 1;
+            if !notes.is_empty() {
+                notes.push('\n');
+            }
+            notes.push_str("TOTP: ");
+            notes.push_str(raw);
+        })
+        .ok()
+}
+
+/// Keeps `entry` unless it is a login without a password; notes and keys need none
+fn push_entry(parsed: &mut ParsedImport, entry: PasswordEntry) {
+    if entry.kind == EntryKind::Login && entry.password.is_empty() {
+        parsed.skipped += 1;
+    } else {
+        parsed.entries.push(entry);
+    }
+}
+
+/// An imported entry awaiting confirmation in the preview
+pub struct ImportCandidate {
+    pub entry: PasswordEntry,
+    /// Already in the vault (or earlier in the same file) with the same service and username
+    pub duplicate: bool,
+    /// Whether the entry will be written when the import is confirmed
+    pub include: bool,
+}
+
+/// An export loaded from disk, ready to be previewed, deduplicated and written
+pub struct ImportSession {
+    pub path: String,
+    pub format: ImportFormat,
+    /// CSV only: header row, data rows and the current column mapping
+    pub headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    pub mapping: CsvMapping,
+    pub candidates: Vec<ImportCandidate>,
+    pub skipped: usize,
+    /// TOTP seeds that could not be read and were kept in notes
+    pub unreadable_totp: usize,
+    /// Problem with the current CSV mapping, shown instead of candidates
+    pub mapping_error: Option<String>,
+}
+
+// Sessions live in AppState, which derives Debug; the rows and entries hold passwords
+impl fmt::Debug for ImportSession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImportSession")
+            .field("path", &self.path)
+            .field("format", &self.format)
+            .field("candidates", &self.candidates.len())
+            .field("skipped", &self.skipped)
+            .field("unreadable_totp", &self.unreadable_totp)
+            .finish()
+    }
+}
+
+impl ImportSession {
+    /// Reads and parses `path`, marking entries that already exist in `existing`
+    ///
+    /// `password` is only used for portable vault files; opening one without it returns
+    /// `PasswordRequired` so the caller can ask for it.
+    pub fn open(
+        path: &str,
+        password: Option<&str>,
+        existing: &[PasswordEntry],
+    ) -> Result<Self, ImportError> {
+        // The whole export is in plain text; wipe it once the entries have been read
+        let contents = Zeroizing::new(fs::read_to_string(path)?);
+        let format = ImportFormat::detect(path, &contents);
+
+        let mut session = Self {
+            path: path.to_string(),
+            format,
+            headers: Vec::new(),
+            rows: Vec::new(),
+            mapping: CsvMapping::guess(&[]),
+            candidates: Vec::new(),
+            skipped: 0,
+            unreadable_totp: 0,
+            mapping_error: None,
+        };
+
+        match format {
+            ImportFormat::Csv => {
+                let (headers, rows) = read_csv(&contents)?;
+                session.mapping = CsvMapping::guess(&headers);
+                session.headers = headers;
+                session.rows = rows;
+                session.remap(existing);
+            }
+            ImportFormat::Bitwarden => session.set_parsed(parse_bitwarden(&contents)?, existing),
+            ImportFormat::KeePass => session.set_parsed(parse_keepass(&contents)?, existing),
+            ImportFormat::PortableVault => {
+                let password = password.ok_or(ImportError::PasswordRequired)?;
+                // An export is never rewritten in place, so it needs no snapshots
+                let entries = Vault::unlock(path, password, 0)?.retrieve_password()?;
+                let parsed = ParsedImport {
+                    entries,
+                    ..Default::default()
+                };
+                session.set_parsed(parsed, existing);
+            }
+        }
+        Ok(session)
+    }
+
+    /// Moves a CSV field to the next column and rebuilds the preview
+    pub fn cycle_mapping(&mut self, field: CsvField, existing: &[PasswordEntry]) {
+        if self.format == ImportFormat::Csv {
+            self.mapping.cycle(field, self.headers.len());
+            self.remap(existing);
+        }
+    }
+
+    fn remap(&mut self, existing: &[PasswordEntry]) {
+        match parse_csv_rows(&self.rows, &self.mapping) {
+            Ok(parsed) => {
+                self.mapping_error = None;
+                self.set_parsed(parsed, existing);
+            }
+            Err(e) => {
+                self.mapping_error = Some(e.to_string());
+                self.candidates.clear();
+                self.skipped = 0;
+                self.unreadable_totp = 0;
+            }
+        }
+    }
+
+    fn set_parsed(&mut self, parsed: ParsedImport, existing: &[PasswordEntry]) {
+        let mut seen: Vec<&PasswordEntry> = existing.iter().collect();
+        let mut candidates = Vec::with_capacity(parsed.entries.len());
+
+        for entry in &parsed.entries {
+            let duplicate = seen.iter().any(|other| same_login(other, entry));
+            seen.push(entry);
+            candidates.push(duplicate);
+        }
+
+        self.candidates = parsed
+            .entries
+            .into_iter()
+            .zip(candidates)
+            .map(|(entry, duplicate)| ImportCandidate {
+                entry,
+                duplicate,
+                include: !duplicate,
+            })
+            .collect();
+        self.skipped = parsed.skipped;
+        self.unreadable_totp = parsed.unreadable_totp;
+    }
+
+    /// Flips whether the candidate at `index` will be imported
+    pub fn toggle(&mut self, index: usize) {
+        if let Some(candidate) = self.candidates.get_mut(index) {
+            candidate.include = !candidate.include;
+        }
+    }
+
+    /// Entries selected for import
+    pub fn selected_entries(&self) -> Vec<PasswordEntry> {
+        self.candidates
+            .iter()
+            .filter(|candidate| candidate.include)
+            .map(|candidate| candidate.entry.clone())
+            .collect()
+    }
+}
+
+/// Two entries describe the same login: same service (case-insensitive) and username
+fn same_login(a: &PasswordEntry, b: &PasswordEntry) -> bool {
+    a.service.trim().eq_ignore_ascii_case(b.service.trim())
+        && a.username.trim() == b.username.trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(service: &str, username: &str) -> PasswordEntry {
+        PasswordEntry {
+            service: service.to_string(),
+            username: username.to_string(),
+            password: "secret".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_csv_mapping_and_dedupe() {
+        let path = std::env::temp_dir().join(format!("toolbox-import-{}.csv", std::process::id()));
+        let path = path.to_str().expect("temp path is not UTF-8").to_string();
+        fs::write(
+            &path,
+            "Title,Login,Password,Notes\n\
+             github.com,alice,pw1,\n\
+             GitHub.com,alice,pw2,dup\n\
+             example.com,bob,,no password\n\
+             gitlab.com,carol,pw3,\n",
+        )
+        .unwrap();
+
+        let existing = vec![entry("gitlab.com", "carol")];
+        let mut session = ImportSession::open(&path, None, &existing).expect("Import failed");
+        assert_eq!(session.format, ImportFormat::Csv);
+        assert_eq!(
+            session.mapping,
+            CsvMapping {
+                service: Some(0),
+                username: Some(1),
+                password: Some(2),
+                totp: None,
+                url: None,
+                notes: Some(3),
+                tags: None,
+                custom_fields: None,
+                kind: None,
+                private_key: None,
+                public_key: None,
+            }
+        );
+        assert_eq!(session.skipped, 1);
+        let duplicates: Vec<bool> = session.candidates.iter().map(|c| c.duplicate).collect();
+        assert_eq!(duplicates, vec![false, true, true]);
+        assert_eq!(session.selected_entries().len(), 1);
+
+        session.toggle(1);
+        assert_eq!(session.selected_entries()[1].password, "pw2");
+
+        // Mapping the password to "Notes" changes which rows are usable
+        session.cycle_mapping(CsvField::Password, &existing);
+        assert_eq!(session.mapping.password, Some(3));
+        assert_eq!(session.candidates.len(), 2);
+        session.cycle_mapping(CsvField::Password, &existing);
+        assert!(session.mapping_error.is_some());
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_bitwarden_export() {
+        let parsed = parse_bitwarden(
+            r#"{
+                "encrypted": false,
+                "items": [
+                    {"type": 1, "name": "GitHub", "login": {"username": "alice", "password": "pw",
+                     "totp": "otpauth://totp/GitHub?secret=JBSWY3DPEHPK3PXP",
+                     "uris": [{"match": null, "uri": "https://github.com"}]}},
+                    {"type": 1, "name": "", "login": {"username": "bob", "password": "pw2",
+                     "totp": null, "uris": [{"uri": "https://example.com"}]}},
+                    {"type": 1, "name": "Steam", "notes": "games", "login": {"username": "carol",
+                     "password": "pw3", "totp": "steam://ABCDEFGH", "uris": []}},
+                    {"type": 2, "name": "A note", "notes": "text", "secureNote": {"type": 0}},
+                    {"type": 3, "name": "Visa", "card": {"number": "4111"}}
+                ]
+            }"#,
+        )
+        .expect("Parse failed");
+
+        assert_eq!(parsed.entries.len(), 4);
+        assert_eq!(parsed.skipped, 1);
+        assert_eq!(parsed.entries[0].service, "GitHub");
+        assert!(parsed.entries[0].totp.is_some());
+        assert_eq!(parsed.entries[1].service, "https://example.com");
+        // A seed that cannot be read is counted and kept in the notes
+        assert_eq!(parsed.unreadable_totp, 1);
+        assert!(parsed.entries[2].totp.is_none());
+        assert_eq!(parsed.entries[2].notes, "games\nTOTP: steam://ABCDEFGH");
+        assert_eq!(parsed.entries[3].kind, EntryKind::SecureNote);
+        assert_eq!(parsed.entries[3].service, "A note");
+        assert_eq!(parsed.entries[3].notes, "text");
+
+        assert!(matches!(
+            parse_bitwarden(r#"{"encrypted": true, "encKeyValidation_DO_NOT_EDIT": "x"}"#),
+            Err(ImportError::EncryptedExport)
+        ));
+    }
+
+    #[test]
+    fn test_keepass_export_skips_history_and_recycle_bin() {
+        let parsed = parse_keepass(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <KeePassFile>
+              <Meta><RecycleBinUUID>bin</RecycleBinUUID></Meta>
+              <Root><Group><UUID>root</UUID><Name>Root</Name>
+                <Entry>
+                  <String><Key>Title</Key><Value>GitHub</Value></String>
+                  <String><Key>UserName</Key><Value>alice</Value></String>
+                  <String><Key>Password</Key><Value>current</Value></String>
+                  <History><Entry>
+                    <String><Key>Title</Key><Value>GitHub</Value></String>
+                    <String><Key>Password</Key><Value>old</Value></String>
+                  </Entry></History>
+                </Entry>
+                <Group><UUID>bin</UUID><Name>Recycle Bin</Name>
+                  <Entry>
+                    <String><Key>Title</Key><Value>Deleted</Value></String>
+                    <String><Key>Password</Key><Value>gone</Value></String>
+                  </Entry>
+                </Group>
+              </Group></Root>
+            </KeePassFile>"#,
+        )
+        .expect("Parse failed");
+
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].username, "alice");
+        assert_eq!(parsed.entries[0].password, "current");
+
+        // No recycle bin and groups without UUIDs: every entry is kept
+        let parsed = parse_keepass(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <KeePassFile>
+              <Meta><RecycleBinUUID></RecycleBinUUID></Meta>
+              <Root><Group><Name>Root</Name>
+                <Entry>
+                  <String><Key>Title</Key><Value>GitHub</Value></String>
+                  <String><Key>Password</Key><Value>pw</Value></String>
+                </Entry>
+                <Group><UUID></UUID><Name>Work</Name>
+                  <Entry>
+                    <String><Key>Title</Key><Value>Jira</Value></String>
+                    <String><Key>Password</Key><Value>pw2</Value></String>
+                  </Entry>
+                </Group>
+              </Group></Root>
+            </KeePassFile>"#,
+        )
+        .expect("Parse failed");
+        assert_eq!(parsed.entries.len(), 2);
+    }
+}
@@ -0,0 +1,299 @@
+use chrono::Utc;
+use std::cmp::Reverse;
+use std::fs;
+use std::io;
+use std::path::Path;
+use zeroize::Zeroizing;
+
+use super::crypto::{derive_key, KdfParams};
+use super::format::{read_vault, write_temporary, StoredVault, VAULT_CIPHER};
+use super::vaults::create_vault_dir;
+use super::{PasswordEntry, Vault, VaultError};
+
+const SNAPSHOT_EXTENSION: &str = "json";
+
+/// Hands out the key for a file's KDF parameters
+type KeyFor<'a> = dyn FnMut(&KdfParams) -> Result<Zeroizing<Vec<u8>>, VaultError> + 'a;
+
+/// Directory holding the snapshots of the vault at `vault_path`
+pub fn snapshot_dir(vault_path: &str) -> String {
+    format!("{}.snapshots", vault_path)
+}
+
+/// A copy of the vault file taken before it was rewritten
+///
+/// Snapshots are the encrypted vault files as they were, so one taken before a master
+/// password change still needs the old password.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: String,
+    /// When it was taken (Unix milliseconds)
+    pub taken_at_ms: i64,
+}
+
+/// Snapshots of the vault at `vault_path`, newest first
+pub fn list_snapshots(vault_path: &str) -> io::Result<Vec<Snapshot>> {
+    let entries = match fs::read_dir(snapshot_dir(vault_path)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(SNAPSHOT_EXTENSION) {
+            continue;
+        }
+        let taken_at_ms = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<i64>().ok());
+        if let Some(taken_at_ms) = taken_at_ms {
+            snapshots.push(Snapshot {
+                path: path.to_string_lossy().into_owned(),
+                taken_at_ms,
+            });
+        }
+    }
+    snapshots.sort_by_key(|snapshot| Reverse(snapshot.taken_at_ms));
+    Ok(snapshots)
+}
+
+/// Copies the current vault file into its snapshot directory, keeping the `keep` newest
+///
+/// Does nothing when `keep` is 0 or there is no vault file yet.
+pub fn take_snapshot(vault_path: &str, keep: usize) -> io::Result<()> {
+    if keep == 0 || !Path::new(vault_path).is_file() {
+        return Ok(());
+    }
+
+    let dir = snapshot_dir(vault_path);
+    create_vault_dir(&dir)?;
+    let mut taken_at_ms = Utc::now().timestamp_millis();
+    let mut target = Path::new(&dir).join(format!("{}.{}", taken_at_ms, SNAPSHOT_EXTENSION));
+    // Two writes within the same millisecond must not replace each other's snapshot
+    while target.exists() {
+        taken_at_ms += 1;
+        target = Path::new(&dir).join(format!("{}.{}", taken_at_ms, SNAPSHOT_EXTENSION));
+    }
+    fs::copy(vault_path, &target)?;
+
+    for old in list_snapshots(vault_path)?.into_iter().skip(keep) {
+        fs::remove_file(old.path)?;
+    }
+    Ok(())
+}
+
+/// Puts `snapshot` back in place of the vault at `vault_path`
+///
+/// The snapshot is first copied to a private temporary file next to the vault, so the
+/// vault is only touched once the copy is complete. The file being replaced is kept next
+/// to the vault as `<vault>.damaged-<time>`, so a restore never throws data away. Returns
+/// that path, if there was a file to keep.
+pub fn restore_snapshot(vault_path: &str, snapshot: &Snapshot) -> io::Result<Option<String>> {
+    let tmp_path = write_temporary(vault_path, &fs::read(&snapshot.path)?)?;
+
+    let kept = if Path::new(vault_path).exists() {
+        let kept = format!("{}.damaged-{}", vault_path, Utc::now().timestamp_millis());
+        if let Err(e) = fs::rename(vault_path, &kept) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+        Some(kept)
+    } else {
+        None
+    };
+
+    if let Err(e) = fs::rename(&tmp_path, vault_path) {
+        // Put the replaced file back rather than leave no vault at all
+        if let Some(ref kept) = kept {
+            let _ = fs::rename(kept, vault_path);
+        }
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    Ok(kept)
+}
+
+/// What checking one vault file found: its entry count, or why it cannot be read
+#[derive(Debug)]
+pub struct FileCheck {
+    pub path: String,
+    /// Set for snapshots
+    pub snapshot: Option<Snapshot>,
+    pub result: Result<usize, VaultError>,
+}
+
+/// Result of checking a vault and its snapshots
+#[derive(Debug)]
+pub struct VaultCheck {
+    pub vault: FileCheck,
+    /// Newest first
+    pub snapshots: Vec<FileCheck>,
+}
+
+impl VaultCheck {
+    pub fn is_healthy(&self) -> bool {
+        self.vault.result.is_ok()
+    }
+
+    /// True if the password opened the vault or any of its snapshots
+    pub fn password_verified(&self) -> bool {
+        let opened = |check: &FileCheck| !matches!(check.result, Err(VaultError::WrongPassword));
+        let readable = |check: &FileCheck| {
+            !matches!(
+                check.result,
+                Err(VaultError::Malformed(_)
+                    | VaultError::UnsupportedVersion(_)
+                    | VaultError::Io(_))
+            )
+        };
+        std::iter::once(&self.vault)
+            .chain(&self.snapshots)
+            .any(|check| opened(check) && readable(check))
+    }
+
+    /// True if the password was refused everywhere, i.e. it is most likely mistyped
+    pub fn wrong_password(&self) -> bool {
+        !self.password_verified()
+            && std::iter::once(&self.vault)
+                .chain(&self.snapshots)
+                .any(|check| matches!(check.result, Err(VaultError::WrongPassword)))
+    }
+}
+
+/// Validates the structure, header and MAC of one vault file and parses its entries
+fn check_file(path: &str, key_for: &mut KeyFor) -> Result<usize, VaultError> {
+    let vault_file = match read_vault(path)? {
+        StoredVault::Aead(vault_file) => vault_file,
+        StoredVault::Empty => return Err(VaultError::Malformed("the file is empty".to_string())),
+        StoredVault::CbcV1(_) | StoredVault::EnvKeyed(_) => {
+            return Err(VaultError::Malformed(
+                "older vault format without a MAC; unlocking it upgrades it".to_string(),
+            ))
+        }
+    };
+
+    let header = &vault_file.header;
+    if header.cipher != VAULT_CIPHER {
+        return Err(VaultError::Malformed(format!(
+            "unknown cipher {}",
+            header.cipher
+        )));
+    }
+    if header.kdf.algorithm != "argon2id" {
+        return Err(VaultError::Malformed(format!(
+            "unknown key derivation {}",
+            header.kdf.algorithm
+        )));
+    }
+
+    let key = key_for(&header.kdf)?;
+    let decrypted = vault_file.open(&key)?;
+    let entries: Vec<PasswordEntry> = serde_json::from_slice(&decrypted)
+        .map_err(|e| VaultError::Malformed(format!("entries do not parse: {}", e)))?;
+    Ok(entries.len())
+}
+
+fn check_with(vault_path: &str, key_for: &mut KeyFor) -> VaultCheck {
+    let vault = FileCheck {
+        path: vault_path.to_string(),
+        snapshot: None,
+        result: check_file(vault_path, key_for),
+    };
+    let snapshots = list_snapshots(vault_path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|snapshot| FileCheck {
+            path: snapshot.path.clone(),
+            result: check_file(&snapshot.path, key_for),
+            snapshot: Some(snapshot),
+        })
+        .collect();
+    VaultCheck { vault, snapshots }
+}
+
+/// Checks the vault at `vault_path` and its snapshots with `master_password`
+///
+/// Used when the vault cannot be unlocked. Keys are derived once per distinct salt, so
+/// snapshots taken under the same master password cost no extra Argon2id runs.
+pub fn check_vault(vault_path: &str, master_password: &str) -> VaultCheck {
+    let mut keys: Vec<(KdfParams, Zeroizing<Vec<u8>>)> = Vec::new();
+    check_with(vault_path, &mut |kdf| {
+        if let Some((_, key)) = keys.iter().find(|(params, _)| params == kdf) {
+            return Ok(key.clone());
+        }
+        let key = derive_key(master_password, kdf)?;
+        keys.push((kdf.clone(), key.clone()));
+        Ok(key)
+    })
+}
+
+impl Vault {
+    /// Checks the open vault's file and snapshots with the key already in memory
+    ///
+    /// Snapshots from before a master password change report `WrongPassword`.
+    pub fn check(&self) -> VaultCheck {
+        check_with(&self.file_path, &mut |kdf| {
+            if *kdf == self.header.kdf {
+                Ok(self.key.clone())
+            } else {
+                Err(VaultError::WrongPassword)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::test_vault;
+    use super::*;
+
+    #[test]
+    fn test_snapshots_roll_and_restore_a_damaged_vault() {
+        let mut vault = test_vault("snapshots", "pw");
+        vault.set_snapshot_limit(2);
+        for service in ["one", "two", "three"] {
+            vault
+                .save_password(&PasswordEntry {
+                    service: service.to_string(),
+                    ..Default::default()
+                })
+                .expect("Save failed");
+        }
+        // Three rewrites, two snapshots kept: the vault as it was with one and two entries
+        let snapshots = list_snapshots(&vault.file_path).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(vault.check().is_healthy());
+
+        fs::write(&vault.file_path, "{ damaged").unwrap();
+        assert!(vault.retrieve_password().is_err());
+        // A vault that cannot be read is never replaced by an empty one
+        assert!(vault.save_password(&PasswordEntry::default()).is_err());
+        assert_eq!(fs::read_to_string(&vault.file_path).unwrap(), "{ damaged");
+
+        let check = check_vault(&vault.file_path, "pw");
+        assert!(!check.is_healthy());
+        assert!(check.password_verified());
+        assert_eq!(check.snapshots[0].result.as_ref().ok(), Some(&2));
+        assert!(!check_vault(&vault.file_path, "wrong").password_verified());
+
+        // A stale temporary file must not hand its permissions to the restored vault
+        fs::write(format!("{}.tmp", vault.file_path), "stale").unwrap();
+        let kept = restore_snapshot(&vault.file_path, &snapshots[0]).unwrap();
+        assert_eq!(vault.retrieve_password().unwrap().len(), 2);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&vault.file_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        let kept = kept.expect("Damaged file was not kept");
+        assert_eq!(fs::read_to_string(&kept).unwrap(), "{ damaged");
+
+        let _ = fs::remove_file(&vault.file_path);
+        let _ = fs::remove_file(&kept);
+        let _ = fs::remove_dir_all(snapshot_dir(&vault.file_path));
+    }
+}
@@ -0,0 +1,157 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+
+use super::format::write_atomic;
+use super::integrity::snapshot_dir;
+use super::share::identity_path;
+
+/// Longest back-off between two unlock attempts
+const MAX_BACKOFF_SECONDS: u64 = 300;
+
+/// Failed unlock attempts of one vault, kept in a file next to it
+///
+/// The counter survives restarts, so quitting toolbox does not reset the back-off.
+/// It only slows down guessing through the UI; the Argon2id key derivation is what
+/// protects a copied vault file.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct UnlockAttempts {
+    /// Failed attempts since the last successful unlock
+    pub failed: u32,
+    /// Time of the last failure (Unix seconds)
+    pub last_failed_at: Option<i64>,
+}
+
+impl UnlockAttempts {
+    fn path(vault_path: &str) -> String {
+        format!("{}.attempts", vault_path)
+    }
+
+    /// Reads the counter of the vault at `vault_path`; a missing file means no failures
+    ///
+    /// An unreadable counter counts as one failure, so a damaged file still leaves a
+    /// back-off in place.
+    pub fn load(vault_path: &str) -> Self {
+        match fs::read(Self::path(vault_path)) {
+            Ok(contents) => serde_json::from_slice(&contents).unwrap_or(Self {
+                failed: 1,
+                last_failed_at: Some(Utc::now().timestamp()),
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, vault_path: &str) -> io::Result<()> {
+        let contents = serde_json::to_vec(self).map_err(io::Error::other)?;
+        write_atomic(&Self::path(vault_path), &contents)
+    }
+
+    /// Forgets the failures after a successful unlock
+    pub fn reset(vault_path: &str) -> io::Result<()> {
+        match fs::remove_file(Self::path(vault_path)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn record_failure(&mut self, now: i64) {
+        self.failed = self.failed.saturating_add(1);
+        self.last_failed_at = Some(now);
+    }
+
+    /// Seconds the next attempt has to wait after the last failure: 1, 2, 4, ... up to
+    /// five minutes
+    pub fn backoff_seconds(&self) -> u64 {
+        match self.failed {
+            0 => 0,
+            failed => 1u64
+                .checked_shl(failed - 1)
+                .unwrap_or(u64::MAX)
+                .min(MAX_BACKOFF_SECONDS),
+        }
+    }
+
+    /// True once `max_attempts` failures have been reached (0 means no limit)
+    pub fn limit_reached(&self, max_attempts: u32) -> bool {
+        max_attempts > 0 && self.failed >= max_attempts
+    }
+
+    /// Seconds until another attempt is allowed at `now`
+    ///
+    /// After `max_attempts` failures the wait is at least `lockout_seconds`.
+    pub fn wait_seconds(&self, now: i64, max_attempts: u32, lockout_seconds: u64) -> u64 {
+        let mut wait = self.backoff_seconds();
+        if self.limit_reached(max_attempts) {
+            wait = wait.max(lockout_seconds);
+        }
+        let elapsed = self
+            .last_failed_at
+            .map(|failed_at| now.saturating_sub(failed_at).max(0) as u64)
+            .unwrap_or(0);
+        wait.saturating_sub(elapsed)
+    }
+}
+
+/// Deletes the vault at `vault_path` together with its snapshots, attempt counter and
+/// sharing key
+pub fn wipe_vault(vault_path: &str) -> io::Result<()> {
+    fs::remove_file(vault_path)?;
+    for removed in [
+        fs::remove_dir_all(snapshot_dir(vault_path)),
+        fs::remove_file(identity_path(vault_path)),
+    ] {
+        match removed {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
+        }
+    }
+    UnlockAttempts::reset(vault_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_lockout_applies() {
+        let mut attempts = UnlockAttempts::default();
+        assert_eq!(attempts.wait_seconds(1_000, 3, 900), 0);
+
+        attempts.record_failure(1_000);
+        assert_eq!(attempts.wait_seconds(1_000, 3, 900), 1);
+        attempts.record_failure(1_000);
+        assert_eq!(attempts.wait_seconds(1_000, 3, 900), 2);
+        assert_eq!(attempts.wait_seconds(1_002, 3, 900), 0);
+
+        attempts.record_failure(1_010);
+        assert!(attempts.limit_reached(3));
+        assert_eq!(attempts.wait_seconds(1_010, 3, 900), 900);
+        assert_eq!(attempts.wait_seconds(1_910, 3, 900), 0);
+        // Without a limit only the back-off applies
+        assert_eq!(attempts.wait_seconds(1_010, 0, 900), 4);
+
+        attempts.failed = 40;
+        assert_eq!(attempts.backoff_seconds(), MAX_BACKOFF_SECONDS);
+    }
+
+    #[test]
+    fn test_attempts_are_persisted() {
+        let path =
+            std::env::temp_dir().join(format!("toolbox-attempts-{}.json", std::process::id()));
+        let path = path.to_str().expect("temp path is not UTF-8").to_string();
+        let _ = UnlockAttempts::reset(&path);
+        assert_eq!(UnlockAttempts::load(&path), UnlockAttempts::default());
+
+        let mut attempts = UnlockAttempts::default();
+        attempts.record_failure(42);
+        attempts.save(&path).expect("Save failed");
+        assert_eq!(UnlockAttempts::load(&path), attempts);
+
+        fs::write(UnlockAttempts::path(&path), "garbage").expect("Write failed");
+        assert_eq!(UnlockAttempts::load(&path).failed, 1);
+
+        UnlockAttempts::reset(&path).expect("Reset failed");
+        assert_eq!(UnlockAttempts::load(&path).failed, 0);
+    }
+}
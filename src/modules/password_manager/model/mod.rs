@@ -1,193 +1,1088 @@
-use aes::Aes256;
-use base64::{engine::general_purpose, Engine as _};
-use cbc::{Decryptor, Encryptor};
-use cipher::{block_padding::Pkcs7, KeyIvInit};
-use cipher::{BlockDecryptMut, BlockEncryptMut};
-use rand::{thread_rng, RngCore};
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use std::{fs, io};
-use dotenv::dotenv;
-use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use zeroize::Zeroizing;
 
-const FILE_PATH: &str = "passwords.json";
+use crate::core::SecretString;
 
-#[derive(Serialize, Deserialize, Clone)]
+pub mod audit;
+pub mod clipboard;
+pub mod crypto;
+pub mod export;
+pub mod format;
+pub mod generator;
+pub mod import;
+pub mod integrity;
+pub mod lockout;
+pub mod search;
+pub mod share;
+pub mod ssh;
+pub mod strength;
+pub mod totp;
+pub mod vaults;
+
+use crypto::{decrypt_cbc, derive_key, key_check, legacy_env_key, KdfParams};
+use format::{read_vault, write_atomic, write_temporary, StoredVault, VaultFile, VaultHeader};
+use integrity::take_snapshot;
+use share::{identity_path, open_identity, seal_identity, Identity};
+use totp::TotpConfig;
+
+/// Vault file used before named vaults; it is moved into the vault directory as `default`
+pub const FILE_PATH: &str = "passwords.json";
+
+/// What a vault item stores
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    /// Username and password for a service
+    #[default]
+    Login,
+    /// Free-form text kept in `notes`
+    SecureNote,
+    /// SSH private key; `password` holds its passphrase, if it has one
+    SshKey,
+}
+
+impl EntryKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            EntryKind::Login => "Login",
+            EntryKind::SecureNote => "Secure note",
+            EntryKind::SshKey => "SSH key",
+        }
+    }
+
+    /// Identifier used in the vault file and CSV exports
+    pub fn id(self) -> &'static str {
+        match self {
+            EntryKind::Login => "login",
+            EntryKind::SecureNote => "secure_note",
+            EntryKind::SshKey => "ssh_key",
+        }
+    }
+
+    /// Reads an [`id`](Self::id), a display name or another manager's type name
+    /// ("note"); anything else is a login
+    pub fn from_id(id: &str) -> Self {
+        match id.trim().to_lowercase().replace([' ', '-'], "_").as_str() {
+            "secure_note" | "securenote" | "note" => EntryKind::SecureNote,
+            "ssh_key" | "sshkey" => EntryKind::SshKey,
+            _ => EntryKind::Login,
+        }
+    }
+
+    fn is_login(&self) -> bool {
+        *self == EntryKind::Login
+    }
+}
+
+/// An item in the vault: a login, a secure note or an SSH key
+///
+/// `service` is the item's title for every kind.
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct PasswordEntry {
+    /// Entries saved before typed items are logins
+    #[serde(default, skip_serializing_if = "EntryKind::is_login")]
+    pub kind: EntryKind,
     pub service: String,
     pub username: String,
-    pub password: String,
+    pub password: SecretString,
+    /// 2FA seed for entries that also need a one-time code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp: Option<TotpConfig>,
+    /// Unix timestamp of the last password change (unknown for entries saved before it was tracked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_changed_at: Option<i64>,
+    /// Login page of the service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Free-form text; the content of a secure note
+    #[serde(default, skip_serializing_if = "SecretString::is_empty")]
+    pub notes: SecretString,
+    /// Labels used to filter the list; a `/` nests a tag like a folder (`work/email`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_fields: Vec<CustomField>,
+    /// Unix timestamps of creation and last edit (unknown for entries saved before they were tracked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified_at: Option<i64>,
+    /// Previous passwords, newest first (stored inside the encrypted vault like everything else)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<PasswordHistoryEntry>,
+    /// Private key of an SSH key item, in OpenSSH or PEM format
+    #[serde(default, skip_serializing_if = "SecretString::is_empty")]
+    pub private_key: SecretString,
+    /// Public key line of an SSH key item (`ssh-ed25519 AAAA... comment`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+}
+
+// Entries are held in AppState, which derives Debug; keep the password out of it
+impl fmt::Debug for PasswordEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordEntry")
+            .field("kind", &self.kind)
+            .field("service", &self.service)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("totp", &self.totp)
+            .field("password_changed_at", &self.password_changed_at)
+            .field("url", &self.url)
+            // Notes often hold recovery codes
+            .field("notes", &"<redacted>")
+            .field("tags", &self.tags)
+            .field("custom_fields", &self.custom_fields)
+            .field("created_at", &self.created_at)
+            .field("modified_at", &self.modified_at)
+            .field("history", &self.history)
+            .field("private_key", &self.private_key)
+            .field("public_key", &self.public_key)
+            .finish()
+    }
 }
 
-// errored here
-pub fn retrieve_password() -> io::Result<Vec<PasswordEntry>> {
-    let entries = load_passwords()?;
-    Ok(entries)
+impl PasswordEntry {
+    /// True if the entry has `tag` or a tag nested below it (`work` matches `work/email`)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| {
+            own.eq_ignore_ascii_case(tag)
+                || own
+                    .get(..tag.len() + 1)
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}/", tag)))
+        })
+    }
 }
 
-pub fn save_password(entry: &PasswordEntry) -> io::Result<()> {
-    let mut entries = load_passwords().or_else(|e| {
-        println!("Error loading existing passwords: {}", e);
-        // Explicitly specify the type of Vec::new() as Vec<PasswordEntry>
-        Ok::<Vec<PasswordEntry>, io::Error>(Vec::new())
-    })?;
+/// A password an entry used before it was changed
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PasswordHistoryEntry {
+    pub password: SecretString,
+    /// When this password was set (unknown for entries saved before it was tracked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_at: Option<i64>,
+    /// When it was replaced
+    pub replaced_at: i64,
+}
 
-    entries.push(entry.clone()); // Clone the entry to own it
-    let json = serde_json::to_string(&entries).map_err(|_| {
-        io::Error::new(
-            io::ErrorKind::Other,
-            "Failed to serialize passwords to JSON",
-        )
-    })?;
+impl fmt::Debug for PasswordHistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PasswordHistoryEntry")
+            .field("password", &"<redacted>")
+            .field("set_at", &self.set_at)
+            .field("replaced_at", &self.replaced_at)
+            .finish()
+    }
+}
 
-    let encrypted = encrypt(json.as_bytes())?;
-    fs::write(FILE_PATH, &encrypted)
-        .map_err(|_| io::Error::new(io::ErrorKind::Other, "Failed to write to passwords.json"))?;
+/// Extra key/value data kept with an entry (PINs, security questions, account numbers)
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct CustomField {
+    pub name: String,
+    pub value: SecretString,
+    /// Masked in the list like a password
+    #[serde(default)]
+    pub secret: bool,
+}
 
-    Ok(())
+impl fmt::Debug for CustomField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value: &dyn fmt::Debug = if self.secret { &"<redacted>" } else { &self.value.as_str() };
+        f.debug_struct("CustomField")
+            .field("name", &self.name)
+            .field("value", value)
+            .field("secret", &self.secret)
+            .finish()
+    }
 }
 
-fn load_passwords() -> io::Result<Vec<PasswordEntry>> {
-    if let Ok(encrypted) = fs::read(FILE_PATH) {
-        if encrypted.is_empty() {
-            // Treat an empty file as having no passwords
-            return Ok(Vec::<PasswordEntry>::new());
+/// Sets a new password, moving the current one into the history
+///
+/// Password age drives the rotation warnings in the security audit, so it is only
+/// reset when the value actually changes.
+fn change_password(
+    entry: &mut PasswordEntry,
+    password: SecretString,
+    now: i64,
+    history_depth: usize,
+) {
+    if entry.password != password {
+        let old = PasswordHistoryEntry {
+            password: std::mem::replace(&mut entry.password, password),
+            set_at: entry.password_changed_at,
+            replaced_at: now,
+        };
+        entry.history.insert(0, old);
+        entry.password_changed_at = Some(now);
+    }
+    entry.history.truncate(history_depth);
+}
+
+/// Splits a comma-separated tag list, dropping blanks and repeats
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split(',').map(|tag| tag.trim().trim_matches('/')) {
+        if !tag.is_empty() && !tags.iter().any(|own| own.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
         }
-        match decrypt(&encrypted) {
-            Ok(decrypted) => {
-                let entries: Vec<PasswordEntry> =
-                    serde_json::from_slice(&decrypted).map_err(|_| {
-                        io::Error::new(io::ErrorKind::InvalidData, "JSON deserialization failed")
-                    })?;
-                Ok(entries)
+    }
+    tags
+}
+
+/// Parses custom fields written one per line as `name=value`; `!name=value` marks a secret
+pub fn parse_custom_fields(input: &str) -> Result<Vec<CustomField>, String> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Custom field '{}' must be written as name=value", line))?;
+            let (name, secret) = match name.trim().strip_prefix('!') {
+                Some(name) => (name.trim(), true),
+                None => (name.trim(), false),
+            };
+            if name.is_empty() {
+                return Err(format!("Custom field '{}' has no name", line));
             }
-            Err(_) => {
-                // Handle decryption error
-                Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "Failed to decrypt existing passwords. The data might be corrupted.",
-                ))
+            Ok(CustomField {
+                name: name.to_string(),
+                value: value.trim().into(),
+                secret,
+            })
+        })
+        .collect()
+}
+
+/// Inverse of [`parse_custom_fields`], used to prefill the edit form
+pub fn format_custom_fields(fields: &[CustomField]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            format!(
+                "{}{}={}",
+                if field.secret { "!" } else { "" },
+                field.name,
+                field.value.as_str()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every tag in use plus the folders above nested tags, sorted for the filter menu
+pub fn all_tags(entries: &[PasswordEntry]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in entries.iter().flat_map(|entry| &entry.tags) {
+        let mut end = 0;
+        // "work/email" contributes "work" and "work/email"
+        for part in tag.split('/') {
+            end += part.len();
+            let folder = &tag[..end];
+            if !tags.iter().any(|own| own.eq_ignore_ascii_case(folder)) {
+                tags.push(folder.to_string());
             }
+            end += 1;
         }
-    } else {
-        // File does not exist
-        Ok(Vec::<PasswordEntry>::new())
     }
+    tags.sort_by_key(|tag| tag.to_lowercase());
+    tags
 }
 
-fn encrypt(data: &[u8]) -> io::Result<Vec<u8>> {
-    let key = get_encryption_key().map_err(|_| {
-        io::Error::new(io::ErrorKind::InvalidInput, "Failed to get encryption key")
-    })?;
-    if key.len() != 32 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "Key must be 32 bytes long",
-        ));
+/// Finds another entry for the same service (case-insensitive)
+///
+/// `skip` excludes the entry currently being edited from the comparison.
+pub fn find_duplicate_service(
+    entries: &[PasswordEntry],
+    service: &str,
+    skip: Option<usize>,
+) -> Option<usize> {
+    let service = service.trim().to_lowercase();
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| Some(*i) != skip)
+        .find(|(_, entry)| entry.service.trim().to_lowercase() == service)
+        .map(|(i, _)| i)
+}
+
+#[derive(Debug)]
+pub enum VaultError {
+    Io(io::Error),
+    /// The master password does not match the vault header
+    WrongPassword,
+    /// The key is right but the header or ciphertext failed authentication
+    Tampered,
+    /// Container version this build does not understand
+    UnsupportedVersion(u32),
+    /// File or decrypted payload is not a valid vault
+    Malformed(String),
+    /// `.env` ENCRYPTION_KEY is missing or does not open a legacy vault
+    LegacyKey(String),
+    AlreadyExists,
+    /// No entry at the given index
+    EntryNotFound(usize),
+    /// The entry has no previous password at the given index
+    HistoryNotFound(usize),
+    /// The vault name cannot be used as a file name
+    InvalidName(String),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Io(e) => write!(f, "IO error: {}", e),
+            VaultError::WrongPassword => write!(f, "Incorrect master password"),
+            VaultError::Tampered => write!(
+                f,
+                "Vault integrity check failed: the file has been modified or corrupted"
+            ),
+            VaultError::UnsupportedVersion(v) => {
+                write!(f, "Unsupported vault version {} (written by a newer toolbox?)", v)
+            }
+            VaultError::Malformed(msg) => write!(f, "Vault file is not valid: {}", msg),
+            VaultError::LegacyKey(msg) => write!(f, "Legacy vault migration failed: {}", msg),
+            VaultError::AlreadyExists => write!(f, "A vault already exists at this location"),
+            VaultError::EntryNotFound(index) => write!(f, "No password entry at position {}", index),
+            VaultError::HistoryNotFound(index) => {
+                write!(f, "No previous password at position {}", index)
+            }
+            VaultError::InvalidName(msg) => write!(f, "Invalid vault name: {}", msg),
+        }
     }
+}
 
-    let mut iv = [0u8; 16];
-    thread_rng().fill_bytes(&mut iv);
+impl std::error::Error for VaultError {}
 
-    let cipher = Encryptor::<Aes256>::new_from_slices(&key, &iv)
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Invalid key or IV length"))?;
+impl From<io::Error> for VaultError {
+    fn from(e: io::Error) -> Self {
+        VaultError::Io(e)
+    }
+}
 
-    // Prepare output buffer with sufficient capacity
-    let mut encrypted = vec![0u8; data.len() + 16]; // Adjust size as needed
+/// What the unlock screen has to ask for before a vault can be opened
+#[derive(Debug, Clone, PartialEq)]
+pub enum VaultStatus {
+    /// No vault exists yet — a new master password must be chosen
+    Missing,
+    /// Vault was written with the old `.env` ENCRYPTION_KEY and must be migrated
+    Legacy,
+    /// Vault is protected by a master password
+    Protected,
+}
 
-    // Encrypt data and apply padding
-    let ciphertext = cipher
-        .encrypt_padded_b2b_mut::<Pkcs7>(data, &mut encrypted) // Pass `data` directly
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("Encryption failed: {:?}", e)))?;
+/// Inspects the vault file without decrypting it
+///
+/// Unreadable files report `Protected` so that the unlock attempt surfaces the actual error.
+pub fn vault_status(file_path: &str) -> VaultStatus {
+    match read_vault(file_path) {
+        Ok(StoredVault::Empty) => VaultStatus::Missing,
+        Ok(StoredVault::EnvKeyed(_)) => VaultStatus::Legacy,
+        Ok(StoredVault::CbcV1(_)) | Ok(StoredVault::Aead(_)) | Err(_) => VaultStatus::Protected,
+    }
+}
 
-    // Prepend IV to the ciphertext for decryption
-    let mut result = iv.to_vec();
-    result.extend_from_slice(ciphertext);
+/// An unlocked password vault
+///
+/// Holds the key derived from the master password for as long as the vault is open;
+/// dropping the `Vault` locks it again.
+pub struct Vault {
+    file_path: String,
+    header: VaultHeader,
+    key: Zeroizing<Vec<u8>>,
+    /// Snapshots of the file kept before each rewrite (0 keeps none)
+    snapshot_limit: usize,
+}
 
-    // Encode the result in base64
-    let encoded = general_purpose::STANDARD.encode(&result);
-    Ok(encoded.into_bytes())
+// The derived key must never end up in logs or panic messages
+impl fmt::Debug for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Vault")
+            .field("file_path", &self.file_path)
+            .field("header", &self.header)
+            .field("snapshot_limit", &self.snapshot_limit)
+            .finish_non_exhaustive()
+    }
 }
 
-fn decrypt(data: &[u8]) -> io::Result<Vec<u8>> {
-    let key = get_encryption_key().map_err(|_| {
-        io::Error::new(io::ErrorKind::InvalidInput, "Failed to get encryption key")
-    })?;
-    // Decode base64
-    let decoded = general_purpose::STANDARD.decode(data).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("Base64 decoding failed: {:?}", e),
-        )
-    })?;
+impl Vault {
+    /// Creates a new, empty vault protected by `master_password`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a vault already exists at `file_path` or it cannot be written.
+    pub fn create(file_path: &str, master_password: &str) -> Result<Self, VaultError> {
+        if !matches!(read_vault(file_path)?, StoredVault::Empty) {
+            return Err(VaultError::AlreadyExists);
+        }
 
-    if decoded.len() < 16 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            "Invalid data length",
-        ));
+        let vault = Self::with_new_key(file_path, master_password)?;
+        vault.write_passwords(&[])?;
+        Ok(vault)
     }
 
-    // Split IV and ciphertext
-    let (iv, ciphertext) = decoded.split_at(16);
+    /// Opens an existing vault by deriving its key from `master_password`
+    ///
+    /// Version 1 (AES-256-CBC) vaults are rewritten in the authenticated format as
+    /// soon as they unlock. Like every other rewrite, that keeps `snapshot_limit`
+    /// snapshots, so the file as it was before the upgrade can be restored.
+    ///
+    /// # Errors
+    ///
+    /// `WrongPassword` if the master password is wrong, `Tampered` if the file fails
+    /// authentication, or `Malformed`/`UnsupportedVersion` if it is not a usable vault.
+    pub fn unlock(
+        file_path: &str,
+        master_password: &str,
+        snapshot_limit: usize,
+    ) -> Result<Self, VaultError> {
+        match read_vault(file_path)? {
+            StoredVault::Aead(vault_file) => {
+                let key = derive_key(master_password, &vault_file.header.kdf)?;
+                let mut vault = Self {
+                    file_path: file_path.to_string(),
+                    header: vault_file.header.clone(),
+                    key,
+                    snapshot_limit,
+                };
+                let entries = Self::parse_entries(&vault_file.open(&vault.key)?)?;
 
-    let cipher = Decryptor::<Aes256>::new_from_slices(&key, iv).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("Cipher creation failed: {:?}", e),
-        )
-    })?;
-
-    // Prepare buffer for decrypted data
-    let mut decrypted = vec![0u8; ciphertext.len()];
-    let decrypted_data = cipher
-        .decrypt_padded_b2b_mut::<Pkcs7>(ciphertext, &mut decrypted)
-        .map_err(|e| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("Decryption failed: {:?}", e),
-            )
+                // Vaults from before key ages were recorded start counting from now
+                if vault.header.key_created_at.is_none() {
+                    vault.header.key_created_at = Some(Utc::now().timestamp());
+                    vault.write_passwords(&entries)?;
+                }
+                Ok(vault)
+            }
+            StoredVault::CbcV1(legacy) => {
+                let key = derive_key(master_password, &legacy.header.kdf)?;
+                // CBC has no MAC, so a failed decrypt cannot be told apart from a wrong password
+                let decrypted = decrypt_cbc(legacy.data.as_bytes(), &key)
+                    .map_err(|_| VaultError::WrongPassword)?;
+                let entries =
+                    Self::parse_entries(&decrypted).map_err(|_| VaultError::WrongPassword)?;
+
+                let vault = Self {
+                    file_path: file_path.to_string(),
+                    header: VaultHeader::new(legacy.header.kdf, &key),
+                    key,
+                    snapshot_limit,
+                };
+                vault.write_passwords(&entries)?;
+                Ok(vault)
+            }
+            StoredVault::EnvKeyed(_) => Err(VaultError::LegacyKey(
+                "this vault still uses ENCRYPTION_KEY and must be migrated".to_string(),
+            )),
+            StoredVault::Empty => Err(VaultError::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                "No vault found",
+            ))),
+        }
+    }
+
+    /// Re-encrypts a vault written with the legacy `.env` ENCRYPTION_KEY under `master_password`
+    ///
+    /// The new file is written next to the old one and renamed over it, so an interrupted
+    /// migration leaves the legacy vault intact.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `ENCRYPTION_KEY` is not available or does not decrypt the vault.
+    pub fn migrate_legacy(file_path: &str, master_password: &str) -> Result<Self, VaultError> {
+        let StoredVault::EnvKeyed(encrypted) = read_vault(file_path)? else {
+            return Err(VaultError::LegacyKey(
+                "vault is not keyed by ENCRYPTION_KEY".to_string(),
+            ));
+        };
+
+        let legacy_key = legacy_env_key().map_err(|e| {
+            VaultError::LegacyKey(format!("ENCRYPTION_KEY is required to migrate this vault: {}", e))
+        })?;
+        let decrypted = decrypt_cbc(&encrypted, &legacy_key).map_err(|_| {
+            VaultError::LegacyKey("ENCRYPTION_KEY does not decrypt the existing vault".to_string())
         })?;
+        let entries = Self::parse_entries(&decrypted)?;
 
-    // Capture the decrypted length before mutable borrow
-    let decrypted_len = decrypted_data.len();
+        let vault = Self::with_new_key(file_path, master_password)?;
+        vault.write_passwords(&entries)?;
+        Ok(vault)
+    }
 
-    // Truncate buffer to actual decrypted data length
-    decrypted.truncate(decrypted_len);
+    /// Re-encrypts the whole vault under a key derived from `new_password`
+    ///
+    /// A fresh salt is generated even if the password stays the same, so this also
+    /// rotates the key. The new vault and sharing key files are written next to the old
+    /// ones and read back with the new key before either is renamed into place; on any
+    /// failure the old files are left (or put back) as they were and the vault stays
+    /// open with its old key.
+    ///
+    /// # Errors
+    ///
+    /// `WrongPassword` if `current_password` does not match, or the error that stopped
+    /// the new file from being written or verified.
+    pub fn change_master_password(
+        &mut self,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<(), VaultError> {
+        let current_key = derive_key(current_password, &self.header.kdf)?;
+        if key_check(&current_key) != self.header.key_check {
+            return Err(VaultError::WrongPassword);
+        }
 
-    Ok(decrypted)
-}
+        let entries = self.load_passwords()?;
+        let identity = self.stored_identity()?;
+        let rotated = Self::with_new_key(&self.file_path, new_password)?;
+        let contents = rotated.seal_passwords(&entries)?;
+
+        let tmp_path = write_temporary(&self.file_path, &contents)?;
+        let verify = || -> Result<(), VaultError> {
+            let not_read_back =
+                || VaultError::Malformed("re-encrypted vault did not read back".to_string());
+            let StoredVault::Aead(vault_file) = read_vault(&tmp_path)? else {
+                return Err(not_read_back());
+            };
+            let written = Self::parse_entries(&vault_file.open(&rotated.key)?)?;
+            if written.len() != entries.len() {
+                return Err(not_read_back());
+            }
+            Ok(())
+        };
+        if let Err(e) = verify() {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
 
-fn get_encryption_key() -> Result<Vec<u8>, Box<dyn std::error::Error>> {
-    dotenv().ok(); // Load environment variables from .env file
-    let key = env::var("ENCRYPTION_KEY")?;
-    if key.len() != 32 {
-        return Err("Key must be 32 bytes long".into());
+        // The sharing key pair is sealed with the vault key, so it moves to the new one too
+        let identity_tmp_path = match identity {
+            Some(ref identity) => {
+                let written = seal_identity(identity, &rotated.key).and_then(|sealed| {
+                    let path = write_temporary(&identity_path(&self.file_path), &sealed)?;
+                    open_identity(&fs::read(&path)?, &rotated.key)?;
+                    Ok(path)
+                });
+                match written {
+                    Ok(path) => Some(path),
+                    Err(e) => {
+                        let _ = fs::remove_file(&tmp_path);
+                        let _ = fs::remove_file(format!("{}.tmp", identity_path(&self.file_path)));
+                        return Err(e);
+                    }
+                }
+            }
+            None => None,
+        };
+        let remove_temporaries = || {
+            let _ = fs::remove_file(&tmp_path);
+            if let Some(ref path) = identity_tmp_path {
+                let _ = fs::remove_file(path);
+            }
+        };
+
+        // Kept in memory so the old vault can be put back if the key pair cannot follow it
+        let previous = match fs::read(&self.file_path) {
+            Ok(previous) => Zeroizing::new(previous),
+            Err(e) => {
+                remove_temporaries();
+                return Err(e.into());
+            }
+        };
+        if let Err(e) = take_snapshot(&self.file_path, self.snapshot_limit)
+            .and_then(|()| fs::rename(&tmp_path, &self.file_path))
+        {
+            remove_temporaries();
+            return Err(e.into());
+        }
+        if let Some(ref path) = identity_tmp_path {
+            if let Err(e) = fs::rename(path, identity_path(&self.file_path)) {
+                let _ = fs::remove_file(path);
+                write_atomic(&self.file_path, &previous)?;
+                return Err(e.into());
+            }
+        }
+
+        *self = Self {
+            snapshot_limit: self.snapshot_limit,
+            ..rotated
+        };
+        Ok(())
+    }
+
+    /// Key pair other vaults share entries to, created the first time it is needed
+    ///
+    /// The secret key is kept in a file next to the vault, encrypted with the vault key.
+    pub fn sharing_identity(&self) -> Result<Identity, VaultError> {
+        if let Some(identity) = self.stored_identity()? {
+            return Ok(identity);
+        }
+        let identity = Identity::generate();
+        write_atomic(
+            &identity_path(&self.file_path),
+            &seal_identity(&identity, &self.key)?,
+        )?;
+        Ok(identity)
+    }
+
+    fn stored_identity(&self) -> Result<Option<Identity>, VaultError> {
+        match fs::read(identity_path(&self.file_path)) {
+            Ok(contents) => open_identity(&contents, &self.key).map(Some),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Keeps the `limit` most recent copies of the vault file, taken before each rewrite
+    pub fn set_snapshot_limit(&mut self, limit: usize) {
+        self.snapshot_limit = limit;
     }
-    Ok(key.into_bytes())
-}
 
-// Use this function to get the key where needed
+    /// Whole days since the vault key was derived
+    pub fn key_age_days(&self, now: i64) -> Option<i64> {
+        self.header
+            .key_created_at
+            .map(|created_at| (now - created_at).max(0) / 86_400)
+    }
+
+    pub fn retrieve_password(&self) -> Result<Vec<PasswordEntry>, VaultError> {
+        let entries = self.load_passwords()?;
+        Ok(entries)
+    }
+
+    pub fn save_password(&self, entry: &PasswordEntry) -> Result<(), VaultError> {
+        self.save_passwords(std::slice::from_ref(entry))
+    }
+
+    /// Appends several entries with a single rewrite of the vault (used by imports)
+    pub fn save_passwords(&self, new_entries: &[PasswordEntry]) -> Result<(), VaultError> {
+        // A vault that cannot be read must fail loudly, never be replaced by the new entries
+        let mut entries = self.load_passwords()?;
+
+        let now = Utc::now().timestamp();
+        entries.extend(new_entries.iter().map(|entry| PasswordEntry {
+            password_changed_at: entry.password_changed_at.or(Some(now)),
+            created_at: entry.created_at.or(Some(now)),
+            modified_at: entry.modified_at.or(Some(now)),
+            ..entry.clone()
+        }));
+        self.write_passwords(&entries)
+    }
+
+    /// Replaces the entry at `index` and rewrites the vault
+    ///
+    /// If the password changed, the old one is kept in the entry's history, which is
+    /// trimmed to the `history_depth` most recent passwords.
+    pub fn update_password(
+        &self,
+        index: usize,
+        entry: &PasswordEntry,
+        history_depth: usize,
+    ) -> Result<(), VaultError> {
+        let mut entries = self.load_passwords()?;
+        let slot = entries
+            .get_mut(index)
+            .ok_or(VaultError::EntryNotFound(index))?;
+
+        let now = Utc::now().timestamp();
+        let mut updated = PasswordEntry {
+            password: slot.password.clone(),
+            password_changed_at: slot.password_changed_at,
+            created_at: slot.created_at,
+            modified_at: Some(now),
+            history: std::mem::take(&mut slot.history),
+            ..entry.clone()
+        };
+        change_password(&mut updated, entry.password.clone(), now, history_depth);
+        *slot = updated;
+        self.write_passwords(&entries)
+    }
+
+    /// Makes the previous password at `history_index` current again
+    ///
+    /// The password being replaced moves into the history, so a restore can be undone.
+    pub fn restore_password(
+        &self,
+        index: usize,
+        history_index: usize,
+        history_depth: usize,
+    ) -> Result<(), VaultError> {
+        let mut entries = self.load_passwords()?;
+        let slot = entries
+            .get_mut(index)
+            .ok_or(VaultError::EntryNotFound(index))?;
+        if history_index >= slot.history.len() {
+            return Err(VaultError::HistoryNotFound(history_index));
+        }
+
+        let now = Utc::now().timestamp();
+        let restored = slot.history.remove(history_index);
+        change_password(slot, restored.password, now, history_depth);
+        slot.modified_at = Some(now);
+        self.write_passwords(&entries)
+    }
+
+    /// Removes the entry at `index` and rewrites the vault, returning the removed entry
+    pub fn delete_password(&self, index: usize) -> Result<PasswordEntry, VaultError> {
+        let mut entries = self.load_passwords()?;
+        if index >= entries.len() {
+            return Err(VaultError::EntryNotFound(index));
+        }
+        let removed = entries.remove(index);
+        self.write_passwords(&entries)?;
+        Ok(removed)
+    }
+
+    fn with_new_key(file_path: &str, master_password: &str) -> Result<Self, VaultError> {
+        let kdf = KdfParams::generate();
+        let key = derive_key(master_password, &kdf)?;
+
+        Ok(Self {
+            file_path: file_path.to_string(),
+            header: VaultHeader::new(kdf, &key),
+            key,
+            snapshot_limit: 0,
+        })
+    }
+
+    fn parse_entries(decrypted: &[u8]) -> Result<Vec<PasswordEntry>, VaultError> {
+        serde_json::from_slice(decrypted)
+            .map_err(|_| VaultError::Malformed("JSON deserialization failed".to_string()))
+    }
+
+    fn load_passwords(&self) -> Result<Vec<PasswordEntry>, VaultError> {
+        match read_vault(&self.file_path)? {
+            StoredVault::Aead(vault_file) => Self::parse_entries(&vault_file.open(&self.key)?),
+            _ => Err(VaultError::Malformed(
+                "Vault file was replaced by a different format".to_string(),
+            )),
+        }
+    }
+
+    /// Serializes and encrypts `entries` into the contents of a vault file
+    fn seal_passwords(&self, entries: &[PasswordEntry]) -> Result<Vec<u8>, VaultError> {
+        let json = Zeroizing::new(
+            serde_json::to_vec(entries)
+                .map_err(|_| io::Error::other("Failed to serialize passwords to JSON"))?,
+        );
+
+        let vault_file = VaultFile::seal(&self.header, &json, &self.key)?;
+        Ok(serde_json::to_vec(&vault_file)
+            .map_err(|_| io::Error::other("Failed to serialize vault file"))?)
+    }
+
+    /// Encrypts `entries`, snapshots the current file, then atomically replaces it
+    fn write_passwords(&self, entries: &[PasswordEntry]) -> Result<(), VaultError> {
+        let contents = self.seal_passwords(entries)?;
+        take_snapshot(&self.file_path, self.snapshot_limit)?;
+        write_atomic(&self.file_path, &contents)?;
+        Ok(())
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs;
+
+    /// Cheap Argon2 costs so tests don't spend seconds in key derivation
+    fn test_kdf() -> KdfParams {
+        KdfParams {
+            memory_kib: 64,
+            iterations: 1,
+            ..KdfParams::generate()
+        }
+    }
+
+    pub(super) fn test_vault(name: &str, master_password: &str) -> Vault {
+        let path = std::env::temp_dir().join(format!("toolbox-{}-{}.json", name, std::process::id()));
+        let path = path.to_str().expect("temp path is not UTF-8").to_string();
+        let _ = fs::remove_file(&path);
+
+        let kdf = test_kdf();
+        let key = derive_key(master_password, &kdf).expect("Key derivation failed");
+        let vault = Vault {
+            file_path: path,
+            header: VaultHeader::new(kdf, &key),
+            key,
+            snapshot_limit: 0,
+        };
+        vault.write_passwords(&[]).expect("Write failed");
+        vault
+    }
 
     #[test]
     fn test_encrypt_decrypt() {
         let original_data = b"Test data for encryption";
+        let key = derive_key("correct horse battery staple", &test_kdf())
+            .expect("Key derivation failed");
 
-        // Encrypt the data
-        let encrypted = match encrypt(original_data) { // Remove the `key` argument
-            Ok(data) => data,
+        let (nonce, ciphertext) = match crypto::seal(original_data, &key, b"header") {
+            Ok(sealed) => sealed,
             Err(e) => panic!("Encryption failed with error: {}", e),
         };
 
-        // Decrypt the data
-        let decrypted = match decrypt(&encrypted) { // Remove the `key` argument
-            Ok(data) => data,
-            Err(e) => panic!("Decryption failed with error: {}", e),
+        let decrypted = crypto::open(&ciphertext, &nonce, &key, b"header")
+            .expect("Decryption failed");
+        assert_eq!(original_data.to_vec(), *decrypted);
+
+        assert!(crypto::open(&ciphertext, &nonce, &key, b"other header").is_none());
+    }
+
+    #[test]
+    fn test_unlock_rejects_wrong_master_password() {
+        let vault = test_vault("wrong-password", "right");
+        vault
+            .save_password(&PasswordEntry {
+                service: "example.com".to_string(),
+                username: "alice".to_string(),
+                password: "hunter2".into(),
+                ..Default::default()
+            })
+            .expect("Save failed");
+
+        assert_eq!(vault_status(&vault.file_path), VaultStatus::Protected);
+        let err = Vault::unlock(&vault.file_path, "wrong", 0).expect_err("Wrong password unlocked the vault");
+        assert!(matches!(err, VaultError::WrongPassword));
+
+        let reopened = Vault::unlock(&vault.file_path, "right", 0).expect("Unlock failed");
+        assert_eq!(reopened.retrieve_password().expect("Load failed").len(), 1);
+
+        let _ = fs::remove_file(&vault.file_path);
+    }
+
+    #[test]
+    fn test_update_and_delete_rewrite_vault() {
+        let vault = test_vault("update-delete", "right");
+        for service in ["github.com", "GitHub.com ", "gitlab.com"] {
+            vault
+                .save_password(&PasswordEntry {
+                    service: service.to_string(),
+                    username: "alice".to_string(),
+                    password: "hunter2".into(),
+                    ..Default::default()
+                })
+                .expect("Save failed");
+        }
+
+        let entries = vault.retrieve_password().expect("Load failed");
+        assert_eq!(find_duplicate_service(&entries, "github.com", None), Some(0));
+        assert_eq!(find_duplicate_service(&entries, "github.com", Some(0)), Some(1));
+        assert_eq!(find_duplicate_service(&entries, "gitlab.com", Some(2)), None);
+
+        let mut edited = entries[2].clone();
+        edited.password = "changed".into();
+        vault.update_password(2, &edited, 5).expect("Update failed");
+        let removed = vault.delete_password(1).expect("Delete failed");
+        assert_eq!(removed.service, "GitHub.com ");
+
+        let entries = vault.retrieve_password().expect("Load failed");
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].password, "changed");
+        assert!(matches!(vault.delete_password(5), Err(VaultError::EntryNotFound(5))));
+
+        let _ = fs::remove_file(&vault.file_path);
+    }
+
+    #[test]
+    fn test_old_entries_load_and_new_fields_round_trip() {
+        // Entries written before URLs, notes, tags and timestamps existed
+        let old: Vec<PasswordEntry> = serde_json::from_str(
+            r#"[{"service":"example.com","username":"alice","password":"hunter2"}]"#,
+        )
+        .expect("Old entry did not load");
+        assert!(old[0].tags.is_empty() && old[0].created_at.is_none());
+
+        let vault = test_vault("rich-entries", "right");
+        vault
+            .save_password(&PasswordEntry {
+                url: Some("https://example.com/login".to_string()),
+                notes: "line one\nline two".into(),
+                tags: parse_tags("work/email, Personal, work/email,"),
+                custom_fields: parse_custom_fields("PIN=1234\n!Recovery code = abc-def\n")
+                    .expect("Fields did not parse"),
+                ..old[0].clone()
+            })
+            .expect("Save failed");
+
+        let entry = &vault.retrieve_password().expect("Load failed")[0];
+        assert_eq!(entry.tags, ["work/email", "Personal"]);
+        assert!(entry.has_tag("work") && entry.has_tag("WORK/email") && !entry.has_tag("wor"));
+        assert_eq!(all_tags(std::slice::from_ref(entry)), ["Personal", "work", "work/email"]);
+        assert_eq!(entry.custom_fields[1].value, "abc-def");
+        assert!(entry.custom_fields[1].secret);
+        assert_eq!(
+            format_custom_fields(&entry.custom_fields),
+            "PIN=1234\n!Recovery code=abc-def"
+        );
+        assert!(!format!("{:?}", entry).contains("abc-def"));
+        assert!(parse_custom_fields("no separator").is_err());
+
+        let created = entry.created_at.expect("Creation time not set");
+        vault.update_password(0, entry, 5).expect("Update failed");
+        let updated = &vault.retrieve_password().expect("Load failed")[0];
+        assert_eq!(updated.created_at, Some(created));
+        assert!(updated.modified_at >= Some(created));
+
+        let _ = fs::remove_file(&vault.file_path);
+    }
+
+    #[test]
+    fn test_password_history_and_restore() {
+        let vault = test_vault("history", "right");
+        let mut entry = PasswordEntry {
+            service: "example.com".to_string(),
+            password: "first".into(),
+            ..Default::default()
         };
+        vault.save_password(&entry).expect("Save failed");
+        for password in ["second", "third", "fourth"] {
+            entry.password = password.into();
+            vault.update_password(0, &entry, 2).expect("Update failed");
+        }
+        // Saving without a new password leaves the history alone
+        vault.update_password(0, &entry, 2).expect("Update failed");
+
+        let stored = &vault.retrieve_password().expect("Load failed")[0];
+        let history: Vec<&str> = stored.history.iter().map(|old| old.password.as_str()).collect();
+        assert_eq!(history, ["third", "second"]);
+        assert!(stored.history[0].set_at.is_some());
+
+        vault.restore_password(0, 1, 2).expect("Restore failed");
+        let stored = &vault.retrieve_password().expect("Load failed")[0];
+        assert_eq!(stored.password, "second");
+        let history: Vec<&str> = stored.history.iter().map(|old| old.password.as_str()).collect();
+        assert_eq!(history, ["fourth", "third"]);
+        assert!(matches!(
+            vault.restore_password(0, 2, 2),
+            Err(VaultError::HistoryNotFound(2))
+        ));
+
+        let _ = fs::remove_file(&vault.file_path);
+    }
+
+    #[test]
+    fn test_change_master_password_reencrypts_vault() {
+        let mut vault = test_vault("rotate", "old");
+        // Headers written before key ages were recorded still authenticate
+        vault.header.key_created_at = None;
+        vault
+            .save_password(&PasswordEntry {
+                service: "example.com".to_string(),
+                password: "hunter2".into(),
+                ..Default::default()
+            })
+            .expect("Save failed");
+        let reopened = Vault::unlock(&vault.file_path, "old", 0).expect("Unlock failed");
+        assert_eq!(reopened.key_age_days(Utc::now().timestamp()), Some(0));
+
+        let recipient = vault.sharing_identity().unwrap().recipient();
+        let before = fs::read(&vault.file_path).expect("Read failed");
+        assert!(matches!(
+            vault.change_master_password("wrong", "new"),
+            Err(VaultError::WrongPassword)
+        ));
+        assert_eq!(fs::read(&vault.file_path).expect("Read failed"), before);
+
+        // A stale temporary file must not hand its permissions to the rotated vault
+        fs::write(format!("{}.tmp", vault.file_path), b"stale").expect("Write failed");
+        vault.change_master_password("old", "new").expect("Rotation failed");
+        assert!(!std::path::Path::new(&format!("{}.tmp", vault.file_path)).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for path in [vault.file_path.clone(), identity_path(&vault.file_path)] {
+                let mode = fs::metadata(&path).expect("File missing").permissions().mode();
+                assert_eq!(mode & 0o777, 0o600, "{} is readable by others", path);
+            }
+        }
+        assert!(matches!(
+            Vault::unlock(&vault.file_path, "old", 0),
+            Err(VaultError::WrongPassword)
+        ));
+        let reopened = Vault::unlock(&vault.file_path, "new", 0).expect("Unlock failed");
+        assert_eq!(reopened.retrieve_password().expect("Load failed")[0].password, "hunter2");
+        assert_eq!(vault.retrieve_password().expect("Load failed").len(), 1);
+        // The sharing key pair survives the rotation
+        let identity = reopened.sharing_identity().expect("Sharing key lost");
+        assert_eq!(identity.recipient(), recipient);
+
+        let _ = fs::remove_file(&vault.file_path);
+        let _ = fs::remove_file(identity_path(&vault.file_path));
+    }
+
+    /// A vault written before master passwords: base64(IV || AES-256-CBC) under `.env` ENCRYPTION_KEY
+    const ENV_KEYED_FIXTURE: &str = "BwcHBwcHBwcHBwcHBwcHB1qP0nrB3sR5PS78tv2DP2eHhdBAAUaw64U3EBU3cDAtILvDjqlduruarfXT86WSaAUPjc9UxayRKKeOiniqYqQJ76O4bXPSpML82OIZjEy2";
+
+    /// A version 1 vault (Argon2id key, unauthenticated CBC body) with the master password "right"
+    const CBC_V1_FIXTURE: &str = r#"{"header":{"kdf":{"algorithm":"argon2id","salt":"c2FsdHNhbHRzYWx0c2FsdA==","memory_kib":64,"iterations":1,"parallelism":1}},"data":"BwcHBwcHBwcHBwcHBwcHBwjIaJXoWIj7hjqh+Gsj7PWCgdYoDO5BtwJqCiSB523HYvmN0yZNxnvNsoLsQvUG9xo4qda32KwYaVUeOf63iOvcRkKknvPUmJX5rbeMicD3"}"#;
+
+    fn fixture_path(name: &str, contents: &str) -> String {
+        let path = std::env::temp_dir().join(format!("toolbox-{}-{}.json", name, std::process::id()));
+        fs::write(&path, contents).expect("Write failed");
+        path.to_str().expect("temp path is not UTF-8").to_string()
+    }
+
+    #[test]
+    fn test_env_keyed_vault_migrates_with_its_entries() {
+        let path = fixture_path("env-keyed", ENV_KEYED_FIXTURE);
+        assert_eq!(vault_status(&path), VaultStatus::Legacy);
+        assert!(matches!(Vault::unlock(&path, "new", 0), Err(VaultError::LegacyKey(_))));
+
+        std::env::set_var("ENCRYPTION_KEY", "0123456789abcdef0123456789abcdef");
+        let vault = Vault::migrate_legacy(&path, "new").expect("Migration failed");
+        assert_eq!(vault_status(&path), VaultStatus::Protected);
+
+        let reopened = Vault::unlock(&path, "new", 0).expect("Unlock failed");
+        let entries = reopened.retrieve_password().expect("Load failed");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].service, "example.com");
+        assert_eq!(entries[0].password, "hunter2");
+        assert_eq!(vault.retrieve_password().expect("Load failed").len(), 1);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_cbc_v1_vault_upgrades_to_gcm_on_unlock() {
+        let path = fixture_path("cbc-v1", CBC_V1_FIXTURE);
+        assert!(matches!(read_vault(&path), Ok(StoredVault::CbcV1(_))));
+        assert!(matches!(Vault::unlock(&path, "wrong", 0), Err(VaultError::WrongPassword)));
+        assert!(matches!(read_vault(&path), Ok(StoredVault::CbcV1(_))));
+
+        let vault = Vault::unlock(&path, "right", 1).expect("Unlock failed");
+        assert!(matches!(read_vault(&path), Ok(StoredVault::Aead(_))));
+        // The file as it was before the upgrade is kept as a snapshot
+        let snapshots = integrity::list_snapshots(&path).expect("Snapshots not listed");
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(fs::read_to_string(&snapshots[0].path).expect("Read failed"), CBC_V1_FIXTURE);
+        assert_eq!(vault.retrieve_password().expect("Load failed")[0].username, "alice");
+
+        let reopened = Vault::unlock(&path, "right", 0).expect("Upgraded vault did not unlock");
+        let entries = reopened.retrieve_password().expect("Load failed");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].password, "hunter2");
+
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_dir_all(integrity::snapshot_dir(&path));
+    }
+
+    #[test]
+    fn test_tampered_vault_is_reported() {
+        let vault = test_vault("tampered", "right");
+
+        let mut vault_file: serde_json::Value =
+            serde_json::from_slice(&fs::read(&vault.file_path).expect("Read failed"))
+                .expect("Vault is not JSON");
+        vault_file["header"]["kdf"]["iterations"] = serde_json::json!(2);
+        fs::write(&vault.file_path, serde_json::to_vec(&vault_file).expect("Serialize failed"))
+            .expect("Write failed");
+
+        assert!(matches!(vault.retrieve_password(), Err(VaultError::Tampered)));
 
-        assert_eq!(original_data.to_vec(), decrypted);
+        let _ = fs::remove_file(&vault.file_path);
     }
 }
@@ -0,0 +1,208 @@
+use super::PasswordEntry;
+
+// fzf-style scoring: every matched character earns points, gaps cost points, and
+// matches at the start of a word or right after the previous match earn a bonus
+const SCORE_MATCH: i64 = 16;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL_CASE: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+const FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// Where a fuzzy match was found in `text`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Char indices of the matched characters, used for highlighting
+    pub positions: Vec<usize>,
+}
+
+/// Matches `pattern` as a case-insensitive subsequence of `text`
+///
+/// Like fzf's fast path, the first occurrence is found scanning forward and then
+/// tightened scanning backward, so `gh` in `GitHub` matches `G` and `H` rather than
+/// spanning the whole string.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    // One char per text char so positions line up with `chars`
+    let lower: Vec<char> = chars
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+
+    let mut matched = 0;
+    let mut end = None;
+    for (i, &c) in lower.iter().enumerate() {
+        if c == pattern[matched] {
+            matched += 1;
+            if matched == pattern.len() {
+                end = Some(i);
+                break;
+            }
+        }
+    }
+
+    let mut positions = Vec::with_capacity(pattern.len());
+    let mut remaining = pattern.len();
+    for i in (0..=end?).rev() {
+        if lower[i] == pattern[remaining - 1] {
+            positions.push(i);
+            remaining -= 1;
+            if remaining == 0 {
+                break;
+            }
+        }
+    }
+    positions.reverse();
+
+    let mut score = 0;
+    let mut previous: Option<usize> = None;
+    for (n, &position) in positions.iter().enumerate() {
+        let mut bonus = boundary_bonus(&chars, position);
+        if let Some(previous) = previous {
+            if position == previous + 1 {
+                bonus = bonus.max(BONUS_CONSECUTIVE);
+            } else {
+                let gap = (position - previous - 1) as i64;
+                score -= PENALTY_GAP_START + PENALTY_GAP_EXTENSION * (gap - 1);
+            }
+        }
+        if n == 0 {
+            bonus *= FIRST_CHAR_MULTIPLIER;
+        }
+        score += SCORE_MATCH + bonus;
+        previous = Some(position);
+    }
+
+    Some(FuzzyMatch { score, positions })
+}
+
+fn boundary_bonus(chars: &[char], position: usize) -> i64 {
+    let current = chars[position];
+    match position.checked_sub(1).map(|before| chars[before]) {
+        None => BONUS_BOUNDARY,
+        Some(before) if !before.is_alphanumeric() => BONUS_BOUNDARY,
+        Some(before)
+            if (before.is_lowercase() && current.is_uppercase())
+                || (before.is_alphabetic() && current.is_numeric()) =>
+        {
+            BONUS_CAMEL_CASE
+        }
+        Some(_) => 0,
+    }
+}
+
+/// Entry field a search hit was found in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchField {
+    Service,
+    Username,
+    Url,
+    /// The tags joined with ", ", as the password list shows them
+    Tags,
+}
+
+/// An entry matching a search, with the best-scoring field
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// Position of the entry in the vault
+    pub index: usize,
+    pub score: i64,
+    pub field: SearchField,
+    pub positions: Vec<usize>,
+}
+
+/// Ranks the entries matching `query`, best first
+///
+/// Each entry is scored by its best field; on a tie the service name wins over the
+/// username, URL and tags.
+pub fn search_entries(entries: &[PasswordEntry], query: &str) -> Vec<SearchHit> {
+    let query = query.trim();
+    let mut hits: Vec<SearchHit> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let url = entry.url.clone().unwrap_or_default();
+            let tags = entry.tags.join(", ");
+            [
+                (SearchField::Service, entry.service.as_str()),
+                (SearchField::Username, entry.username.as_str()),
+                (SearchField::Url, url.as_str()),
+                (SearchField::Tags, tags.as_str()),
+            ]
+            .into_iter()
+            .filter_map(|(field, text)| {
+                fuzzy_match(query, text).map(|found| SearchHit {
+                    index,
+                    score: found.score,
+                    field,
+                    positions: found.positions,
+                })
+            })
+            .reduce(|best, hit| if hit.score > best.score { hit } else { best })
+        })
+        .collect();
+
+    // Stable sort keeps vault order among equal scores
+    hits.sort_by_key(|hit| -hit.score);
+    hits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(service: &str, username: &str, tags: &[&str]) -> PasswordEntry {
+        PasswordEntry {
+            service: service.to_string(),
+            username: username.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_match_positions() {
+        assert_eq!(
+            fuzzy_match("gthb", "GitHub").map(|found| found.positions),
+            Some(vec![0, 2, 3, 5])
+        );
+        // The backward pass picks the tightest window
+        assert_eq!(
+            fuzzy_match("ab", "a-xab").map(|found| found.positions),
+            Some(vec![3, 4])
+        );
+        assert_eq!(fuzzy_match("xyz", "GitHub"), None);
+        assert_eq!(fuzzy_match("", "GitHub").map(|found| found.score), Some(0));
+    }
+
+    #[test]
+    fn test_search_ranks_entries() {
+        let entries = vec![
+            entry("Digital Hub", "bob", &[]),
+            entry("gitlab.com", "alice", &["work"]),
+            entry("github.com", "alice", &[]),
+            entry("bank", "gh-user", &["finance"]),
+        ];
+
+        let hits = search_entries(&entries, "gh");
+        let order: Vec<usize> = hits.iter().map(|hit| hit.index).collect();
+        assert_eq!(order, [3, 2, 0]);
+        assert_eq!(hits[0].field, SearchField::Username);
+
+        let hits = search_entries(&entries, "wrk");
+        assert_eq!(hits.len(), 1);
+        assert_eq!((hits[0].index, hits[0].field), (1, SearchField::Tags));
+
+        assert_eq!(search_entries(&entries, "  ").len(), entries.len());
+    }
+}
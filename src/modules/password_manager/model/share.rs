@@ -0,0 +1,320 @@
+use age::secrecy::ExposeSecret;
+use age::{DecryptError, EncryptError};
+use base64::{engine::general_purpose, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::str::FromStr;
+use zeroize::Zeroizing;
+
+use super::crypto::{open, seal};
+use super::export::create_new;
+use super::{PasswordEntry, VaultError};
+
+// Entries are shared as ASCII-armored age v1 files (https://age-encryption.org/v1) with a
+// single X25519 recipient, so `age -d -i <identity>` can read them as well.
+
+/// Errors from sharing an entry or opening one shared with this vault
+#[derive(Debug)]
+pub enum ShareError {
+    Io(io::Error),
+    /// A public or secret key string that could not be decoded
+    InvalidKey(String),
+    /// The file is not an age file this version understands
+    Malformed(String),
+    /// None of the file's recipients is this vault's key
+    NotForThisKey,
+    /// The header MAC or the payload failed to authenticate
+    Tampered,
+    /// The entry could not be encrypted
+    Crypto(String),
+}
+
+impl fmt::Display for ShareError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShareError::Io(e) => write!(f, "I/O error: {}", e),
+            ShareError::InvalidKey(reason) => write!(f, "Invalid key: {}", reason),
+            ShareError::Malformed(reason) => write!(f, "Not a readable age file: {}", reason),
+            ShareError::NotForThisKey => {
+                write!(f, "The file was not encrypted to this vault's public key")
+            }
+            ShareError::Tampered => write!(f, "The file failed authentication"),
+            ShareError::Crypto(reason) => write!(f, "Cryptographic error: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ShareError {}
+
+impl From<io::Error> for ShareError {
+    fn from(error: io::Error) -> Self {
+        ShareError::Io(error)
+    }
+}
+
+impl From<EncryptError> for ShareError {
+    fn from(error: EncryptError) -> Self {
+        match error {
+            EncryptError::Io(e) => ShareError::Io(e),
+            e => ShareError::Crypto(e.to_string()),
+        }
+    }
+}
+
+impl From<DecryptError> for ShareError {
+    fn from(error: DecryptError) -> Self {
+        match error {
+            DecryptError::NoMatchingKeys => ShareError::NotForThisKey,
+            DecryptError::InvalidMac | DecryptError::DecryptionFailed => ShareError::Tampered,
+            // The payload is read from memory, so a failed read is a chunk that did not authenticate
+            DecryptError::Io(e) if e.kind() == io::ErrorKind::InvalidData => ShareError::Tampered,
+            DecryptError::Io(e) => ShareError::Io(e),
+            e => ShareError::Malformed(e.to_string()),
+        }
+    }
+}
+
+/// A public key entries can be shared to (`age1...`)
+#[derive(Clone, PartialEq)]
+pub struct Recipient(age::x25519::Recipient);
+
+impl Recipient {
+    pub fn parse(encoded: &str) -> Result<Self, ShareError> {
+        age::x25519::Recipient::from_str(encoded.trim())
+            .map(Self)
+            .map_err(|e| ShareError::InvalidKey(e.to_string()))
+    }
+}
+
+impl fmt::Display for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Debug for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Recipient({})", self.0)
+    }
+}
+
+/// The local key pair shared entries are encrypted to
+pub struct Identity(age::x25519::Identity);
+
+// The secret key must never end up in logs or panic messages
+impl fmt::Debug for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Identity({})", self.recipient())
+    }
+}
+
+impl Identity {
+    pub fn generate() -> Self {
+        Self(age::x25519::Identity::generate())
+    }
+
+    /// Parses an `AGE-SECRET-KEY-1...` string, as written by `age-keygen`
+    pub fn parse(encoded: &str) -> Result<Self, ShareError> {
+        age::x25519::Identity::from_str(encoded.trim())
+            .map(Self)
+            .map_err(|e| ShareError::InvalidKey(e.to_string()))
+    }
+
+    /// The public key teammates encrypt to
+    pub fn recipient(&self) -> Recipient {
+        Recipient(self.0.to_public())
+    }
+
+    /// The secret key in `age-keygen` format
+    pub fn to_secret_string(&self) -> Zeroizing<String> {
+        Zeroizing::new(self.0.to_string().expose_secret().to_string())
+    }
+}
+
+/// Encrypts `plaintext` to `recipient` as an ASCII-armored age file, which survives
+/// being pasted into chat
+pub fn encrypt(plaintext: &[u8], recipient: &Recipient) -> Result<String, ShareError> {
+    Ok(age::encrypt_and_armor(&recipient.0, plaintext)?)
+}
+
+/// Decrypts an age file (binary or ASCII-armored) with `identity`
+pub fn decrypt(data: &[u8], identity: &Identity) -> Result<Zeroizing<Vec<u8>>, ShareError> {
+    Ok(Zeroizing::new(age::decrypt(&identity.0, data)?))
+}
+
+/// Writes `entry` to a new file at `file_path`, encrypted to `recipient` and armored
+///
+/// The entry's password history stays behind; only the current values are shared.
+pub fn share_entry(
+    entry: &PasswordEntry,
+    recipient: &Recipient,
+    file_path: &str,
+) -> Result<(), ShareError> {
+    let shared = PasswordEntry {
+        history: Vec::new(),
+        ..entry.clone()
+    };
+    let json = Zeroizing::new(
+        serde_json::to_vec(&shared).map_err(|e| ShareError::Malformed(e.to_string()))?,
+    );
+    let armored = encrypt(&json, recipient)?;
+    let mut file = create_new(file_path)?;
+    file.write_all(armored.as_bytes())?;
+    Ok(())
+}
+
+/// Reads an entry shared to `identity` from `file_path`
+///
+/// Its timestamps are cleared so the entry counts as created when it is saved.
+pub fn open_shared_entry(
+    file_path: &str,
+    identity: &Identity,
+) -> Result<PasswordEntry, ShareError> {
+    let data = fs::read(file_path)?;
+    let json = decrypt(&data, identity)?;
+    let entry: PasswordEntry = serde_json::from_slice(&json)
+        .map_err(|_| ShareError::Malformed("the file does not hold a vault entry".to_string()))?;
+    Ok(PasswordEntry {
+        created_at: None,
+        modified_at: None,
+        ..entry
+    })
+}
+
+/// Path of the file holding the sharing key pair of the vault at `vault_path`
+pub fn identity_path(vault_path: &str) -> String {
+    format!("{}.identity", vault_path)
+}
+
+/// Sharing key pair as stored next to a vault, sealed with the vault key
+#[derive(Serialize, Deserialize)]
+struct StoredIdentity {
+    /// Public key, in clear so it can be shown without unlocking; bound as AAD
+    public_key: String,
+    /// Base64-encoded AES-GCM nonce
+    nonce: String,
+    /// Base64-encoded `AGE-SECRET-KEY-1...` string, encrypted with the vault key
+    ciphertext: String,
+}
+
+/// Encrypts `identity` with the vault `key` into the contents of an identity file
+pub(super) fn seal_identity(identity: &Identity, key: &[u8]) -> Result<Vec<u8>, VaultError> {
+    let public_key = identity.recipient().to_string();
+    let secret = identity.to_secret_string();
+    let (nonce, ciphertext) = seal(secret.as_bytes(), key, public_key.as_bytes())?;
+    let stored = StoredIdentity {
+        public_key,
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    };
+    serde_json::to_vec(&stored).map_err(|e| VaultError::Malformed(e.to_string()))
+}
+
+/// Decrypts an identity file written by [`seal_identity`]
+pub(super) fn open_identity(contents: &[u8], key: &[u8]) -> Result<Identity, VaultError> {
+    let stored: StoredIdentity =
+        serde_json::from_slice(contents).map_err(|e| VaultError::Malformed(e.to_string()))?;
+    let nonce = general_purpose::STANDARD
+        .decode(&stored.nonce)
+        .map_err(|_| VaultError::Tampered)?;
+    let ciphertext = general_purpose::STANDARD
+        .decode(&stored.ciphertext)
+        .map_err(|_| VaultError::Tampered)?;
+    let secret =
+        open(&ciphertext, &nonce, key, stored.public_key.as_bytes()).ok_or(VaultError::Tampered)?;
+    let secret = std::str::from_utf8(&secret).map_err(|_| VaultError::Tampered)?;
+    Identity::parse(secret).map_err(|e| VaultError::Malformed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+
+    /// The `armor` vector of the age testkit (https://c2sp.org/age), written by the
+    /// reference implementation; the payload is only known by its SHA-256
+    const TESTKIT_IDENTITY: &str =
+        "AGE-SECRET-KEY-1XMWWC06LY3EE5RYTXM9MFLAZ2U56JJJ36S0MYPDRWSVLUL66MV4QX3S7F6";
+    const TESTKIT_RECIPIENT: &str =
+        "age1w3tyke4gev25vaxxsvcgqu4484rf6ejpmavs57p6yz6lhy2sfs5swrvwyn";
+    const TESTKIT_FILE: &str = "-----BEGIN AGE ENCRYPTED FILE-----
+YWdlLWVuY3J5cHRpb24ub3JnL3YxCi0+IFgyNTUxOSBURWlGMHlwcXIrYnB2Y3FY
+TnlDVkpwTDdPdXdQZFZ3UEw3S1FFYkZET0NjCkVtRUNBRWNLTituL1ZzOVNiV2lW
+K0h1MHIrRThSNzdEZFdZeWQ4M253N1UKLS0tIFZuKzU0anFpaVVDRStXWmNFVlkz
+ZjFzcUhqbHUvejFMQ1EvVDdYbTdxSTAK7s9ix86RtDMnTmjU8vkTTLdMW/73vqpS
+yPC8DpksHoMx+2Y=
+-----END AGE ENCRYPTED FILE-----
+";
+    const TESTKIT_PAYLOAD_SHA256: &str =
+        "013f54400c82da08037759ada907a8b864e97de81c088a182062c4b5622fd2ab";
+
+    #[test]
+    fn test_decrypts_age_testkit_vector() {
+        let identity = Identity::parse(TESTKIT_IDENTITY).expect("Testkit identity rejected");
+        assert_eq!(identity.recipient().to_string(), TESTKIT_RECIPIENT);
+        assert_eq!(*identity.to_secret_string(), TESTKIT_IDENTITY);
+        assert_eq!(
+            Recipient::parse(TESTKIT_RECIPIENT).unwrap(),
+            identity.recipient()
+        );
+
+        let payload = decrypt(TESTKIT_FILE.as_bytes(), &identity).expect("Testkit file rejected");
+        let digest: String = Sha256::digest(&*payload)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        assert_eq!(digest, TESTKIT_PAYLOAD_SHA256);
+
+        assert!(matches!(
+            decrypt(TESTKIT_FILE.as_bytes(), &Identity::generate()),
+            Err(ShareError::NotForThisKey)
+        ));
+        assert!(Recipient::parse("age1notakey").is_err());
+    }
+
+    #[test]
+    fn test_entry_round_trips_to_its_recipient_only() {
+        let identity = Identity::generate();
+        let recipient = Recipient::parse(&identity.recipient().to_string()).unwrap();
+        let parsed = Identity::parse(&identity.to_secret_string()).unwrap();
+        assert_eq!(parsed.recipient(), recipient);
+
+        let path = std::env::temp_dir().join(format!("toolbox-share-{}.age", std::process::id()));
+        let path = path.to_str().expect("temp path is not UTF-8").to_string();
+        let _ = fs::remove_file(&path);
+        let entry = PasswordEntry {
+            service: "example.com".to_string(),
+            username: "alice".to_string(),
+            password: "shared-secret".into(),
+            ..Default::default()
+        };
+        share_entry(&entry, &recipient, &path).expect("Share failed");
+        assert!(
+            share_entry(&entry, &recipient, &path).is_err(),
+            "Existing file was overwritten"
+        );
+
+        let opened = open_shared_entry(&path, &identity).expect("Open failed");
+        assert_eq!(opened.password, "shared-secret");
+        let stranger = Identity::generate();
+        assert!(matches!(
+            open_shared_entry(&path, &stranger),
+            Err(ShareError::NotForThisKey)
+        ));
+
+        // Large payloads span several STREAM chunks, and flipped bits are caught
+        let large = vec![7u8; 64 * 1024 * 2 + 5];
+        let mut encrypted = age::encrypt(&recipient.0, &large).unwrap();
+        assert_eq!(*decrypt(&encrypted, &identity).unwrap(), large);
+        let last = encrypted.len() - 1;
+        encrypted[last] ^= 1;
+        assert!(matches!(
+            decrypt(&encrypted, &identity),
+            Err(ShareError::Tampered)
+        ));
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,455 @@
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// Most common leaked passwords, most frequent first (rank = position + 1)
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "2000",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "klaster",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "1111",
+    "zxcvbn",
+    "555555",
+    "11111111",
+    "131313",
+    "freedom",
+    "777777",
+    "pass",
+    "maggie",
+    "159753",
+    "aaaaaa",
+    "ginger",
+    "princess",
+    "joshua",
+    "cheese",
+    "amanda",
+    "summer",
+    "love",
+    "ashley",
+    "nicole",
+    "chelsea",
+    "biteme",
+    "matthew",
+    "access",
+    "yankees",
+    "987654321",
+    "dallas",
+    "austin",
+    "thunder",
+    "taylor",
+    "matrix",
+    "welcome",
+    "admin",
+    "passw0rd",
+    "login",
+    "secret",
+    "changeme",
+    "hello",
+    "whatever",
+    "qwerty123",
+    "password1",
+    "1q2w3e4r",
+    "football1",
+    "monkey1",
+    "letmein1",
+    "trustno",
+    "starwars1",
+    "dragon1",
+    "princess1",
+    "sunshine1",
+    "welcome1",
+];
+
+/// Bundled diceware wordlist doubles as a dictionary of common words
+static DICTIONARY: Lazy<HashMap<String, u64>> = Lazy::new(|| {
+    let mut ranks = HashMap::new();
+    for word in include_str!("wordlist.txt").lines() {
+        // Uniform rank: every word is one of 1296 equally likely choices
+        ranks.insert(word.to_string(), 1296);
+    }
+    for (index, password) in COMMON_PASSWORDS.iter().enumerate() {
+        ranks.insert(password.to_string(), index as u64 + 1);
+    }
+    ranks
+});
+
+/// Length in characters of the longest dictionary entry; longer substrings cannot match
+static LONGEST_WORD: Lazy<usize> = Lazy::new(|| {
+    DICTIONARY
+        .keys()
+        .map(|word| word.chars().count())
+        .max()
+        .unwrap_or(0)
+});
+
+const KEYBOARD_ROWS: &[&str] = &[
+    "`1234567890-=",
+    "qwertyuiop[]\\",
+    "asdfghjkl;'",
+    "zxcvbnm,./",
+];
+
+/// Guesses charged per character that is not part of any pattern (zxcvbn's value)
+const BRUTEFORCE_CARDINALITY: f64 = 10.0;
+
+/// Weak pattern found in a password, reported to explain a low score
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Weakness {
+    CommonPassword,
+    DictionaryWord,
+    Sequence,
+    Repeat,
+    KeyboardPattern,
+    Year,
+}
+
+impl Weakness {
+    pub fn description(self) -> &'static str {
+        match self {
+            Weakness::CommonPassword => "is a common password",
+            Weakness::DictionaryWord => "contains a dictionary word",
+            Weakness::Sequence => "contains a sequence like abc or 123",
+            Weakness::Repeat => "contains repeated characters",
+            Weakness::KeyboardPattern => "contains a keyboard pattern",
+            Weakness::Year => "contains a year",
+        }
+    }
+}
+
+/// Result of [`estimate`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Strength {
+    /// 0 (too guessable) to 4 (very unguessable), on zxcvbn's scale
+    pub score: u8,
+    /// log10 of the estimated number of guesses needed
+    pub guesses_log10: f64,
+    /// Patterns used in the cheapest way to guess the password
+    pub weaknesses: Vec<Weakness>,
+}
+
+impl Strength {
+    pub fn label(&self) -> &'static str {
+        match self.score {
+            0 => "very weak",
+            1 => "weak",
+            2 => "fair",
+            3 => "strong",
+            _ => "very strong",
+        }
+    }
+}
+
+/// A pattern covering `password[start..end]` (in chars) that takes `guesses` to find
+struct Match {
+    start: usize,
+    end: usize,
+    guesses: f64,
+    weakness: Weakness,
+}
+
+/// Estimates how many guesses an attacker needs, zxcvbn-style
+///
+/// The password is split into the cheapest combination of known patterns (dictionary
+/// words with l33t and capitalisation, sequences, repeats, keyboard runs, years) and
+/// brute-forced characters; the product of their guess counts is the estimate.
+pub fn estimate(password: &str) -> Strength {
+    let chars: Vec<char> = password.chars().collect();
+    if chars.is_empty() {
+        return Strength {
+            score: 0,
+            guesses_log10: 0.0,
+            weaknesses: Vec::new(),
+        };
+    }
+
+    let mut matches = Vec::new();
+    dictionary_matches(&chars, &mut matches);
+    sequence_matches(&chars, &mut matches);
+    repeat_matches(&chars, &mut matches);
+    keyboard_matches(&chars, &mut matches);
+    year_matches(&chars, &mut matches);
+
+    // best[i] = (log10 guesses for chars[..i], pattern used to reach i)
+    let mut best: Vec<(f64, Option<usize>)> = vec![(f64::INFINITY, None); chars.len() + 1];
+    best[0] = (0.0, None);
+    for end in 1..=chars.len() {
+        best[end] = (best[end - 1].0 + BRUTEFORCE_CARDINALITY.log10(), None);
+        for (index, m) in matches.iter().enumerate().filter(|(_, m)| m.end == end) {
+            let candidate = best[m.start].0 + m.guesses.max(1.0).log10();
+            if candidate < best[end].0 {
+                best[end] = (candidate, Some(index));
+            }
+        }
+    }
+
+    let mut weaknesses = Vec::new();
+    let mut position = chars.len();
+    while position > 0 {
+        match best[position].1 {
+            Some(index) => {
+                let m = &matches[index];
+                if !weaknesses.contains(&m.weakness) {
+                    weaknesses.push(m.weakness);
+                }
+                position = m.start;
+            }
+            None => position -= 1,
+        }
+    }
+
+    let guesses_log10 = best[chars.len()].0;
+    let score = match guesses_log10 {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    };
+
+    Strength {
+        score,
+        guesses_log10,
+        weaknesses,
+    }
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' | '!' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+fn dictionary_matches(chars: &[char], matches: &mut Vec<Match>) {
+    for start in 0..chars.len() {
+        for end in start + 3..=chars.len().min(start + *LONGEST_WORD) {
+            let token = &chars[start..end];
+            let lower: String = token.iter().flat_map(|c| c.to_lowercase()).collect();
+            let unleeted: String = lower.chars().map(unleet).collect();
+
+            let (rank, leet) = match (DICTIONARY.get(&lower), DICTIONARY.get(&unleeted)) {
+                (Some(rank), _) => (*rank, false),
+                (None, Some(rank)) => (*rank, true),
+                (None, None) => continue,
+            };
+
+            // Capitalisation and substitutions multiply the guesses, as in zxcvbn
+            let uppercase = token.iter().filter(|c| c.is_uppercase()).count();
+            let case_factor = match uppercase {
+                0 => 1.0,
+                1 if token[0].is_uppercase() => 2.0,
+                n => 2f64.powi(n.min(token.len()) as i32),
+            };
+            let leet_factor = if leet { 4.0 } else { 1.0 };
+
+            matches.push(Match {
+                start,
+                end,
+                guesses: rank as f64 * case_factor * leet_factor,
+                weakness: if rank <= COMMON_PASSWORDS.len() as u64 && end - start == chars.len() {
+                    Weakness::CommonPassword
+                } else {
+                    Weakness::DictionaryWord
+                },
+            });
+        }
+    }
+}
+
+fn sequence_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let mut start = 0;
+    while start + 2 < chars.len() {
+        let delta = chars[start + 1] as i32 - chars[start] as i32;
+        let same_class = |a: char, b: char| {
+            (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+                || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+                || (a.is_ascii_digit() && b.is_ascii_digit())
+        };
+
+        let mut end = start + 1;
+        while end < chars.len()
+            && delta.abs() == 1
+            && chars[end] as i32 - chars[end - 1] as i32 == delta
+            && same_class(chars[end - 1], chars[end])
+        {
+            end += 1;
+        }
+
+        if end - start >= 3 {
+            let first = chars[start];
+            let base = if matches!(first, 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+                4.0
+            } else if first.is_ascii_digit() {
+                10.0
+            } else {
+                26.0
+            };
+            let direction = if delta < 0 { 2.0 } else { 1.0 };
+            matches.push(Match {
+                start,
+                end,
+                guesses: base * (end - start) as f64 * direction,
+                weakness: Weakness::Sequence,
+            });
+            start = end;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+fn repeat_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start + 1;
+        while end < chars.len() && chars[end] == chars[start] {
+            end += 1;
+        }
+        if end - start >= 3 {
+            matches.push(Match {
+                start,
+                end,
+                guesses: BRUTEFORCE_CARDINALITY * (end - start) as f64,
+                weakness: Weakness::Repeat,
+            });
+        }
+        start = end;
+    }
+}
+
+fn keyboard_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    for row in KEYBOARD_ROWS {
+        let forward: Vec<char> = row.chars().collect();
+        let backward: Vec<char> = row.chars().rev().collect();
+        for start in 0..lower.len() {
+            // A run cannot be longer than the row it was typed on
+            for end in (start + 4..=lower.len().min(start + forward.len())).rev() {
+                let token = &lower[start..end];
+                let found = [&forward, &backward]
+                    .iter()
+                    .any(|line| line.windows(token.len()).any(|window| window == token));
+                if found {
+                    matches.push(Match {
+                        start,
+                        end,
+                        guesses: 40.0 * token.len() as f64,
+                        weakness: Weakness::KeyboardPattern,
+                    });
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn year_matches(chars: &[char], matches: &mut Vec<Match>) {
+    for start in 0..chars.len().saturating_sub(3) {
+        let token: String = chars[start..start + 4].iter().collect();
+        if let Ok(year) = token.parse::<u32>() {
+            if (1900..=2099).contains(&year) {
+                matches.push(Match {
+                    start,
+                    end: start + 4,
+                    guesses: 120.0,
+                    weakness: Weakness::Year,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_patterns_score_low() {
+        assert_eq!(estimate("password").score, 0);
+        assert_eq!(
+            estimate("password").weaknesses,
+            vec![Weakness::CommonPassword]
+        );
+        assert!(estimate("P@ssw0rd").score <= 1);
+        assert!(estimate("qwertyuiop").score <= 1);
+        assert!(estimate("abcdef123456").score <= 1);
+        assert!(estimate("zzzzzzzzzzzz").score <= 1);
+        assert!(estimate("Summer1987").weaknesses.contains(&Weakness::Year));
+    }
+
+    #[test]
+    fn test_random_and_passphrases_score_high() {
+        assert_eq!(estimate("").score, 0);
+        assert_eq!(estimate("vX7#qLp2!mRw9zTk").score, 4);
+        // Six diceware words are still strong even though each is a dictionary word
+        let passphrase = estimate("acid-brave-cloud-lunar-maple-vivid");
+        assert_eq!(passphrase.score, 4);
+        assert!(passphrase.weaknesses.contains(&Weakness::DictionaryWord));
+        // Pasted key material is only searched for substrings as long as a dictionary word
+        assert_eq!(estimate(&"vX7#qLp2!mRw9zTk".repeat(256)).score, 4);
+    }
+}
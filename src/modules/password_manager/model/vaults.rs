@@ -0,0 +1,135 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::{VaultError, FILE_PATH};
+
+/// Name given to the single vault kept before named vaults existed
+pub const DEFAULT_VAULT_NAME: &str = "default";
+
+const VAULT_EXTENSION: &str = "json";
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Checks that `name` can be used as a vault file name
+///
+/// Names are limited to letters, digits, `-` and `_` so they can never point
+/// outside the vault directory.
+pub fn validate_vault_name(name: &str) -> Result<(), VaultError> {
+    if name.is_empty() {
+        return Err(VaultError::InvalidName(
+            "the name cannot be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(VaultError::InvalidName(format!(
+            "the name is longer than {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(VaultError::InvalidName(
+            "use only letters, digits, '-' and '_'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Path of the vault file called `name` in `vault_dir`
+pub fn vault_path(vault_dir: &str, name: &str) -> String {
+    Path::new(vault_dir)
+        .join(format!("{}.{}", name, VAULT_EXTENSION))
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Names of the vaults in `vault_dir`, sorted; a missing directory has none
+pub fn list_vaults(vault_dir: &str) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(vault_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some(VAULT_EXTENSION) {
+            continue;
+        }
+        // Files that could not have been created here (temp files, odd names) are skipped
+        if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+            if validate_vault_name(name).is_ok() && path.is_file() {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Creates `vault_dir` if needed; on Unix it is only accessible by the current user
+pub fn create_vault_dir(vault_dir: &str) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(vault_dir)
+}
+
+/// Moves the old `passwords.json` in the working directory into `vault_dir` as the
+/// `default` vault
+///
+/// Returns the name it was given, or `None` if there was nothing to move. A `default`
+/// vault that already exists is never replaced.
+pub fn adopt_legacy_vault(vault_dir: &str) -> io::Result<Option<String>> {
+    let target = vault_path(vault_dir, DEFAULT_VAULT_NAME);
+    if !Path::new(FILE_PATH).is_file() || Path::new(&target).exists() {
+        return Ok(None);
+    }
+
+    create_vault_dir(vault_dir)?;
+    fs::rename(FILE_PATH, &target)?;
+    Ok(Some(DEFAULT_VAULT_NAME.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Vault;
+    use super::*;
+
+    #[test]
+    fn test_named_vaults_are_listed_and_keyed_separately() {
+        let dir = std::env::temp_dir().join(format!("toolbox-vaults-{}", std::process::id()));
+        let dir = dir.to_str().expect("temp path is not UTF-8").to_string();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(list_vaults(&dir).unwrap(), Vec::<String>::new());
+
+        create_vault_dir(&dir).unwrap();
+        Vault::create(&vault_path(&dir, "team-infra"), "infra-pw").unwrap();
+        Vault::create(&vault_path(&dir, "personal"), "personal-pw").unwrap();
+        fs::write(Path::new(&dir).join("notes.txt"), "not a vault").unwrap();
+
+        assert_eq!(list_vaults(&dir).unwrap(), ["personal", "team-infra"]);
+        assert!(Vault::unlock(&vault_path(&dir, "personal"), "personal-pw", 0).is_ok());
+        assert!(matches!(
+            Vault::unlock(&vault_path(&dir, "personal"), "infra-pw", 0),
+            Err(VaultError::WrongPassword)
+        ));
+
+        assert!(validate_vault_name("team-infra_2").is_ok());
+        for name in ["", "../escape", "with space", "a.b"] {
+            assert!(matches!(
+                validate_vault_name(name),
+                Err(VaultError::InvalidName(_))
+            ));
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
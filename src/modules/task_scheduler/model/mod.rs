@@ -13,6 +13,8 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
+use crate::core::SecretString;
+
 pub mod task;
 pub mod scheduler;
 
@@ -54,7 +56,7 @@ pub struct EmailConfig {
     pub smtp_server: String,
     pub smtp_port: u16,
     pub username: String,
-    pub password: String,
+    pub password: SecretString,
     pub retry_attempts: u32,
     pub retry_delay_seconds: u64,
 }
@@ -66,7 +68,7 @@ impl Default for EmailConfig {
             smtp_server: String::new(),
             smtp_port: 587,
             username: String::new(),
-            password: String::new(),
+            password: SecretString::new(),
             retry_attempts: 3,
             retry_delay_seconds: 5,
         }
@@ -205,7 +207,7 @@ impl TaskScheduler {
                 .map_err(|e| format!("Failed to create email: {}", e))?;
 
             // Create SMTP transport
-            let creds = Credentials::new(config.username.clone(), config.password.clone());
+            let creds = Credentials::new(config.username.clone(), config.password.to_string());
 
             // Use proper transport settings for different email providers
             let mailer = if config.smtp_server.contains("gmail") {
@@ -403,7 +405,7 @@ impl TaskScheduler {
             .map_err(|e| format!("Failed to create SMS email: {}", e))?;
 
         // Use the same SMTP transport as email
-        let creds = Credentials::new(email_config.username.clone(), email_config.password.clone());
+        let creds = Credentials::new(email_config.username.clone(), email_config.password.to_string());
         let mailer = self.create_smtp_transport(&email_config, &creds)?;
 
         match mailer.send(&email) {
@@ -798,7 +800,7 @@ impl TaskScheduler {
         println!("Username: {}", &config.username);
         println!("Password: {}", if config.password.is_empty() { "EMPTY!" } else { "******" });
         
-        let creds = Credentials::new(config.username.clone(), config.password.clone());
+        let creds = Credentials::new(config.username.clone(), config.password.to_string());
 
         // Create a properly configured transport with improved settings based on the provider
         let mailer = self.create_smtp_transport(&config, &creds)?;